@@ -16,9 +16,12 @@ Usage:
     python3 scripts/sync-snapshot.py --json             # JSON output
     python3 scripts/sync-snapshot.py --sample-rate 48000
     python3 scripts/sync-snapshot.py --hosts X Y        # Query specific hosts
+    python3 scripts/sync-snapshot.py --key SECRET       # Targets with time_query_key set
 """
 
 import argparse
+import hashlib
+import hmac
 import json
 import socket
 import struct
@@ -54,8 +57,11 @@ DEFAULT_REFERENCE = "strih.lan"
 
 PORT = 31900
 REQUEST_MAGIC = 0x4453594E  # "DSYN"
+EXTENDED_REQUEST_MAGIC = 0x44535958  # "DSYX": 80-byte response with [64-79]
+TAG_SIZE = 16  # truncated HMAC-SHA256 in shared-key mode
 RESPONSE_MAGIC = 0x44535952  # "DSYR"
-MODES = {0: "INIT", 1: "ACQ", 2: "PROD", 3: "LOCK", 4: "NANO", 5: "NTP-only"}
+MODES = {0: "INIT", 1: "ACQ", 2: "PROD", 3: "LOCK", 4: "NANO", 5: "NTP-only", 6: "FAULT"}
+ALGORITHMS = {0: "", 1: "rate-pi", 2: "kalman"}
 
 # =============================================================================
 # AUDIO SYNC THRESHOLDS (from controller.rs constants)
@@ -91,6 +97,10 @@ class TimeResponse:
     ntp_failed: bool = False
     settled: bool = False
     has_ntp_fields: bool = False  # True if remote sent non-zero NTP data
+    algorithm: str = ""  # byte [63], empty on older versions
+    # Offset detail (bytes [64-79], None on older versions)
+    raw_offset_ns: Optional[int] = None
+    smoothed_offset_ns: Optional[int] = None
     error: Optional[str] = None
 
 
@@ -98,13 +108,30 @@ class TimeResponse:
 # QUERY FUNCTIONS
 # =============================================================================
 
-def query_target(host: str, ip: str, timeout: float = 0.5) -> TimeResponse:
+def auth_tag(key: bytes, data: bytes) -> bytes:
+    """Truncated HMAC-SHA256 tag for shared-key mode."""
+    return hmac.new(key, data, hashlib.sha256).digest()[:TAG_SIZE]
+
+
+def query_target(host: str, ip: str, timeout: float = 0.5,
+                 key: Optional[bytes] = None) -> TimeResponse:
+    """Query the extended response; versions that don't know it stay silent."""
+    result = query_target_with(host, ip, EXTENDED_REQUEST_MAGIC, timeout, key)
+    if result.error == "Timeout":
+        result = query_target_with(host, ip, REQUEST_MAGIC, timeout, key)
+    return result
+
+
+def query_target_with(host: str, ip: str, magic: int, timeout: float = 0.5,
+                      key: Optional[bytes] = None) -> TimeResponse:
     """Send UDP time query and parse response."""
     sock = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
     sock.settimeout(timeout)
 
     request_id = int(time.time() * 1000) & 0xFFFFFFFF
-    request = struct.pack(">II", REQUEST_MAGIC, request_id)
+    request = struct.pack(">II", magic, request_id)
+    if key:
+        request += auth_tag(key, request)
 
     error_resp = lambda err: TimeResponse(
         host=host, ip=ip, system_time_ns=0, monotonic_counter=0,
@@ -115,12 +142,18 @@ def query_target(host: str, ip: str, timeout: float = 0.5) -> TimeResponse:
     try:
         t1 = time.perf_counter_ns()
         sock.sendto(request, (ip, PORT))
-        data, _ = sock.recvfrom(64)
+        data, _ = sock.recvfrom(128)
         t4 = time.perf_counter_ns()
 
         if len(data) < 64:
             return error_resp("Short response")
 
+        if key:
+            body, tag = data[:-TAG_SIZE], data[-TAG_SIZE:]
+            if len(body) < 64 or not hmac.compare_digest(tag, auth_tag(key, body)):
+                return error_resp("Bad response tag")
+            data = body
+
         magic, resp_id = struct.unpack(">II", data[0:8])
         if magic != RESPONSE_MAGIC or resp_id != request_id:
             return error_resp("Invalid response")
@@ -141,6 +174,12 @@ def query_target(host: str, ip: str, timeout: float = 0.5) -> TimeResponse:
         flags = data[62]
         ntp_failed = bool(flags & 0x01)
         settled = bool(flags & 0x02)
+        algorithm = ALGORITHMS.get(data[63], f"?{data[63]}")
+
+        # Raw + smoothed offset [64-79] (80-byte response)
+        raw_off = smoothed_off = None
+        if len(data) >= 80:
+            raw_off, smoothed_off = struct.unpack(">qq", data[64:80])
 
         # Detect whether remote has NTP fields (all-zero = old version)
         has_ntp = (ntp_off_us != 0 or accum_phase != 0 or flags != 0)
@@ -163,6 +202,9 @@ def query_target(host: str, ip: str, timeout: float = 0.5) -> TimeResponse:
             ntp_failed=ntp_failed,
             settled=settled,
             has_ntp_fields=has_ntp,
+            algorithm=algorithm,
+            raw_offset_ns=raw_off,
+            smoothed_offset_ns=smoothed_off,
         )
     except socket.timeout:
         return error_resp("Timeout")
@@ -172,13 +214,14 @@ def query_target(host: str, ip: str, timeout: float = 0.5) -> TimeResponse:
         sock.close()
 
 
-def query_all(targets: Dict[str, str], timeout: float = 0.5) -> List[TimeResponse]:
+def query_all(targets: Dict[str, str], timeout: float = 0.5,
+              key: Optional[bytes] = None) -> List[TimeResponse]:
     """Query all targets in parallel."""
     results: Dict[str, TimeResponse] = {}
     threads = []
 
     def worker(host: str, ip: str):
-        results[host] = query_target(host, ip, timeout)
+        results[host] = query_target(host, ip, timeout, key)
 
     for host, ip in targets.items():
         t = threading.Thread(target=worker, args=(host, ip))
@@ -469,6 +512,15 @@ def print_comprehensive(results: List[TimeResponse], reference: str, sample_rate
         print(f"{r.host:<15}{r.drift_rate_ppm:>+12.2f}  {r.freq_adj_ppm:>+12.2f}  "
               f"{offset_human:<18}{r.ptp_offset_ns:<16}{r.mode:<7}{r.gm_uuid:<22}{gm_match:<5}")
 
+    # Cross-machine spread of the smoothed offset (raw values are single noise samples)
+    smoothed = [r.smoothed_offset_ns for r in results
+                if not r.error and r.smoothed_offset_ns is not None and r.gm_uuid == ref_gm]
+    raw = [r.raw_offset_ns for r in results
+           if not r.error and r.raw_offset_ns is not None and r.gm_uuid == ref_gm]
+    if len(smoothed) >= 2:
+        print(f"  Smoothed offset spread: {format_ns_offset(max(smoothed) - min(smoothed))} "
+              f"across {len(smoothed)} hosts (raw: {format_ns_offset(max(raw) - min(raw))})")
+
     print()
 
     # ── Table 3: UTC Time Alignment (NTP + wall clock) ─────────────────────
@@ -715,6 +767,8 @@ Examples:
                        help="Output as JSON")
     parser.add_argument("--hosts", nargs="+",
                        help="Query specific hosts only")
+    parser.add_argument("--key",
+                       help="Shared key (ntp_server_mode.time_query_key of the targets)")
     args = parser.parse_args()
 
     # Select targets
@@ -734,7 +788,8 @@ Examples:
         targets = TARGETS
 
     # Query all targets
-    results = query_all(targets, args.timeout)
+    key = args.key.encode() if args.key else None
+    results = query_all(targets, args.timeout, key)
 
     # Output
     if args.json:
@@ -0,0 +1,173 @@
+//! Sync inter-arrival gate.
+//!
+//! When the OS stalls the receive path, the Syncs queued in the socket buffer are
+//! delivered in a burst afterwards. Their T2 reflects when the stall ended, not when
+//! the packet arrived. Such packets show up as an arrival interval that does not
+//! fit the master's (very regular) send interval, so they are rejected here before
+//! they reach the servo.
+
+/// Intervals used to learn the send interval before gating starts.
+const LEARN_INTERVALS: u32 = 8;
+/// Smoothing of the learned interval once gating is active.
+const INTERVAL_ALPHA: f64 = 0.05;
+/// Consecutive rejections after which the interval is re-learned
+/// (master changed its sync interval).
+const RELEARN_AFTER_REJECTS: u32 = 16;
+
+#[derive(Debug)]
+pub struct ArrivalGate {
+    /// Max deviation from the expected arrival (ns)
+    gate_ns: i64,
+    last_arrival_ns: Option<i64>,
+    mean_interval_ns: f64,
+    learned: u32,
+    consecutive_rejects: u32,
+    rejected: u64,
+}
+
+impl ArrivalGate {
+    pub fn new(gate_us: i64) -> Self {
+        Self {
+            gate_ns: gate_us.saturating_mul(1000),
+            last_arrival_ns: None,
+            mean_interval_ns: 0.0,
+            learned: 0,
+            consecutive_rejects: 0,
+            rejected: 0,
+        }
+    }
+
+    /// Check the arrival time (T2, ns) of the next Sync. Returns false if it
+    /// arrived off the learned cadence and its T2 should not be trusted.
+    ///
+    /// Intervals are measured from the last accepted arrival, so a delayed
+    /// packet does not shift the expected cadence of the ones after it.
+    pub fn accept(&mut self, t2_ns: i64) -> bool {
+        let Some(last) = self.last_arrival_ns else {
+            self.last_arrival_ns = Some(t2_ns);
+            return true;
+        };
+        let interval = (t2_ns - last) as f64;
+        if interval <= 0.0 {
+            // Clock stepped backwards or duplicate timestamp
+            return self.reject(t2_ns);
+        }
+
+        if self.learned < LEARN_INTERVALS {
+            self.learned += 1;
+            self.mean_interval_ns += (interval - self.mean_interval_ns) / self.learned as f64;
+            self.last_arrival_ns = Some(t2_ns);
+            return true;
+        }
+
+        // Lost Syncs leave a gap of whole intervals - that is not a delay
+        let periods = (interval / self.mean_interval_ns).round().max(1.0);
+        let deviation = interval - periods * self.mean_interval_ns;
+        if deviation.abs() > self.gate_ns as f64 {
+            return self.reject(t2_ns);
+        }
+
+        self.consecutive_rejects = 0;
+        if periods == 1.0 {
+            self.mean_interval_ns += (interval - self.mean_interval_ns) * INTERVAL_ALPHA;
+        }
+        self.last_arrival_ns = Some(t2_ns);
+        true
+    }
+
+    fn reject(&mut self, t2_ns: i64) -> bool {
+        self.rejected += 1;
+        self.consecutive_rejects += 1;
+        if self.consecutive_rejects >= RELEARN_AFTER_REJECTS {
+            // Cadence changed for good - learn it again from here
+            self.relearn();
+            self.last_arrival_ns = Some(t2_ns);
+        }
+        false
+    }
+
+    fn relearn(&mut self) {
+        self.mean_interval_ns = 0.0;
+        self.learned = 0;
+        self.consecutive_rejects = 0;
+    }
+
+    /// Forget the cadence (sync source changed).
+    pub fn reset(&mut self) {
+        self.last_arrival_ns = None;
+        self.relearn();
+    }
+
+    /// Learned send interval (ns), None while still learning.
+    pub fn mean_interval_ns(&self) -> Option<f64> {
+        (self.learned >= LEARN_INTERVALS).then_some(self.mean_interval_ns)
+    }
+
+    /// Total number of rejected arrivals.
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: i64 = 125_000_000; // Dante: 8 Syncs/s
+
+    fn learned_gate() -> (ArrivalGate, i64) {
+        let mut gate = ArrivalGate::new(2_000);
+        let mut t = 1_000_000_000;
+        for _ in 0..=LEARN_INTERVALS {
+            assert!(gate.accept(t));
+            t += INTERVAL;
+        }
+        (gate, t)
+    }
+
+    #[test]
+    fn test_regular_arrivals_accepted_with_small_jitter() {
+        let (mut gate, mut t) = learned_gate();
+        assert_eq!(gate.mean_interval_ns(), Some(INTERVAL as f64));
+        for i in 0..50 {
+            let jitter = if i % 2 == 0 { 300_000 } else { -300_000 };
+            assert!(gate.accept(t + jitter));
+            t += INTERVAL;
+        }
+        assert_eq!(gate.rejected(), 0);
+    }
+
+    #[test]
+    fn test_stall_burst_rejected() {
+        let (mut gate, t) = learned_gate();
+        // 40ms stall: the Sync due at t arrives 40ms late, the next one right after it
+        assert!(!gate.accept(t + 40_000_000));
+        assert!(!gate.accept(t + 40_100_000));
+        // Back on cadence
+        assert!(gate.accept(t + 2 * INTERVAL));
+        assert_eq!(gate.rejected(), 2);
+    }
+
+    #[test]
+    fn test_lost_sync_is_not_a_delay() {
+        let (mut gate, t) = learned_gate();
+        // One Sync lost: gap of two intervals
+        assert!(gate.accept(t + INTERVAL));
+        assert_eq!(gate.rejected(), 0);
+    }
+
+    #[test]
+    fn test_relearns_after_interval_change() {
+        let (mut gate, mut t) = learned_gate();
+        // Master switches to a 330ms sync interval (off the old cadence)
+        let new_interval = 330_000_000;
+        for _ in 0..(RELEARN_AFTER_REJECTS + LEARN_INTERVALS + 4) {
+            t += new_interval;
+            gate.accept(t);
+        }
+        let mean = gate.mean_interval_ns().unwrap();
+        assert!((mean - new_interval as f64).abs() < 1.0, "mean {}", mean);
+        t += new_interval;
+        assert!(gate.accept(t));
+    }
+}
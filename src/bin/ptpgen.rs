@@ -0,0 +1,167 @@
+//! PTPv1 test grandmaster: transmits Sync/FollowUp with controlled rate, jitter and
+//! frequency offset, so the full receive -> parse -> servo path of `dantesync` can be
+//! exercised on one machine.
+//!
+//! Multicast loopback is enabled on the sending socket, so a `dantesync --allow-loopback`
+//! on the same host receives the packets.
+//!
+//! T1 is the generator's simulated master clock: it starts at "uptime" 1000s (like
+//! Dante) and runs `--drift-ppm` faster than the local clock. Jitter delays the
+//! actual transmission after T1 was taken, like a busy sender or switch would.
+
+use clap::Parser;
+use dantesync::net;
+use dantesync::ptp::{self, PtpTimestamp};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Master uptime at generator start (ns)
+const START_UPTIME_NS: i64 = 1_000_000_000_000;
+
+#[derive(Parser, Debug)]
+#[command(about = "PTPv1 test grandmaster (loopback multicast) for end-to-end servo testing")]
+struct Args {
+    /// Interface IP to send on (default: auto-detected)
+    #[arg(long)]
+    interface: Option<Ipv4Addr>,
+
+    /// Sync messages per second
+    #[arg(long, default_value_t = 8.0)]
+    rate: f64,
+
+    /// Max random send delay (microseconds, uniform 0..jitter)
+    #[arg(long, default_value_t = 0)]
+    jitter_us: u64,
+
+    /// Master clock frequency offset vs the local clock (PPM)
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    drift_ppm: f64,
+
+    /// Send one-step Syncs (precise T1 in the Sync, no FollowUp)
+    #[arg(long, default_value_t = false)]
+    one_step: bool,
+
+    /// Stop after this many Syncs (default: run forever)
+    #[arg(long)]
+    count: Option<u64>,
+
+    /// Random seed for the jitter (reproducible runs)
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+}
+
+/// xorshift64* - reproducible jitter without a runtime RNG dependency
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+fn create_sender(interface_ip: Ipv4Addr) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0).into())?;
+    socket.set_multicast_if_v4(&interface_ip)?;
+    socket.set_multicast_loop_v4(true)?;
+    socket.set_multicast_ttl_v4(1)?;
+    Ok(socket.into())
+}
+
+fn main() {
+    let args = Args::parse();
+    if args.rate <= 0.0 {
+        eprintln!("--rate must be positive");
+        std::process::exit(2);
+    }
+
+    let interface_ip = match args.interface {
+        Some(ip) => ip,
+        None => match net::get_default_interface() {
+            Ok((_, ip)) => ip,
+            Err(e) => {
+                eprintln!("No interface found: {} (use --interface)", e);
+                std::process::exit(1);
+            }
+        },
+    };
+    let sock = match create_sender(interface_ip) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to create sender on {}: {}", interface_ip, e);
+            std::process::exit(1);
+        }
+    };
+
+    let group = Ipv4Addr::from(ptp::PTP_MULTICAST_ADDR);
+    let event_dst = SocketAddrV4::new(group, ptp::PTP_EVENT_PORT);
+    let general_dst = SocketAddrV4::new(group, ptp::PTP_GENERAL_PORT);
+    let uuid = [0x00, 0x1D, 0xC1, 0xFE, 0xED, 0x01];
+
+    println!("=== ptpgen ===");
+    println!(
+        "Sending on {} | {:.1} Hz | jitter 0..{}us | drift {:+.3} ppm | {}",
+        interface_ip,
+        args.rate,
+        args.jitter_us,
+        args.drift_ppm,
+        if args.one_step {
+            "one-step"
+        } else {
+            "two-step"
+        }
+    );
+
+    let interval = Duration::from_secs_f64(1.0 / args.rate);
+    let mut rng = Rng(args.seed.max(1));
+    let start = Instant::now();
+    let mut seq: u16 = 0;
+    let mut sent: u64 = 0;
+
+    loop {
+        if args.count.is_some_and(|c| sent >= c) {
+            break;
+        }
+
+        // Fixed cadence on the local clock
+        let due = start + interval * sent as u32;
+        if let Some(wait) = due.checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+
+        // T1 from the simulated master clock
+        let elapsed_ns = start.elapsed().as_nanos() as f64;
+        let t1 = PtpTimestamp::from_nanos(
+            START_UPTIME_NS + (elapsed_ns * (1.0 + args.drift_ppm / 1e6)) as i64,
+        );
+
+        if args.jitter_us > 0 {
+            let delay_us = rng.next_u64() % (args.jitter_us + 1);
+            thread::sleep(Duration::from_micros(delay_us));
+        }
+
+        let sync = ptp::encode_sync(uuid, seq, t1, !args.one_step);
+        if let Err(e) = sock.send_to(&sync, event_dst) {
+            eprintln!("Sync send failed: {}", e);
+        }
+        if !args.one_step {
+            let follow_up = ptp::encode_follow_up(uuid, seq, seq, t1);
+            if let Err(e) = sock.send_to(&follow_up, general_dst) {
+                eprintln!("FollowUp send failed: {}", e);
+            }
+        }
+
+        seq = seq.wrapping_add(1);
+        sent += 1;
+        if sent % (args.rate.ceil() as u64 * 10) == 0 {
+            println!("{} Syncs sent", sent);
+        }
+    }
+}
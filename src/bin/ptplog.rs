@@ -1,8 +1,9 @@
 //! Simple PTP packet logger - shows raw T1, T2, and offset without filtering
 
+use dantesync::clock::system_time_to_unix_nanos;
 use std::collections::HashMap;
 use std::net::UdpSocket;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime};
 
 fn main() {
     println!("=== PTP Raw Offset Logger ===\n");
@@ -36,8 +37,9 @@ fn main() {
     while count < 50 {
         // Check event socket (Sync messages)
         if let Ok((size, _)) = sock_event.recv_from(&mut buf) {
-            let t2 = SystemTime::now();
-            let t2_ns = t2.duration_since(UNIX_EPOCH).unwrap().as_nanos() as i64;
+            let Some(t2_ns) = system_time_to_unix_nanos(SystemTime::now()) else {
+                continue;
+            };
 
             if size >= 36 && (buf[0] & 0x0F) == 1 {
                 // PTPv1
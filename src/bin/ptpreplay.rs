@@ -0,0 +1,46 @@
+//! Offline analysis of a packet recording made with `dantesync --record <FILE>`.
+//! Compares application timestamp jitter with the kernel/driver timestamp jitter.
+
+use dantesync::recorder;
+
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(p) => p,
+        None => {
+            eprintln!("Usage: ptpreplay <recording.jsonl>");
+            std::process::exit(2);
+        }
+    };
+
+    let records = match recorder::read_records(&path) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to read recording {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("=== PTP Recording Analysis ===\n");
+    println!("File:             {}", path);
+    println!("Packets:          {}", records.len());
+    if let Some(first) = records.first() {
+        println!("Timestamp source: {}", first.source);
+    }
+
+    match recorder::analyze_jitter(&records) {
+        Some(report) => {
+            println!("Sync intervals:   {}\n", report.intervals);
+            println!(
+                "App-timestamp jitter was {:.1} us, kernel would have been {:.1} us",
+                report.app_jitter_ns / 1000.0,
+                report.kernel_jitter_ns / 1000.0
+            );
+            println!(
+                "App latency over kernel: mean {:.1} us, max {:.1} us",
+                report.mean_latency_ns / 1000.0,
+                report.max_latency_ns as f64 / 1000.0
+            );
+        }
+        None => println!("\nNot enough Sync messages to analyze (need at least 3)."),
+    }
+}
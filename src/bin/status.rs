@@ -0,0 +1,87 @@
+//! Print the status of the running dantesync service (read from its Unix status socket)
+
+#[cfg(not(unix))]
+fn main() {
+    println!("This utility is for Linux/macOS only (use dantesync-tray on Windows).");
+}
+
+#[cfg(unix)]
+fn main() {
+    use clap::Parser;
+    use dantesync::status::SyncStatus;
+    use dantesync::{ipc, status_socket};
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+
+    #[derive(Parser)]
+    #[command(about = "Print the status of the running dantesync service")]
+    struct Args {
+        /// Status socket of the service (its --status-socket)
+        #[arg(long, value_name = "PATH", default_value = status_socket::DEFAULT_STATUS_SOCKET)]
+        socket: PathBuf,
+
+        /// Print the full status as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    }
+
+    let args = Args::parse();
+
+    let status: SyncStatus = match UnixStream::connect(&args.socket)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut stream| {
+            stream.set_read_timeout(Some(ipc::FRAME_READ_TIMEOUT))?;
+            let payload = ipc::read_frame(&mut stream)?;
+            Ok(serde_json::from_slice(&payload)?)
+        }) {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!(
+                "Cannot read status from {}: {} (is dantesync running?)",
+                args.socket.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&status).expect("status serializes")
+        );
+        return;
+    }
+
+    println!("Mode:    {}", status.mode);
+    println!(
+        "Locked:  {}",
+        if status.is_locked {
+            "yes".to_string()
+        } else if status.settled {
+            "no (settled)".to_string()
+        } else if status.settling_threshold > 0 {
+            format!(
+                "no (settling {}/{})",
+                status.settling_count, status.settling_threshold
+            )
+        } else {
+            "no".to_string()
+        }
+    );
+    println!("Offset:  {:.3} us", status.offset_ns as f64 / 1000.0);
+    if let (Some(stddev), Some(p95)) = (status.offset_stddev_ns, status.offset_p95_ns) {
+        println!(
+            "Jitter:  {:.3} us stddev, {:.3} us p95 ({} samples, 60s)",
+            stddev as f64 / 1000.0,
+            p95 as f64 / 1000.0,
+            status.offset_stats_samples
+        );
+    }
+    println!("Drift:   {:+.3} ppm (oscillator)", status.drift_ppm);
+    println!("Adjust:  {:+.3} ppm", status.freq_adj_ppm);
+    println!("Rate:    {:+.3} ppm (smoothed)", status.smoothed_rate_ppm);
+    if let Some(ip) = status.gm_source_ip {
+        println!("Master:  {}", ip);
+    }
+}
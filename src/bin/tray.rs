@@ -7,6 +7,7 @@ fn main() {
 
 #[cfg(windows)]
 mod app {
+    use dantesync::ipc;
     use serde::Deserialize;
     use std::cell::RefCell;
     use std::sync::atomic::{AtomicBool, Ordering};
@@ -75,12 +76,18 @@ mod app {
         // Core fields
         pub offset_ns: i64,
         pub drift_ppm: f64,
+        #[serde(default)]
+        pub freq_adj_ppm: f64,
         #[serde(rename = "gm_uuid")]
         pub _gm_uuid: Option<[u8; 6]>,
         /// IP address of the PTP grandmaster device
         #[serde(default)]
         pub gm_source_ip: Option<std::net::Ipv4Addr>,
         pub settled: bool,
+        #[serde(default)]
+        pub settling_count: usize,
+        #[serde(default)]
+        pub settling_threshold: usize,
         #[serde(rename = "updated_ts")]
         pub _updated_ts: u64,
 
@@ -372,14 +379,33 @@ mod app {
                     {
                         Ok(mut client) => {
                             loop {
-                                let mut len_buf = [0u8; 4];
-                                if client.read_exact(&mut len_buf).await.is_err() {
-                                    break;
+                                let mut len_buf = [0u8; ipc::FRAME_HEADER_LEN];
+                                match tokio::time::timeout(
+                                    ipc::FRAME_READ_TIMEOUT,
+                                    client.read_exact(&mut len_buf),
+                                )
+                                .await
+                                {
+                                    Ok(Ok(_)) => {}
+                                    _ => break,
                                 }
-                                let len = u32::from_le_bytes(len_buf) as usize;
+                                // Absurd length = connected mid-frame: reconnect to resync
+                                let len = match ipc::decode_frame_len(len_buf) {
+                                    Ok(len) => len,
+                                    Err(e) => {
+                                        eprintln!("IPC framing error, reconnecting: {}", e);
+                                        break;
+                                    }
+                                };
                                 let mut buf = vec![0u8; len];
-                                if client.read_exact(&mut buf).await.is_err() {
-                                    break;
+                                match tokio::time::timeout(
+                                    ipc::FRAME_READ_TIMEOUT,
+                                    client.read_exact(&mut buf),
+                                )
+                                .await
+                                {
+                                    Ok(Ok(_)) => {}
+                                    _ => break,
                                 }
 
                                 match serde_json::from_slice::<SyncStatus>(&buf) {
@@ -559,10 +585,12 @@ mod app {
                             // STATUS TEXT
                             // ================================================
 
-                            let mode_str = if status.mode.is_empty() {
-                                if status.is_locked { "LOCK" } else { "ACQ" }
+                            let mode_str = if !status.settled && !is_ptp_offline && status.settling_threshold > 0 {
+                                format!("Settling {}/{}", status.settling_count, status.settling_threshold)
+                            } else if status.mode.is_empty() {
+                                if status.is_locked { "LOCK" } else { "ACQ" }.to_string()
                             } else {
-                                &status.mode
+                                status.mode.clone()
                             };
 
                             // Drift rate display (rate of change, not absolute offset)
@@ -582,12 +610,12 @@ mod app {
                             };
 
                             let tooltip = format!(
-                                "DanteSync v{}\nMode: {} | Drift: {}\nFreq Adj: {:+.1}ppm\nNTP Offset: {:+}us{}{}",
-                                version, mode_str, drift_str, status.drift_ppm, status.ntp_offset_us, phase_str, gm_str
+                                "DanteSync v{}\nMode: {} | Drift: {}\nFreq Adj: {:+.1}ppm (oscillator {:+.1}ppm)\nNTP Offset: {:+}us{}{}",
+                                version, mode_str, drift_str, status.freq_adj_ppm, status.drift_ppm, status.ntp_offset_us, phase_str, gm_str
                             );
 
                             let status_text = format!("{} | Drift: {}", mode_str, drift_str);
-                            let mode_text = format!("Mode: {} | Adj: {:+.1}ppm", mode_str, status.drift_ppm);
+                            let mode_text = format!("Mode: {} | Adj: {:+.1}ppm", mode_str, status.freq_adj_ppm);
 
                             // Format grandmaster IP for menu
                             let gm_ip_text = match &status.gm_source_ip {
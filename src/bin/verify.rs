@@ -0,0 +1,104 @@
+//! Query the time servers of several dantesync hosts and print how far apart
+//! their system clocks are.
+
+use clap::Parser;
+use dantesync::time_server::{TimeClient, TimeQueryResult, TIME_SERVER_PORT};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(about = "Check that dantesync hosts agree on the time (UDP time query, port 31900)")]
+struct Args {
+    /// Hosts to query (HOST or HOST:PORT)
+    #[arg(required = true, value_name = "HOST")]
+    hosts: Vec<String>,
+
+    /// Per-host response timeout in milliseconds
+    #[arg(long, default_value_t = 500)]
+    timeout_ms: u64,
+
+    /// Shared key of the hosts (ntp_server_mode.time_query_key)
+    #[arg(long)]
+    key: Option<String>,
+}
+
+fn resolve(host: &str) -> anyhow::Result<SocketAddr> {
+    let spec = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, TIME_SERVER_PORT)
+    };
+    spec.to_socket_addrs()?
+        .find(SocketAddr::is_ipv4)
+        .ok_or_else(|| anyhow::anyhow!("no IPv4 address"))
+}
+
+fn main() {
+    let args = Args::parse();
+    let timeout = Duration::from_millis(args.timeout_ms);
+
+    let mut client = match TimeClient::new() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Cannot open UDP socket: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(key) = &args.key {
+        client = client.with_auth_key(key.as_bytes());
+    }
+
+    println!(
+        "{:<24} {:<9} {:<7} {:>14} {:>10}",
+        "HOST", "MODE", "LOCKED", "VS LOCAL (us)", "RTT (us)"
+    );
+    let mut answered: Vec<(&str, TimeQueryResult)> = Vec::new();
+    let mut failed = 0;
+    for host in &args.hosts {
+        match resolve(host).and_then(|addr| client.query(addr, timeout)) {
+            Ok(result) => {
+                println!(
+                    "{:<24} {:<9} {:<7} {:>+14.1} {:>10.1}",
+                    host,
+                    result.mode,
+                    if result.is_locked { "yes" } else { "no" },
+                    result.offset_from_local_ns() as f64 / 1000.0,
+                    result.round_trip.as_nanos() as f64 / 1000.0
+                );
+                answered.push((host, result));
+            }
+            Err(e) => {
+                println!("{:<24} ERROR: {}", host, e);
+                failed += 1;
+            }
+        }
+    }
+
+    if answered.len() >= 2 {
+        // Offsets are each measured against this machine, so their difference
+        // is the spread between two hosts (± half of each round trip)
+        println!("\nPairwise system time spread:");
+        let mut worst: Option<(i64, &str, &str)> = None;
+        for (i, (a, ra)) in answered.iter().enumerate() {
+            for (b, rb) in &answered[i + 1..] {
+                let spread = ra.offset_from_local_ns() - rb.offset_from_local_ns();
+                println!("  {} - {}: {:+.1} us", a, b, spread as f64 / 1000.0);
+                if worst.map_or(true, |(w, _, _)| spread.abs() > w) {
+                    worst = Some((spread.abs(), a, b));
+                }
+            }
+        }
+        if let Some((spread, a, b)) = worst {
+            println!(
+                "Max spread: {:.1} us ({} - {})",
+                spread as f64 / 1000.0,
+                a,
+                b
+            );
+        }
+    }
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
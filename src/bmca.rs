@@ -0,0 +1,279 @@
+//! Master candidate tracking and selection.
+//!
+//! ## The Problem
+//! Most Dante installs have exactly one PTP master. Collecting candidates and running
+//! selection logic there is pure overhead, and a single stray packet from another
+//! device would trigger a sync source switch (soft reset).
+//!
+//! ## The Solution
+//! Track every Sync source UUID with its last-seen time. Selection (BMCA) only
+//! engages once at least `min_masters` distinct masters have been seen within
+//! `candidate_window`. Below that, the controller stays on the single-master fast
+//! path and locks to whatever master is sending, exactly as before.
+//!
+//! Once engaged, masters are ranked by the quality they announce (PTPv2 Announce:
+//! priority1, clockClass, clockAccuracy, variance, priority2), then by UUID. PTPv1
+//! (Dante) masters announce nothing and all rank equal. The selected master is
+//! kept until a better one appears or it is silent for `master_timeout`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// What a master announces about its clock, compared field by field in this
+/// order (lower is better), as in the IEEE 1588 dataset comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MasterQuality {
+    pub priority1: u8,
+    pub clock_class: u8,
+    pub clock_accuracy: u8,
+    pub offset_scaled_log_variance: u16,
+    pub priority2: u8,
+}
+
+impl Default for MasterQuality {
+    /// IEEE 1588 defaults: what a master that announces nothing ranks as
+    fn default() -> Self {
+        Self {
+            priority1: 128,
+            clock_class: 248,
+            clock_accuracy: 0xFE,
+            offset_scaled_log_variance: 0xFFFF,
+            priority2: 128,
+        }
+    }
+}
+
+/// Pick the master to follow among `candidates`.
+///
+/// The best quality wins, lowest UUID among equals. The current master is kept
+/// while it is a candidate and no candidate is strictly better (no flapping
+/// between equal masters).
+pub fn select_grandmaster(
+    candidates: &[([u8; 6], MasterQuality)],
+    current: Option<[u8; 6]>,
+) -> Option<[u8; 6]> {
+    let (best_uuid, best_quality) = candidates
+        .iter()
+        .min_by_key(|(uuid, quality)| (*quality, *uuid))
+        .copied()?;
+    match current.and_then(|cur| candidates.iter().find(|(uuid, _)| *uuid == cur)) {
+        Some(&(cur, quality)) if quality <= best_quality => Some(cur),
+        _ => Some(best_uuid),
+    }
+}
+
+/// Tracks observed PTP masters and decides when selection is needed.
+#[derive(Debug)]
+pub struct MasterTracker {
+    /// Last time a Sync was seen from each source UUID
+    last_seen: HashMap<[u8; 6], Instant>,
+    /// Announced quality of candidates (PTPv2 only)
+    quality: HashMap<[u8; 6], MasterQuality>,
+    /// Candidates not seen within this window are forgotten
+    candidate_window: Duration,
+    /// A master silent this long is not selectable
+    master_timeout: Duration,
+    /// Number of distinct masters required before selection engages
+    min_masters: usize,
+    /// Whether selection is currently engaged
+    engaged: bool,
+}
+
+impl MasterTracker {
+    pub fn new(min_masters: usize, candidate_window: Duration, master_timeout: Duration) -> Self {
+        Self {
+            last_seen: HashMap::new(),
+            quality: HashMap::new(),
+            candidate_window,
+            master_timeout,
+            min_masters: min_masters.max(2),
+            engaged: false,
+        }
+    }
+
+    /// Record a Sync from `uuid` at `now` and drop candidates outside the window.
+    pub fn observe(&mut self, uuid: [u8; 6], now: Instant) {
+        self.last_seen.insert(uuid, now);
+        let window = self.candidate_window;
+        self.last_seen
+            .retain(|_, seen| now.saturating_duration_since(*seen) <= window);
+        let last_seen = &self.last_seen;
+        self.quality.retain(|uuid, _| last_seen.contains_key(uuid));
+    }
+
+    /// Record the quality a candidate announced. Ignored until it has sent a Sync.
+    pub fn observe_quality(&mut self, uuid: [u8; 6], quality: MasterQuality) {
+        if self.last_seen.contains_key(&uuid) {
+            self.quality.insert(uuid, quality);
+        }
+    }
+
+    /// Number of distinct masters seen within the candidate window.
+    pub fn candidate_count(&self) -> usize {
+        self.last_seen.len()
+    }
+
+    /// Whether `uuid` is currently a known candidate.
+    pub fn is_candidate(&self, uuid: &[u8; 6]) -> bool {
+        self.last_seen.contains_key(uuid)
+    }
+
+    /// Update the engaged state. Returns `Some(new_state)` when it changed.
+    pub fn update_engaged(&mut self) -> Option<bool> {
+        let should_engage = self.candidate_count() >= self.min_masters;
+        if should_engage != self.engaged {
+            self.engaged = should_engage;
+            Some(should_engage)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    /// Pick the master to follow among candidates heard from within the master
+    /// timeout (see `select_grandmaster`).
+    pub fn select(&self, current: Option<[u8; 6]>, now: Instant) -> Option<[u8; 6]> {
+        let live: Vec<([u8; 6], MasterQuality)> = self
+            .last_seen
+            .iter()
+            .filter(|(_, seen)| now.saturating_duration_since(**seen) <= self.master_timeout)
+            .map(|(uuid, _)| (*uuid, self.quality.get(uuid).copied().unwrap_or_default()))
+            .collect();
+        select_grandmaster(&live, current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: [u8; 6] = [0x00, 0x1D, 0xC1, 0x00, 0x00, 0x0A];
+    const B: [u8; 6] = [0x00, 0x1D, 0xC1, 0x00, 0x00, 0x0B];
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_single_master_stays_on_fast_path() {
+        let mut tracker = MasterTracker::new(2, Duration::from_secs(10), TIMEOUT);
+        let now = Instant::now();
+        for i in 0..10 {
+            tracker.observe(A, now + Duration::from_millis(125 * i));
+        }
+        assert_eq!(tracker.update_engaged(), None, "Should not engage");
+        assert!(!tracker.is_engaged());
+    }
+
+    #[test]
+    fn test_second_master_engages_selection() {
+        let mut tracker = MasterTracker::new(2, Duration::from_secs(10), TIMEOUT);
+        let now = Instant::now();
+        tracker.observe(A, now);
+        tracker.observe(B, now + Duration::from_millis(10));
+        assert_eq!(tracker.update_engaged(), Some(true));
+        assert_eq!(
+            tracker.select(Some(B), now),
+            Some(B),
+            "Current master should be kept while still a candidate"
+        );
+        assert_eq!(
+            tracker.select(None, now),
+            Some(A),
+            "Lowest UUID wins otherwise"
+        );
+    }
+
+    #[test]
+    fn test_stale_candidate_disengages_selection() {
+        let mut tracker = MasterTracker::new(2, Duration::from_secs(10), TIMEOUT);
+        let now = Instant::now();
+        tracker.observe(B, now);
+        tracker.observe(A, now);
+        assert_eq!(tracker.update_engaged(), Some(true));
+
+        // Only A keeps sending; B falls out of the window
+        tracker.observe(A, now + Duration::from_secs(11));
+        assert_eq!(tracker.candidate_count(), 1);
+        assert_eq!(tracker.update_engaged(), Some(false));
+        assert_eq!(
+            tracker.select(Some(B), now + Duration::from_secs(11)),
+            Some(A)
+        );
+    }
+    #[test]
+    fn test_select_grandmaster_ranks_quality_then_uuid() {
+        let default = MasterQuality::default();
+        let better = MasterQuality {
+            priority1: 100,
+            ..default
+        };
+        let class_6 = MasterQuality {
+            clock_class: 6,
+            ..default
+        };
+
+        assert_eq!(select_grandmaster(&[], None), None);
+        assert_eq!(
+            select_grandmaster(&[(A, default), (B, better)], None),
+            Some(B)
+        );
+        // priority1 outranks clockClass
+        assert_eq!(
+            select_grandmaster(&[(A, class_6), (B, better)], Some(A)),
+            Some(B)
+        );
+        // Equal quality: keep the current one, else lowest UUID
+        assert_eq!(
+            select_grandmaster(&[(A, default), (B, default)], Some(B)),
+            Some(B)
+        );
+        assert_eq!(
+            select_grandmaster(&[(A, default), (B, default)], None),
+            Some(A)
+        );
+        // Current master no longer a candidate
+        assert_eq!(select_grandmaster(&[(B, default)], Some(A)), Some(B));
+    }
+
+    #[test]
+    fn test_interleaved_masters_track_one_until_it_goes_silent() {
+        let mut tracker = MasterTracker::new(2, Duration::from_secs(10), TIMEOUT);
+        let start = Instant::now();
+        let mut current = None;
+        let mut followed = Vec::new();
+
+        // Both masters interleave Syncs for 3s; B announces the better quality
+        for i in 0..24 {
+            let now = start + Duration::from_millis(125 * i);
+            for uuid in [A, B] {
+                tracker.observe(uuid, now);
+                tracker.observe_quality(
+                    B,
+                    MasterQuality {
+                        priority1: 1,
+                        ..Default::default()
+                    },
+                );
+                tracker.update_engaged();
+                current = tracker.select(current, now);
+                followed.push(current.unwrap());
+            }
+        }
+        assert!(tracker.is_engaged());
+        // Only the first Sync (from A, before B was seen) was followed from A
+        assert_eq!(followed[0], A);
+        assert!(followed[1..].iter().all(|uuid| *uuid == B));
+
+        // B goes silent: A is selected after the master timeout, not before
+        let last = start + Duration::from_millis(125 * 23);
+        tracker.observe(A, last + Duration::from_secs(4));
+        assert_eq!(
+            tracker.select(current, last + Duration::from_secs(4)),
+            Some(B)
+        );
+        let later = last + Duration::from_secs(6);
+        tracker.observe(A, later);
+        assert_eq!(tracker.select(current, later), Some(A));
+    }
+}
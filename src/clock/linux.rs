@@ -1,11 +1,13 @@
-use super::SystemClock;
+use super::{adjtime_slew_duration, SystemClock};
 use anyhow::{anyhow, Result};
-use libc::{self, adjtimex, settimeofday, timeval, timex, ADJ_FREQUENCY};
+use libc::{self, adjtimex, settimeofday, timeval, timex, ADJ_FREQUENCY, ADJ_OFFSET_SINGLESHOT};
 use std::mem;
 use std::time::Duration;
 
 pub struct LinuxClock {
     original_freq: i64,
+    /// Frequency reported back by adjtimex after the last ADJ_FREQUENCY (PPM)
+    accepted_ppm: Option<f64>,
 }
 
 impl LinuxClock {
@@ -20,6 +22,7 @@ impl LinuxClock {
 
         Ok(LinuxClock {
             original_freq: tx.freq,
+            accepted_ppm: None,
         })
     }
 }
@@ -38,6 +41,10 @@ impl SystemClock for LinuxClock {
             return Err(anyhow!("adjtimex failed to set frequency"));
         }
 
+        // adjtimex writes the resulting kernel state back into tx. The kernel silently
+        // clamps ADJ_FREQUENCY to its tolerance, so this is what is really applied.
+        self.accepted_ppm = Some(tx.freq as f64 / 65536.0);
+
         Ok(())
     }
 
@@ -75,6 +82,29 @@ impl SystemClock for LinuxClock {
         }
         Ok(())
     }
+
+    fn slew_offset(&mut self, offset: Duration, sign: i8) -> Result<Option<Duration>> {
+        let usec = offset.as_micros() as i64;
+
+        // Same as adjtime(2): the kernel slews this offset out at 500ppm on top of the
+        // ADJ_FREQUENCY correction, which is left untouched.
+        let mut tx: timex = unsafe { mem::zeroed() };
+        tx.modes = ADJ_OFFSET_SINGLESHOT;
+        tx.offset = if sign < 0 { -usec } else { usec } as libc::c_long;
+
+        let ret = unsafe { adjtimex(&mut tx) };
+        if ret < 0 {
+            return Err(anyhow!(
+                "adjtimex failed to slew offset: errno={}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(Some(adjtime_slew_duration(offset)))
+    }
+
+    fn accepted_frequency_ppm(&self) -> Option<f64> {
+        self.accepted_ppm
+    }
 }
 
 impl Drop for LinuxClock {
@@ -92,6 +122,23 @@ impl Drop for LinuxClock {
 
 #[cfg(test)]
 mod tests {
+    use crate::clock::adjtime_slew_duration;
+    use std::time::Duration;
+
+    /// The kernel slews 500us per second: 1ms takes 2s, 500ms takes ~17 minutes
+    #[test]
+    fn test_adjtime_slew_duration() {
+        assert_eq!(
+            adjtime_slew_duration(Duration::from_millis(1)),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            adjtime_slew_duration(Duration::from_millis(500)),
+            Duration::from_secs(1000)
+        );
+        assert_eq!(adjtime_slew_duration(Duration::ZERO), Duration::ZERO);
+    }
+
     /// Test PPM to freq_val conversion math
     /// The kernel uses freq_val = ppm * 65536 (16-bit fixed point)
     #[test]
@@ -128,7 +175,7 @@ mod tests {
         assert_eq!(freq_1ppm_direct, 65536);
 
         // -1ppm exactly
-        let freq_neg1ppm_direct = (-1.0_f64 * 65536.0) as i64;
+        let freq_neg1ppm_direct = (-65536.0_f64) as i64;
         assert_eq!(freq_neg1ppm_direct, -65536);
 
         // Verify the conversion formula is correct for boundary values
@@ -186,8 +233,6 @@ mod tests {
     /// Test step_clock offset calculation
     #[test]
     fn test_step_offset_calculation() {
-        use std::time::Duration;
-
         // Helper to compute new timeval from base + offset (same logic as step_clock)
         fn apply_step(base_sec: i64, base_usec: i64, offset: Duration, sign: i8) -> (i64, i64) {
             let offset_sec = offset.as_secs() as i64;
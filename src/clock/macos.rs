@@ -0,0 +1,162 @@
+use super::{adjtime_slew_duration, SystemClock};
+use anyhow::{anyhow, Result};
+use libc::{self, adjtime, c_long, ntp_adjtime, settimeofday, timeval, timex, MOD_FREQUENCY};
+use std::mem;
+use std::time::Duration;
+
+/// ntp_adjtime frequency units: PPM with a 16-bit binary fraction (same as Linux adjtimex)
+fn ppm_to_freq(ppm: f64) -> c_long {
+    (ppm * 65536.0) as c_long
+}
+
+fn freq_to_ppm(freq: c_long) -> f64 {
+    freq as f64 / 65536.0
+}
+
+pub struct MacosClock {
+    original_freq: c_long,
+    /// Frequency reported back by ntp_adjtime after the last MOD_FREQUENCY (PPM)
+    accepted_ppm: Option<f64>,
+}
+
+impl MacosClock {
+    pub fn new() -> Result<Self> {
+        let mut tx: timex = unsafe { mem::zeroed() };
+        tx.modes = 0; // Query mode, allowed without root
+
+        let ret = unsafe { ntp_adjtime(&mut tx) };
+        if ret < 0 {
+            return Err(anyhow!(
+                "ntp_adjtime failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        Ok(MacosClock {
+            original_freq: tx.freq,
+            accepted_ppm: None,
+        })
+    }
+}
+
+impl SystemClock for MacosClock {
+    fn adjust_frequency(&mut self, factor: f64) -> Result<()> {
+        let ppm = (factor - 1.0) * 1_000_000.0;
+
+        let mut tx: timex = unsafe { mem::zeroed() };
+        tx.modes = MOD_FREQUENCY;
+        tx.freq = ppm_to_freq(ppm);
+
+        let ret = unsafe { ntp_adjtime(&mut tx) };
+        if ret < 0 {
+            return Err(anyhow!(
+                "ntp_adjtime failed to set frequency (are you root?): {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        // Like adjtimex, the kernel clamps to its tolerance and writes the result back
+        self.accepted_ppm = Some(freq_to_ppm(tx.freq));
+
+        Ok(())
+    }
+
+    fn step_clock(&mut self, offset: Duration, sign: i8) -> Result<()> {
+        let mut tv: timeval = unsafe { mem::zeroed() };
+        unsafe { libc::gettimeofday(&mut tv, std::ptr::null_mut()) };
+
+        let offset_sec = offset.as_secs() as libc::time_t;
+        let offset_usec = offset.subsec_micros() as libc::suseconds_t;
+
+        if sign > 0 {
+            tv.tv_sec += offset_sec;
+            tv.tv_usec += offset_usec;
+        } else {
+            tv.tv_sec -= offset_sec;
+            tv.tv_usec -= offset_usec;
+        }
+
+        // Normalize
+        while tv.tv_usec >= 1_000_000 {
+            tv.tv_sec += 1;
+            tv.tv_usec -= 1_000_000;
+        }
+        while tv.tv_usec < 0 {
+            tv.tv_sec -= 1;
+            tv.tv_usec += 1_000_000;
+        }
+
+        let ret = unsafe { settimeofday(&tv, std::ptr::null()) };
+        if ret < 0 {
+            return Err(anyhow!(
+                "settimeofday failed: errno={}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+
+    fn slew_offset(&mut self, offset: Duration, sign: i8) -> Result<Option<Duration>> {
+        // Callers bound the offset well below 1s, where the kernel slews at 500ppm
+        let usec = offset.subsec_micros() as libc::suseconds_t;
+        let mut delta: timeval = unsafe { mem::zeroed() };
+        delta.tv_sec = if sign < 0 {
+            -(offset.as_secs() as libc::time_t)
+        } else {
+            offset.as_secs() as libc::time_t
+        };
+        delta.tv_usec = if sign < 0 { -usec } else { usec };
+
+        let ret = unsafe { adjtime(&delta, std::ptr::null_mut()) };
+        if ret < 0 {
+            return Err(anyhow!(
+                "adjtime failed: errno={}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(Some(adjtime_slew_duration(offset)))
+    }
+
+    fn accepted_frequency_ppm(&self) -> Option<f64> {
+        self.accepted_ppm
+    }
+}
+
+impl Drop for MacosClock {
+    fn drop(&mut self) {
+        let mut tx: timex = unsafe { mem::zeroed() };
+        tx.modes = MOD_FREQUENCY;
+        tx.freq = self.original_freq;
+        unsafe { ntp_adjtime(&mut tx) };
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reading the kernel state needs no privileges
+    #[test]
+    fn test_clock_constructs_without_root() {
+        let clock = MacosClock::new().expect("ntp_adjtime query");
+        assert_eq!(clock.accepted_frequency_ppm(), None);
+        // Don't let Drop write the frequency back (needs root)
+        mem::forget(clock);
+    }
+
+    /// Same 16-bit fixed point scaling as the Linux backend
+    #[test]
+    fn test_ppm_to_freq_conversion() {
+        assert_eq!(ppm_to_freq(0.0), 0);
+        assert_eq!(ppm_to_freq(1.0), 65536);
+        assert_eq!(ppm_to_freq(-1.0), -65536);
+        assert_eq!(ppm_to_freq(500.0), 32_768_000);
+        assert!((ppm_to_freq(100.0) - 6_553_600).abs() <= 1);
+        assert_eq!(freq_to_ppm(32_768_000), 500.0);
+        assert_eq!(freq_to_ppm(ppm_to_freq(-12.5)), -12.5);
+    }
+}
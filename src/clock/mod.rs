@@ -1,3 +1,5 @@
+#[cfg(any(windows, test))]
+use anyhow::anyhow;
 use anyhow::Result;
 
 #[cfg_attr(test, mockall::automock)]
@@ -11,6 +13,50 @@ pub trait SystemClock {
 
     /// Stepping the clock (for NTP initial sync)
     fn step_clock(&mut self, offset: std::time::Duration, sign: i8) -> Result<()>;
+
+    /// Remove an offset gradually instead of jumping (no time discontinuity for
+    /// applications). Returns how long the slew takes, or None if the platform has no
+    /// kernel slewing and stepped instead (the default).
+    ///
+    /// Slewing runs at a fixed, small rate, so callers must bound the offset - see
+    /// `filters.step_threshold_ns`. Issuing a new slew replaces any one still in progress.
+    fn slew_offset(
+        &mut self,
+        offset: std::time::Duration,
+        sign: i8,
+    ) -> Result<Option<std::time::Duration>> {
+        self.step_clock(offset, sign)?;
+        Ok(None)
+    }
+
+    /// Frequency (PPM) the OS actually accepted on the last `adjust_frequency`.
+    /// Differs from the request when the kernel clamps it. None if the platform
+    /// does not report it back.
+    fn accepted_frequency_ppm(&self) -> Option<f64> {
+        None
+    }
+}
+
+impl<C: SystemClock + ?Sized> SystemClock for Box<C> {
+    fn adjust_frequency(&mut self, factor: f64) -> Result<()> {
+        (**self).adjust_frequency(factor)
+    }
+
+    fn step_clock(&mut self, offset: std::time::Duration, sign: i8) -> Result<()> {
+        (**self).step_clock(offset, sign)
+    }
+
+    fn slew_offset(
+        &mut self,
+        offset: std::time::Duration,
+        sign: i8,
+    ) -> Result<Option<std::time::Duration>> {
+        (**self).slew_offset(offset, sign)
+    }
+
+    fn accepted_frequency_ppm(&self) -> Option<f64> {
+        (**self).accepted_frequency_ppm()
+    }
 }
 
 #[cfg(windows)]
@@ -18,7 +64,123 @@ mod windows;
 #[cfg(windows)]
 pub use self::windows::WindowsClock as PlatformClock;
 
-#[cfg(unix)]
+#[cfg(target_os = "linux")]
 mod linux;
-#[cfg(unix)]
+#[cfg(target_os = "linux")]
 pub use self::linux::LinuxClock as PlatformClock;
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "macos")]
+pub use self::macos::MacosClock as PlatformClock;
+
+/// Rate at which the kernel works off an adjtime-style offset: 500us per second
+/// (Linux ADJ_OFFSET_SINGLESHOT, macOS adjtime below 1s).
+#[cfg(unix)]
+const ADJTIME_SLEW_PPM: f64 = 500.0;
+
+/// How long the kernel needs to slew away `offset` (see `ADJTIME_SLEW_PPM`).
+#[cfg(unix)]
+fn adjtime_slew_duration(offset: std::time::Duration) -> std::time::Duration {
+    std::time::Duration::from_secs_f64(offset.as_secs_f64() * 1e6 / ADJTIME_SLEW_PPM)
+}
+
+/// Consecutive failed read-backs before `adjust_frequency` reports an error.
+#[cfg(any(windows, test))]
+const VERIFY_FAILURE_LIMIT: u32 = 5;
+
+/// Read-back check of frequency adjustments (Windows). One mismatch can be a race
+/// with another writer; persistent ones mean the clock is not being disciplined.
+#[cfg(any(windows, test))]
+#[derive(Debug, Default)]
+struct AdjustmentVerifier {
+    consecutive_failures: u32,
+}
+
+#[cfg(any(windows, test))]
+impl AdjustmentVerifier {
+    /// One read-back: the adjustment `actual`ly in effect after requesting
+    /// `requested`, and whether the OS reports adjustment disabled.
+    fn check(&mut self, requested: u64, actual: u64, disabled: bool) -> Result<()> {
+        if actual == requested && !disabled {
+            self.consecutive_failures = 0;
+            return Ok(());
+        }
+        self.consecutive_failures += 1;
+        if self.consecutive_failures < VERIFY_FAILURE_LIMIT {
+            return Ok(());
+        }
+        let what = if disabled {
+            "time adjustment was found disabled".to_string()
+        } else {
+            format!(
+                "adjustment read back as {} instead of {}",
+                actual, requested
+            )
+        };
+        Err(anyhow!(
+            "frequency adjustment not taking effect: {} on {} consecutive updates \
+             (another time service adjusting the clock?)",
+            what,
+            self.consecutive_failures
+        ))
+    }
+}
+
+mod null;
+pub use self::null::NullClock;
+
+/// Nanoseconds since the Unix epoch. None for a time before the epoch (a clock
+/// briefly set wrong by a bad step) or beyond i64 (year 2262): such a timestamp
+/// must be skipped, not read as 0.
+pub fn system_time_to_unix_nanos(t: std::time::SystemTime) -> Option<i64> {
+    let since_epoch = t.duration_since(std::time::UNIX_EPOCH).ok()?;
+    i64::try_from(since_epoch.as_nanos()).ok()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verifier_errs_only_on_persistent_failures() {
+        let mut verifier = AdjustmentVerifier::default();
+        for _ in 1..VERIFY_FAILURE_LIMIT {
+            assert!(verifier.check(100, 90, false).is_ok(), "tolerated");
+        }
+        // A good read-back in between starts the count over
+        assert!(verifier.check(100, 100, false).is_ok());
+        for _ in 1..VERIFY_FAILURE_LIMIT {
+            assert!(verifier.check(100, 100, true).is_ok());
+        }
+        let err = verifier.check(100, 100, true).unwrap_err();
+        assert!(err.to_string().contains("disabled"), "{}", err);
+        assert!(verifier.check(100, 90, false).is_err(), "stays failed");
+        assert!(verifier.check(100, 100, false).is_ok(), "recovers");
+    }
+
+    #[test]
+    fn test_system_time_to_unix_nanos() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        assert_eq!(system_time_to_unix_nanos(UNIX_EPOCH), Some(0));
+        assert_eq!(
+            system_time_to_unix_nanos(UNIX_EPOCH + Duration::new(1_700_000_000, 5)),
+            Some(1_700_000_000_000_000_005)
+        );
+        // Pre-epoch: skipped, not 0
+        if let Some(before) = UNIX_EPOCH.checked_sub(Duration::from_nanos(1)) {
+            assert_eq!(system_time_to_unix_nanos(before), None);
+        }
+        if let Some(before) = UNIX_EPOCH.checked_sub(Duration::from_secs(86_400)) {
+            assert_eq!(system_time_to_unix_nanos(before), None);
+        }
+        if let Some(far) = UNIX_EPOCH.checked_add(Duration::from_secs(i64::MAX as u64)) {
+            assert_eq!(system_time_to_unix_nanos(far), None, "beyond i64");
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use super::SystemClock;
+use anyhow::Result;
+use log::{debug, info};
+use std::time::Duration;
+
+/// Clock that ignores every adjustment: monitor-only and dry-run operation, no
+/// privileges needed. Requested adjustments are logged and the last factor kept.
+#[derive(Debug, Default)]
+pub struct NullClock {
+    last_factor: Option<f64>,
+}
+
+impl NullClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Factor of the last `adjust_frequency` call, None if there was none.
+    pub fn last_factor(&self) -> Option<f64> {
+        self.last_factor
+    }
+}
+
+impl SystemClock for NullClock {
+    fn adjust_frequency(&mut self, factor: f64) -> Result<()> {
+        // Every servo update lands here: debug only
+        debug!(
+            "[DryRun] Would adjust frequency: factor {:.9} ({:+.3}ppm)",
+            factor,
+            (factor - 1.0) * 1e6
+        );
+        self.last_factor = Some(factor);
+        Ok(())
+    }
+
+    fn step_clock(&mut self, offset: Duration, sign: i8) -> Result<()> {
+        info!(
+            "[DryRun] Would step clock {}{:?}",
+            if sign > 0 { "+" } else { "-" },
+            offset
+        );
+        Ok(())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_last_factor() {
+        let mut clock = NullClock::new();
+        assert_eq!(clock.last_factor(), None);
+
+        clock.adjust_frequency(1.000_012).unwrap();
+        clock.step_clock(Duration::from_millis(5), -1).unwrap();
+        clock.adjust_frequency(0.999_99).unwrap();
+        assert_eq!(clock.last_factor(), Some(0.999_99));
+        assert_eq!(clock.accepted_frequency_ppm(), None, "nothing was applied");
+    }
+}
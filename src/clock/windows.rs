@@ -1,9 +1,11 @@
-//! Windows clock control using SetSystemTimeAdjustmentPrecise (64-bit Precise API).
+//! Windows clock control using SetSystemTimeAdjustmentPrecise (64-bit Precise API),
+//! falling back to the legacy SetSystemTimeAdjustment (100ns units) where the
+//! Precise API is not available.
 //!
 //! This module includes comprehensive diagnostics to verify that frequency
 //! adjustment actually affects clock speed.
 
-use super::SystemClock;
+use super::{AdjustmentVerifier, SystemClock};
 use anyhow::{anyhow, Result};
 use log::{debug, error, info, warn};
 use std::time::{Duration, Instant};
@@ -17,13 +19,70 @@ use windows::Win32::Security::{
 };
 use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
 use windows::Win32::System::SystemInformation::{
-    GetSystemTimeAdjustmentPrecise, GetSystemTimeAsFileTime, SetSystemTime,
-    SetSystemTimeAdjustmentPrecise,
+    GetSystemTimeAdjustment, GetSystemTimeAdjustmentPrecise, GetSystemTimeAsFileTime,
+    SetSystemTime, SetSystemTimeAdjustment, SetSystemTimeAdjustmentPrecise,
 };
 use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
 use windows::Win32::System::Time::FileTimeToSystemTime;
+use windows_service::service::ServiceState;
+
+/// Which time adjustment API this system accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AdjustmentApi {
+    /// SetSystemTimeAdjustmentPrecise (Windows 10+): finer resolution
+    Precise,
+    /// SetSystemTimeAdjustment: 100ns units per clock interrupt
+    Legacy,
+}
+
+impl AdjustmentApi {
+    /// (adjustment, nominal increment, adjustment disabled)
+    fn get(self) -> Result<(u64, u64, bool)> {
+        let mut disabled = BOOL(0);
+        unsafe {
+            match self {
+                AdjustmentApi::Precise => {
+                    let (mut adj, mut inc) = (0u64, 0u64);
+                    GetSystemTimeAdjustmentPrecise(&mut adj, &mut inc, &mut disabled)?;
+                    Ok((adj, inc, disabled.as_bool()))
+                }
+                AdjustmentApi::Legacy => {
+                    let (mut adj, mut inc) = (0u32, 0u32);
+                    GetSystemTimeAdjustment(&mut adj, &mut inc, &mut disabled)?;
+                    Ok((adj as u64, inc as u64, disabled.as_bool()))
+                }
+            }
+        }
+    }
+
+    /// Set the adjustment and enable it
+    fn set(self, adjustment: u64) -> Result<()> {
+        unsafe {
+            match self {
+                AdjustmentApi::Precise => SetSystemTimeAdjustmentPrecise(adjustment, false)?,
+                AdjustmentApi::Legacy => SetSystemTimeAdjustment(adjustment as u32, false)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adjustment value that runs the clock `ppm` away from nominal.
+///
+/// Precise API: INVERTED - increasing the adjustment slows the clock, so positive
+/// PPM (speed up) DECREASES it; one PPM is `perf_frequency / 1e6` units.
+/// Legacy API: the adjustment is the time added per interrupt, so positive PPM
+/// increases it by `ppm` millionths of the increment.
+fn adjustment_for_ppm(api: AdjustmentApi, increment: u64, perf_frequency: i64, ppm: f64) -> u64 {
+    let delta = match api {
+        AdjustmentApi::Precise => (-ppm * perf_frequency as f64 / 1_000_000.0).round() as i64,
+        AdjustmentApi::Legacy => (ppm * increment as f64 / 1_000_000.0).round() as i64,
+    };
+    (increment as i64 + delta) as u64
+}
 
 pub struct WindowsClock {
+    api: AdjustmentApi,
     original_increment: u64,
     perf_frequency: i64,
 
@@ -36,10 +95,21 @@ pub struct WindowsClock {
     baseline_perf_counter: i64,
     baseline_filetime: u64,
     last_measurement_time: Instant,
+
+    /// Re-enable time adjustment when found disabled (false: respect policy, fail)
+    force_enable_adjustment: bool,
+    /// Persistent read-back mismatches turn into an error
+    verifier: AdjustmentVerifier,
 }
 
 impl WindowsClock {
     pub fn new() -> Result<Self> {
+        Self::with_force_enable(true)
+    }
+
+    /// `force_enable_adjustment`: if time adjustment is disabled (e.g. by a managed
+    /// time policy), enable it with the nominal value. When false, fail instead.
+    pub fn with_force_enable(force_enable_adjustment: bool) -> Result<Self> {
         Self::enable_privilege("SeSystemtimePrivilege")?;
 
         // Get performance counter frequency
@@ -48,13 +118,18 @@ impl WindowsClock {
             QueryPerformanceFrequency(&mut perf_freq)?;
         }
 
-        let mut adj = 0u64;
-        let mut inc = 0u64;
-        let mut disabled = BOOL(0);
-
-        unsafe {
-            GetSystemTimeAdjustmentPrecise(&mut adj, &mut inc, &mut disabled)?;
-        }
+        // Prefer the Precise API; older systems only have the 100ns one
+        let (api, (adj, inc, disabled)) = match AdjustmentApi::Precise.get() {
+            Ok(state) => (AdjustmentApi::Precise, state),
+            Err(e) => {
+                warn!(
+                    "[Clock] GetSystemTimeAdjustmentPrecise failed ({}) - using the legacy 100ns API",
+                    e
+                );
+                (AdjustmentApi::Legacy, AdjustmentApi::Legacy.get()?)
+            }
+        };
+        info!("[Clock] Time adjustment API: {:?}", api);
 
         // Calculate PPM sensitivity
         let ppm_per_unit = 1_000_000.0 / inc as f64;
@@ -71,13 +146,20 @@ impl WindowsClock {
             current_ppm, adj, inc
         );
 
-        // Enable adjustment if disabled
-        if disabled.as_bool() {
-            warn!("Time adjustment was DISABLED! Enabling...");
-            unsafe {
-                SetSystemTimeAdjustmentPrecise(inc, false)?;
+        // Enable adjustment if disabled (unless the policy must be respected)
+        if disabled {
+            if !force_enable_adjustment {
+                error!("[Clock] Time adjustment is DISABLED and --force-enable-adjustment is off - not overriding it");
+                return Err(anyhow!(
+                    "system time adjustment is disabled (possibly by a managed time policy); \
+                     enable it or run with --force-enable-adjustment true"
+                ));
             }
+            warn!("Time adjustment was DISABLED! Enabling (--force-enable-adjustment)...");
+            api.set(inc)?;
             info!("Time adjustment ENABLED with nominal value.");
+        } else {
+            info!("[Clock] Time adjustment already enabled - nothing to override");
         }
 
         // Get baseline measurements
@@ -90,6 +172,7 @@ impl WindowsClock {
         };
 
         let clock = WindowsClock {
+            api,
             original_increment: inc,
             perf_frequency: perf_freq,
             adjustment_count: 0,
@@ -98,6 +181,8 @@ impl WindowsClock {
             baseline_perf_counter: baseline_pc,
             baseline_filetime: baseline_ft,
             last_measurement_time: Instant::now(),
+            force_enable_adjustment,
+            verifier: AdjustmentVerifier::default(),
         };
 
         // Check for interfering processes
@@ -113,44 +198,23 @@ impl WindowsClock {
         info!("");
         info!("Checking for interfering processes...");
 
-        // Check if W32Time service is running
-        let w32time_check = std::process::Command::new("sc")
-            .args(["query", "w32time"])
-            .output();
-
-        match w32time_check {
-            Ok(output) => {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                if output_str.contains("RUNNING") {
-                    error!(
-                        "⚠ W32Time service is RUNNING! This will interfere with time adjustment."
-                    );
-                    error!("  Run: net stop w32time");
-                } else if output_str.contains("STOPPED") {
-                    info!("✓ W32Time service is stopped.");
-                } else {
-                    info!(
-                        "  W32Time status: {}",
-                        output_str.lines().next().unwrap_or("unknown")
-                    );
-                }
-            }
-            Err(e) => {
-                warn!("Could not check W32Time status: {}", e);
+        // Check if W32Time service is running (Service Control Manager)
+        match crate::w32time::state() {
+            Ok(Some(ServiceState::Running | ServiceState::StartPending)) => {
+                error!("⚠ W32Time service is RUNNING! This will interfere with time adjustment.");
+                error!("  Run: net stop w32time (or start DanteSync with --stop-w32time)");
             }
+            Ok(Some(ServiceState::Stopped)) | Ok(None) => info!("✓ W32Time service is stopped."),
+            Ok(Some(state)) => info!("  W32Time status: {:?}", state),
+            Err(e) => warn!("Could not check W32Time status: {:#}", e),
         }
 
         // Check current adjustment state
-        let mut adj = 0u64;
-        let mut inc = 0u64;
-        let mut disabled = BOOL(0);
-        unsafe {
-            if GetSystemTimeAdjustmentPrecise(&mut adj, &mut inc, &mut disabled).is_ok() {
-                if disabled.as_bool() {
-                    error!("⚠ Time adjustment is DISABLED! Another process may have disabled it.");
-                } else {
-                    info!("✓ Time adjustment is enabled.");
-                }
+        if let Ok((_, _, disabled)) = self.api.get() {
+            if disabled {
+                error!("⚠ Time adjustment is DISABLED! Another process may have disabled it.");
+            } else {
+                info!("✓ Time adjustment is enabled.");
             }
         }
         info!("");
@@ -255,14 +319,19 @@ impl WindowsClock {
 }
 
 impl SystemClock for WindowsClock {
+    /// Apply a frequency factor relative to the nominal increment captured in `new()`.
+    ///
+    /// Timer resolution: the daemon requests 1ms resolution (timeBeginPeriod) before
+    /// this clock is created, unless --no-high-res-timer is given. The nominal increment
+    /// is read once at startup, so changing the resolution afterwards (e.g. another
+    /// process calling timeBeginPeriod) leaves `original_increment` as the baseline;
+    /// the verify step below logs a MISMATCH if Windows does not accept the new value.
+    /// Mismatches or disabled adjustment on several consecutive updates return an error.
     fn adjust_frequency(&mut self, factor: f64) -> Result<()> {
         let ppm = (factor - 1.0) * 1_000_000.0;
 
-        // Calculate adjustment delta
-        // NOTE: Windows has INVERTED behavior - increasing adjustment slows the clock!
-        // To speed up (positive PPM), we must DECREASE the adjustment value.
-        let adjustment_delta = (-ppm * self.perf_frequency as f64 / 1_000_000.0).round() as i64;
-        let new_adj = (self.original_increment as i64 + adjustment_delta) as u64;
+        let new_adj =
+            adjustment_for_ppm(self.api, self.original_increment, self.perf_frequency, ppm);
 
         self.adjustment_count += 1;
 
@@ -275,34 +344,29 @@ impl SystemClock for WindowsClock {
             self.adjustment_count, ppm, self.last_adjustment, new_adj, delta_from_nominal
         );
 
-        unsafe {
-            // Apply adjustment
-            SetSystemTimeAdjustmentPrecise(new_adj, false)?;
-
-            // Verify
-            let mut verify_adj = 0u64;
-            let mut verify_inc = 0u64;
-            let mut verify_disabled = BOOL(0);
-
-            if GetSystemTimeAdjustmentPrecise(
-                &mut verify_adj,
-                &mut verify_inc,
-                &mut verify_disabled,
-            )
-            .is_ok()
-            {
-                if verify_adj != new_adj {
-                    error!(
-                        "[FreqAdj] MISMATCH! Requested={}, Actual={}",
-                        new_adj, verify_adj
-                    );
-                }
-                if verify_disabled.as_bool() {
-                    error!("[FreqAdj] TIME ADJUSTMENT DISABLED! Interference detected!");
-                    // Try to re-enable
-                    let _ = SetSystemTimeAdjustmentPrecise(new_adj, false);
+        // Apply adjustment
+        self.api.set(new_adj)?;
+
+        // Verify
+        let mut verified = Ok(());
+        if let Ok((verify_adj, _, verify_disabled)) = self.api.get() {
+            if verify_adj != new_adj {
+                error!(
+                    "[FreqAdj] MISMATCH! Requested={}, Actual={}",
+                    new_adj, verify_adj
+                );
+            }
+            if verify_disabled {
+                error!("[FreqAdj] TIME ADJUSTMENT DISABLED! Interference detected!");
+                if !self.force_enable_adjustment {
+                    return Err(anyhow!(
+                        "time adjustment was disabled by another process (not re-enabling)"
+                    ));
                 }
+                // Try to re-enable
+                let _ = self.api.set(new_adj);
             }
+            verified = self.verifier.check(new_adj, verify_adj, verify_disabled);
         }
 
         self.last_adjustment = new_adj;
@@ -311,7 +375,7 @@ impl SystemClock for WindowsClock {
         // Periodic effectiveness measurement
         self.measure_and_log_effectiveness();
 
-        Ok(())
+        verified
     }
 
     fn step_clock(&mut self, offset: Duration, sign: i8) -> Result<()> {
@@ -383,17 +447,58 @@ impl Drop for WindowsClock {
             self.adjustment_count
         );
 
-        unsafe {
-            match SetSystemTimeAdjustmentPrecise(self.original_increment, false) {
-                Ok(_) => info!("Clock reset to nominal successfully."),
-                Err(e) => error!("Failed to reset clock: {}", e),
-            }
+        match self.api.set(self.original_increment) {
+            Ok(_) => info!("Clock reset to nominal successfully."),
+            Err(e) => error!("Failed to reset clock: {}", e),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{adjustment_for_ppm, AdjustmentApi};
+
+    /// WindowsClock must move the adjustment by the same amount clocktest does for
+    /// the same PPM (clocktest: delta = ppm * perf_frequency / 1e6), with the
+    /// sign inverted for the Precise API.
+    #[test]
+    fn test_adjustment_for_500ppm_matches_clocktest() {
+        let inc: u64 = 156_250;
+        let perf_frequency: i64 = 10_000_000;
+        let clocktest_delta = |ppm: f64| (ppm * perf_frequency as f64 / 1_000_000.0).round() as i64;
+
+        for ppm in [500.0, -500.0] {
+            let adj = adjustment_for_ppm(AdjustmentApi::Precise, inc, perf_frequency, ppm);
+            assert_eq!(adj as i64 - inc as i64, -clocktest_delta(ppm));
+        }
+        assert_eq!(
+            adjustment_for_ppm(AdjustmentApi::Precise, inc, perf_frequency, 500.0),
+            151_250
+        );
+        assert_eq!(
+            adjustment_for_ppm(AdjustmentApi::Precise, inc, perf_frequency, -500.0),
+            161_250
+        );
+        assert_eq!(
+            adjustment_for_ppm(AdjustmentApi::Precise, inc, perf_frequency, 0.0),
+            inc
+        );
+    }
+
+    /// Legacy API: 100ns units relative to the increment, not inverted
+    #[test]
+    fn test_legacy_adjustment_for_ppm() {
+        let inc: u64 = 156_250;
+        assert_eq!(
+            adjustment_for_ppm(AdjustmentApi::Legacy, inc, 10_000_000, 500.0),
+            156_328
+        );
+        assert_eq!(
+            adjustment_for_ppm(AdjustmentApi::Legacy, inc, 10_000_000, -500.0),
+            156_172
+        );
+    }
+
     /// Test PPM to frequency adjustment delta conversion
     ///
     /// Windows frequency adjustment formula:
@@ -1,9 +1,491 @@
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemConfig {
     pub servo: ServoConfig,
     pub filters: FilterConfig,
+    /// Master selection (optional - single-master fast path if omitted)
+    #[serde(default)]
+    pub bmca: BmcaConfig,
+    /// Clock authority fallbacks (optional - defaults if omitted)
+    #[serde(default)]
+    pub clock: ClockConfig,
+    /// Grandmaster restart handling (optional - defaults if omitted)
+    #[serde(default)]
+    pub sequence: SequenceConfig,
+    /// Post-lock convergence verification (optional - defaults if omitted)
+    #[serde(default)]
+    pub convergence: ConvergenceConfig,
+    /// Repeated-reset FAULT detection (optional - defaults if omitted)
+    #[serde(default)]
+    pub fault: FaultConfig,
+    /// PTP message handling (optional - defaults if omitted)
+    #[serde(default)]
+    pub ptp: PtpConfig,
+    /// NTP discipline (optional - PTP primary if omitted)
+    #[serde(default)]
+    pub ntp: NtpConfig,
+}
+
+/// Frequency discipline algorithm (`servo.algorithm`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServoAlgorithm {
+    /// Rate-based PI servo with adaptive gains (ACQ/PROD/NANO)
+    #[default]
+    Pi,
+    /// Two-state (phase + frequency) Kalman filter, see `servo::KalmanServo`
+    Kalman,
+}
+
+/// Where T1 (master send time) comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum T1Source {
+    /// Two-step: precise origin timestamp from the FollowUp (Dante). A master
+    /// sending Syncs with origin timestamps but never a FollowUp is detected as
+    /// one-step and its Sync origin used instead
+    #[default]
+    FollowUp,
+    /// One-step: origin timestamp of the Sync itself, no FollowUp expected
+    Sync,
+    /// Per message: Sync origin unless the header flags a two-step sender (or
+    /// the master is detected as one-step, as above)
+    Auto,
+}
+
+/// What the lock verify gate checks on each sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockCriterion {
+    /// Filtered phase offset held within `lock_offset_ns`
+    #[default]
+    Offset,
+    /// Offset jitter (stddev of recent raw offsets) within `lock_jitter_ns`
+    Jitter,
+    /// Both
+    Both,
+}
+
+/// How an announced leap second is applied to the clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeapCorrection {
+    /// Step one second at the leap instant
+    #[default]
+    Step,
+    /// Slew the second out at `slew_max_ppm` (time is off by up to 1s meanwhile)
+    Slew,
+}
+
+/// PTP message handling.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtpConfig {
+    /// T1 source: "follow_up" (default), "sync" (one-step) or "auto"
+    #[serde(default)]
+    pub t1_source: T1Source,
+    /// Alarm when the measured Sync rate deviates from the expected rate
+    #[serde(default = "default_sync_rate_check")]
+    pub sync_rate_check: bool,
+    /// Expected Sync rate (Hz); None = learn it from the first measurement window
+    #[serde(default)]
+    pub expected_sync_rate_hz: Option<f64>,
+    /// Allowed deviation from the expected rate (percent)
+    #[serde(default = "default_sync_rate_tolerance_pct")]
+    pub sync_rate_tolerance_pct: f64,
+    /// Hold a FollowUp that arrives before its Sync (reordering) this long for the
+    /// Sync to pair with (ms); 0 = drop it as before
+    #[serde(default = "default_followup_hold_ms")]
+    pub followup_hold_ms: u64,
+    /// Warn when the master's T1 timestamps are quantized this coarsely or worse
+    /// (ns, e.g. nanoseconds always 0); 0 = no check
+    #[serde(default = "default_coarse_t1_ns")]
+    pub coarse_t1_ns: u64,
+    /// With coarse T1 timestamps, average this many times more samples per
+    /// window to smooth the quantization out; 1 = keep the window
+    #[serde(default = "default_coarse_t1_window_factor")]
+    pub coarse_t1_window_factor: usize,
+    /// Send a Delay_Req to the master this often (s) and subtract the measured
+    /// path delay from the offset; 0 = off (path delay assumed zero)
+    #[serde(default = "default_delay_req_interval_secs")]
+    pub delay_req_interval_secs: f64,
+    /// Convert T1 from the master's TAI to UTC with the currentUtcOffset its Sync
+    /// carries; false for setups that distribute TAI as UTC
+    #[serde(default = "default_apply_utc_offset")]
+    pub apply_utc_offset: bool,
+    /// Linux: timestamp received PTP event packets with the NIC's hardware clock
+    /// (SO_TIMESTAMPING). Reconfigures the NIC's receive filter; needs the PHC kept
+    /// on the system timescale (phc2sys), otherwise software timestamps are used
+    #[serde(default)]
+    pub hw_timestamping: bool,
+    /// Multicast group PTP is received on (and Delay_Req sent to): 224.0.1.129
+    /// (Dante, AES67 default profile), 224.0.0.107 (peer delay profiles), or an
+    /// IPv6 group such as ff0e::181 / ff02::181. IPv6 needs a socket backend
+    #[serde(default = "default_multicast_group")]
+    pub multicast_group: IpAddr,
+    /// Drop Syncs beyond this many per second from any one source (a device
+    /// flooding the multicast group); 0 = no limit
+    #[serde(default = "default_max_syncs_per_source")]
+    pub max_syncs_per_source: u32,
+    /// Syncs kept waiting for their FollowUp at most; the oldest is evicted
+    #[serde(default = "default_max_pending_syncs")]
+    pub max_pending_syncs: usize,
+    /// PTPv1 subdomain to follow (up to 16 characters); messages of other
+    /// subdomains are dropped. "" accepts every subdomain
+    #[serde(default = "default_subdomain")]
+    pub subdomain: String,
+    /// PTPv2 domainNumber to follow; None accepts every domain
+    #[serde(default)]
+    pub domain_number: Option<u8>,
+}
+
+fn default_sync_rate_check() -> bool {
+    true
+}
+
+fn default_sync_rate_tolerance_pct() -> f64 {
+    25.0
+}
+
+fn default_followup_hold_ms() -> u64 {
+    50
+}
+
+fn default_coarse_t1_ns() -> u64 {
+    1_000
+}
+
+fn default_coarse_t1_window_factor() -> usize {
+    1
+}
+
+fn default_delay_req_interval_secs() -> f64 {
+    2.0
+}
+
+fn default_apply_utc_offset() -> bool {
+    true
+}
+
+fn default_multicast_group() -> IpAddr {
+    IpAddr::from(crate::ptp::PTP_MULTICAST_ADDR)
+}
+
+fn default_subdomain() -> String {
+    crate::ptp::DEFAULT_SUBDOMAIN.to_string()
+}
+
+fn default_max_syncs_per_source() -> u32 {
+    256
+}
+
+fn default_max_pending_syncs() -> usize {
+    200
+}
+
+impl Default for PtpConfig {
+    fn default() -> Self {
+        Self {
+            t1_source: T1Source::default(),
+            sync_rate_check: default_sync_rate_check(),
+            expected_sync_rate_hz: None,
+            sync_rate_tolerance_pct: default_sync_rate_tolerance_pct(),
+            followup_hold_ms: default_followup_hold_ms(),
+            coarse_t1_ns: default_coarse_t1_ns(),
+            coarse_t1_window_factor: default_coarse_t1_window_factor(),
+            delay_req_interval_secs: default_delay_req_interval_secs(),
+            apply_utc_offset: default_apply_utc_offset(),
+            hw_timestamping: false,
+            multicast_group: default_multicast_group(),
+            max_syncs_per_source: default_max_syncs_per_source(),
+            max_pending_syncs: default_max_pending_syncs(),
+            subdomain: default_subdomain(),
+            domain_number: None,
+        }
+    }
+}
+
+/// What disciplines the clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NtpDiscipline {
+    /// PTP sets the frequency, NTP keeps UTC aligned (and steps alone while PTP is offline)
+    #[default]
+    PtpPrimary,
+    /// No PTP master: NTP offsets drive the frequency servo and UTC alignment
+    NtpOnly,
+}
+
+/// NTP discipline.
+///
+/// `ntp_only` is for rooms without a PTP master: the server is polled every
+/// `poll_interval_secs` and its offset is fed to the servo as the phase error.
+/// Failed polls double the interval, up to `max_poll_interval_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NtpConfig {
+    /// "ptp_primary" (default) or "ntp_only"
+    #[serde(default)]
+    pub discipline: NtpDiscipline,
+    /// NTP-only: seconds between polls while the server answers
+    #[serde(default = "default_ntp_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// NTP-only: longest backed-off poll interval after failures (seconds)
+    #[serde(default = "default_ntp_max_poll_interval_secs")]
+    pub max_poll_interval_secs: u64,
+}
+
+fn default_ntp_poll_interval_secs() -> u64 {
+    16
+}
+
+fn default_ntp_max_poll_interval_secs() -> u64 {
+    256
+}
+
+impl Default for NtpConfig {
+    fn default() -> Self {
+        Self {
+            discipline: NtpDiscipline::default(),
+            poll_interval_secs: default_ntp_poll_interval_secs(),
+            max_poll_interval_secs: default_ntp_max_poll_interval_secs(),
+        }
+    }
+}
+
+/// FAULT state after repeated servo resets.
+///
+/// Resets (clock steps, sync source changes, incoherent grandmaster restarts)
+/// happening `max_resets` times within `window_secs` mean the environment is
+/// broken in a way the servo cannot fix. The servo then holds its frequency
+/// instead of thrashing, until no reset happened for a full window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultConfig {
+    /// Resets within the window that trigger FAULT (0 = never)
+    pub max_resets: usize,
+    /// Window over which resets are counted (seconds)
+    pub window_secs: f64,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self {
+            max_resets: 10,
+            window_secs: 60.0,
+        }
+    }
+}
+
+/// Post-lock check that the offset stays flat.
+///
+/// While locked, a line is fitted to the offsets of the last `window_secs`.
+/// A slope above `max_slope_ns_per_s` means the clock is slowly diverging
+/// even though every individual sample looks locked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvergenceConfig {
+    /// Fit the offset trend while locked and alarm on divergence
+    pub slope_check: bool,
+    /// Length of the fitted offset history (seconds)
+    pub window_secs: f64,
+    /// Offset slope (ns/s) above which the lock is considered diverging
+    pub max_slope_ns_per_s: f64,
+}
+
+impl Default for ConvergenceConfig {
+    fn default() -> Self {
+        Self {
+            slope_check: true,
+            window_secs: 60.0,
+            max_slope_ns_per_s: 1_000.0,
+        }
+    }
+}
+
+/// Handling of grandmaster sequence ID resets, duplicates and reordering.
+///
+/// A rebooted grandmaster restarts Sync sequence IDs from 0. Pending Syncs from
+/// before the reboot are dropped. The servo is only soft-reset if the phase
+/// after the restart jumped by more than `restart_coherence_us`.
+///
+/// Duplicated Syncs and pairs arriving behind a newer one are dropped without
+/// touching the filters; only master time going back by more than
+/// `time_regression_us` soft-resets them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceConfig {
+    /// Detect grandmaster restarts from sequence ID resets
+    pub restart_detection: bool,
+    /// Phase jump (µs) after a restart above which time is treated as incoherent
+    pub restart_coherence_us: i64,
+    /// Backward step (µs) of master time between pairs that resets the filters
+    #[serde(default = "default_time_regression_us")]
+    pub time_regression_us: i64,
+}
+
+fn default_time_regression_us() -> i64 {
+    1_000
+}
+
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self {
+            restart_detection: true,
+            restart_coherence_us: 1_000,
+            time_regression_us: default_time_regression_us(),
+        }
+    }
+}
+
+/// Fallback when the OS clamps the requested frequency.
+///
+/// If the kernel accepts less frequency correction than the servo requested
+/// (e.g. adjtimex tolerance), the clock under-corrects forever. With the fallback
+/// enabled, the unapplied part is integrated and made up with small steps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockConfig {
+    /// Make up clamped frequency with occasional small steps
+    pub clamp_step_fallback: bool,
+    /// Accumulated shortfall (µs) before a make-up step is applied
+    pub clamp_step_threshold_us: i64,
+    /// (Linux) Seed the system clock from the hardware RTC before the first NTP query
+    #[serde(default)]
+    pub rtc_cold_start: bool,
+    /// Only seed from the RTC if the system clock is off by more than this (seconds)
+    #[serde(default = "default_rtc_cold_start_threshold_secs")]
+    pub rtc_cold_start_threshold_secs: u64,
+    /// Frequency bias limit (PPM) used to slew offsets away with `--slew-only`
+    #[serde(default = "default_slew_max_ppm")]
+    pub slew_max_ppm: f64,
+    /// Minimum time between clock steps (seconds). Steps requested sooner are
+    /// slewed (NTP) or deferred (clamp make-up). 0 = no limit.
+    #[serde(default = "default_min_step_interval_secs")]
+    pub min_step_interval_secs: u64,
+    /// (Windows) Enable time adjustment if found disabled at startup. Set false
+    /// where a managed time policy must be respected: startup then fails instead.
+    #[serde(default = "default_force_enable_adjustment")]
+    pub force_enable_adjustment: bool,
+    /// Locked with a learned drift at least this large (PPM) is reported as
+    /// "hard-correction": the crystal is near its spec limit
+    #[serde(default = "default_hard_correction_ppm")]
+    pub hard_correction_ppm: f64,
+    /// On exit, walk the frequency correction back to nominal over this long
+    /// (seconds) before releasing the clock. 0 = restore immediately.
+    #[serde(default)]
+    pub shutdown_ramp_secs: f64,
+    /// Audit window (seconds): compare the frequency correction measured against
+    /// the raw monotonic clock with the commanded one. 0 = off.
+    #[serde(default = "default_rate_audit_secs")]
+    pub rate_audit_secs: u64,
+    /// Measured-vs-commanded divergence (PPM) that counts as not honored
+    #[serde(default = "default_rate_audit_tolerance_ppm")]
+    pub rate_audit_tolerance_ppm: f64,
+    /// Leap second correction: "step" (default) or "slew". `--slew-only` always slews.
+    #[serde(default)]
+    pub leap_correction: LeapCorrection,
+    /// Save the learned drift while locked and on exit, and start the servo from
+    /// it after a restart instead of 0 ppm
+    #[serde(default = "default_persist_drift")]
+    pub persist_drift: bool,
+    /// Where the drift is kept; None = /var/lib/dantesync/drift (Linux),
+    /// /var/db/dantesync/drift (macOS), C:\ProgramData\DanteSync\drift (Windows)
+    #[serde(default)]
+    pub drift_file: Option<PathBuf>,
+}
+
+fn default_persist_drift() -> bool {
+    true
+}
+
+fn default_rtc_cold_start_threshold_secs() -> u64 {
+    60
+}
+
+fn default_slew_max_ppm() -> f64 {
+    200.0 // Leaves room for drift correction within the kernel's 500ppm
+}
+
+fn default_min_step_interval_secs() -> u64 {
+    60
+}
+
+fn default_force_enable_adjustment() -> bool {
+    true
+}
+
+fn default_hard_correction_ppm() -> f64 {
+    100.0 // Typical crystal tolerance is well inside ±100ppm
+}
+
+fn default_rate_audit_secs() -> u64 {
+    60
+}
+
+fn default_rate_audit_tolerance_ppm() -> f64 {
+    5.0
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            clamp_step_fallback: true,
+            clamp_step_threshold_us: 500,
+            rtc_cold_start: false,
+            rtc_cold_start_threshold_secs: default_rtc_cold_start_threshold_secs(),
+            slew_max_ppm: default_slew_max_ppm(),
+            min_step_interval_secs: default_min_step_interval_secs(),
+            force_enable_adjustment: default_force_enable_adjustment(),
+            hard_correction_ppm: default_hard_correction_ppm(),
+            shutdown_ramp_secs: 0.0,
+            rate_audit_secs: default_rate_audit_secs(),
+            rate_audit_tolerance_ppm: default_rate_audit_tolerance_ppm(),
+            leap_correction: LeapCorrection::Step,
+            persist_drift: default_persist_drift(),
+            drift_file: None,
+        }
+    }
+}
+
+/// Master selection configuration.
+///
+/// Selection only engages when at least `min_masters` distinct master UUIDs
+/// were seen within `candidate_window_secs`. With a single master, the
+/// controller locks immediately (fast path).
+///
+/// On a sync source change the filters are only reset if the new master's time
+/// does not continue the old one within `switch_coherence_us` (checked on its
+/// first Sync pair), so switching between coherent masters is seamless.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BmcaConfig {
+    /// Distinct masters required before selection engages (minimum 2)
+    pub min_masters: usize,
+    /// Candidates not seen for this long are forgotten (seconds)
+    pub candidate_window_secs: f64,
+    /// Offset discontinuity (µs) up to which a new sync source is treated as the
+    /// same time base (no reset); 0 = always soft reset on a source change
+    #[serde(default = "default_switch_coherence_us")]
+    pub switch_coherence_us: i64,
+    /// Re-run selection when the selected master has been silent this long (seconds)
+    #[serde(default = "default_master_timeout_secs")]
+    pub master_timeout_secs: f64,
+}
+
+fn default_switch_coherence_us() -> i64 {
+    1_000
+}
+
+fn default_master_timeout_secs() -> f64 {
+    5.0
+}
+
+impl Default for BmcaConfig {
+    fn default() -> Self {
+        Self {
+            min_masters: 2,
+            candidate_window_secs: 10.0,
+            switch_coherence_us: default_switch_coherence_us(),
+            master_timeout_secs: default_master_timeout_secs(),
+        }
+    }
 }
 
 /// NTP Server configuration for unified time source mode.
@@ -22,6 +504,15 @@ pub struct NtpServerConfig {
     pub port: u16,
     /// Stratum to report to clients (default 3)
     pub stratum: u8,
+    /// Address or interface name the NTP and time query servers listen on.
+    /// Omitted = all interfaces. Set it on dual-homed hosts to serve the
+    /// management LAN while PTP is received on the AV network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind: Option<String>,
+    /// Shared key for the UDP time query server (port 31900). When set, only
+    /// HMAC-tagged queries are answered. Omitted = answer anyone (legacy).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_query_key: Option<String>,
 }
 
 impl Default for NtpServerConfig {
@@ -30,25 +521,79 @@ impl Default for NtpServerConfig {
             enabled: false,
             port: 123,
             stratum: 3,
+            bind: None,
+            time_query_key: None,
         }
     }
 }
 
-/// Servo configuration - LEGACY FIELDS (not used by controller)
+/// Servo configuration.
 ///
-/// The controller uses hardcoded adaptive gains that auto-tune based on
-/// oscillation detection. These fields exist only for config file backwards
-/// compatibility and are not read by the sync algorithm.
+/// The PI gains act on the drift rate (µs/s), by stage: acquisition (`kp`),
+/// production (`kp_prod`) and NANO (`kp_nano`, `ki_nano`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServoConfig {
-    /// Legacy: not used (controller uses adaptive P gain)
+    /// P gain while acquiring
     pub kp: f64,
-    /// Legacy: not used (controller uses adaptive I gain)
+    /// I gain (drift learning) while acquiring and in production
     pub ki: f64,
-    /// Legacy: not used (controller uses DRIFT_MAX_PPM constant)
+    /// P gain in production (rate settled)
+    #[serde(default = "default_kp_prod")]
+    pub kp_prod: f64,
+    /// P gain in NANO mode
+    #[serde(default = "default_kp_nano")]
+    pub kp_nano: f64,
+    /// I gain in NANO mode
+    #[serde(default = "default_ki_nano")]
+    pub ki_nano: f64,
+    /// Limit on the frequency correction (learned drift + P-term), PPM
     pub max_freq_adj_ppm: f64,
-    /// Legacy: not used (no integral term in current servo)
+    /// Limit on how fast the frequency correction may change (PPM per second of
+    /// sample interval), so clocks slaved to the system clock are not jolted.
+    /// 0 = off.
+    #[serde(default)]
+    pub max_freq_slew_ppm_per_sec: f64,
+    /// Limit on the learned drift (integral / drift baseline), PPM. Capped at
+    /// `max_freq_adj_ppm`
     pub max_integral_ppm: f64,
+    /// Derivative gain on the rate error (0 = off, pure P + I)
+    #[serde(default)]
+    pub kd: f64,
+    /// Discipline algorithm: "pi" (default) or "kalman"
+    #[serde(default)]
+    pub algorithm: ServoAlgorithm,
+    /// Kalman: random walk of the oscillator frequency, ppm per sqrt(s). Higher
+    /// follows temperature changes faster, lower averages jitter longer
+    #[serde(default = "default_kalman_process_noise_ppm")]
+    pub kalman_process_noise_ppm: f64,
+    /// Kalman: noise of one filtered offset (stddev, ns). Raise it on hosts with
+    /// heavy timestamp jitter (Windows user-space timestamps)
+    #[serde(default = "default_kalman_measurement_noise_ns")]
+    pub kalman_measurement_noise_ns: f64,
+}
+
+/// Gains before they became configurable; configs written then still carry them
+pub const LEGACY_KP: f64 = 0.0005;
+pub const LEGACY_KI: f64 = 0.00005;
+
+fn default_kp_prod() -> f64 {
+    0.1
+}
+
+fn default_kp_nano() -> f64 {
+    0.01
+}
+
+fn default_ki_nano() -> f64 {
+    0.005
+}
+
+fn default_kalman_process_noise_ppm() -> f64 {
+    0.01
+}
+
+fn default_kalman_measurement_noise_ns() -> f64 {
+    10_000.0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +602,68 @@ pub struct FilterConfig {
     pub min_delta_ns: i64,
     pub calibration_samples: usize, // Number of samples for timestamp calibration (0 = disabled)
     pub warmup_secs: f64,           // Warmup period in seconds (0.0 = disabled, for tests)
+    /// Minimum time after the first PTP packet before any clock adjustment (seconds, 0 = off).
+    /// Combined with the settling sample count - whichever is longer governs.
+    #[serde(default)]
+    pub first_adjust_grace_secs: f64,
+    /// Valid Sync/FollowUp pairs observed before the first one is trusted (settling)
+    #[serde(default = "default_settling_threshold")]
+    pub settling_threshold: usize,
+    /// Lock verify gate: how far (ns) the filtered phase offset may move from where
+    /// the hold started for a sample to count as settled
+    #[serde(default = "default_lock_offset_ns")]
+    pub lock_offset_ns: i64,
+    /// Lock verify gate: consecutive settled samples required before declaring lock (0 = off)
+    #[serde(default = "default_lock_hold_samples")]
+    pub lock_hold_samples: usize,
+    /// Lock verify gate criterion: "offset" (default), "jitter" or "both"
+    #[serde(default)]
+    pub lock_criterion: LockCriterion,
+    /// Lock verify gate: max stddev of the recent raw offsets (ns) for the jitter criterion
+    #[serde(default = "default_lock_jitter_ns")]
+    pub lock_jitter_ns: i64,
+    /// Offset lock detector: locked once the filtered offset stays within this (ns) for
+    /// `lock_hold_samples` samples. Replaces the rate-based gate above (None = off)
+    #[serde(default)]
+    pub lock_threshold_ns: Option<i64>,
+    /// Offset lock detector: unlocked only beyond this (ns, default 2x `lock_threshold_ns`)
+    #[serde(default)]
+    pub unlock_threshold_ns: Option<i64>,
+    /// Sequenced acquisition: align phase, then frequency only, then full servo (default: off)
+    #[serde(default)]
+    pub sequenced_acquisition: bool,
+    /// Log any single raw offset deviating more than this from the filtered offset (ns, None = off)
+    #[serde(default)]
+    pub log_outlier_above_ns: Option<i64>,
+    /// Reject Syncs arriving more than this off the learned send cadence (µs, None = off).
+    /// Catches T2s delayed in the socket buffer by OS stalls.
+    #[serde(default)]
+    pub arrival_gate_us: Option<i64>,
+    /// Drop phase offsets deviating from the recent median by more than this many
+    /// median absolute deviations (None = off). Catches single late T2 captures.
+    #[serde(default)]
+    pub outlier_mad_k: Option<f64>,
+    /// NTP corrections smaller than this are slewed by the kernel instead of stepped
+    /// (ns, 0 = always step). Capped at 500ms: the kernel slews at 500ppm, so a 500ms
+    /// offset already takes ~17 minutes to work off.
+    #[serde(default)]
+    pub step_threshold_ns: i64,
+}
+
+fn default_lock_offset_ns() -> i64 {
+    5_000 // Same as the 5µs/s rate-stable lock criterion
+}
+
+fn default_lock_hold_samples() -> usize {
+    3
+}
+
+fn default_settling_threshold() -> usize {
+    10
+}
+
+fn default_lock_jitter_ns() -> i64 {
+    10_000
 }
 
 impl Default for SystemConfig {
@@ -71,8 +678,6 @@ impl Default for SystemConfig {
         // - NTP handles all time stepping via periodic UTC corrections
         // - PTP locks the frequency while NTP keeps absolute time correct
         //
-        // The controller uses ADAPTIVE gains, so kp/ki values here are for reference only.
-        // Actual gains are auto-tuned based on oscillation detection.
 
         // Platform-specific values
         #[cfg(windows)]
@@ -82,11 +687,19 @@ impl Default for SystemConfig {
 
         SystemConfig {
             servo: ServoConfig {
-                // Reference values only - controller uses adaptive gains
-                kp: 0.0005,
-                ki: 0.00005,
+                // Rate PI: aggressive P while acquiring, gentle in production
+                kp: 0.8,
+                ki: 0.05,
+                kp_prod: default_kp_prod(),
+                kp_nano: default_kp_nano(),
+                ki_nano: default_ki_nano(),
                 max_freq_adj_ppm: 500.0,
-                max_integral_ppm: 100.0,
+                max_freq_slew_ppm_per_sec: 0.0,
+                max_integral_ppm: 500.0,
+                kd: 0.0,
+                algorithm: ServoAlgorithm::Pi,
+                kalman_process_noise_ppm: default_kalman_process_noise_ppm(),
+                kalman_measurement_noise_ns: default_kalman_measurement_noise_ns(),
             },
             filters: FilterConfig {
                 // Sample window for median filtering (same on both platforms)
@@ -98,11 +711,58 @@ impl Default for SystemConfig {
 
                 // Warmup period (same on both platforms)
                 warmup_secs: 3.0,
+
+                // No extra startup grace by default (warmup covers the common case)
+                first_adjust_grace_secs: 0.0,
+
+                // Pairs observed before the servo trusts the timestamps
+                settling_threshold: default_settling_threshold(),
+
+                // Lock verify gate (offset must hold before declaring lock)
+                lock_offset_ns: default_lock_offset_ns(),
+                lock_hold_samples: default_lock_hold_samples(),
+                lock_criterion: LockCriterion::default(),
+                lock_jitter_ns: default_lock_jitter_ns(),
+                lock_threshold_ns: None,
+                unlock_threshold_ns: None,
+
+                // Simultaneous acquisition (sequenced is opt-in)
+                sequenced_acquisition: false,
+
+                // Outlier breadcrumb logging (off by default)
+                log_outlier_above_ns: None,
+
+                // Inter-arrival gate and phase outlier rejection (off by default)
+                arrival_gate_us: None,
+                outlier_mad_k: None,
+                step_threshold_ns: 0,
             },
+            bmca: BmcaConfig::default(),
+            clock: ClockConfig::default(),
+            sequence: SequenceConfig::default(),
+            convergence: ConvergenceConfig::default(),
+            fault: FaultConfig::default(),
+            ptp: PtpConfig::default(),
+            ntp: NtpConfig::default(),
         }
     }
 }
 
+impl SystemConfig {
+    /// Reject settings the controller cannot run with.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(hz) = self.ptp.expected_sync_rate_hz {
+            if hz.is_nan() || hz <= 0.0 {
+                anyhow::bail!(
+                    "ptp.expected_sync_rate_hz must be positive (got {}); omit it to learn the rate",
+                    hz
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -116,10 +776,13 @@ mod tests {
         let config = SystemConfig::default();
 
         // Verify servo defaults
-        assert!((config.servo.kp - 0.0005).abs() < f64::EPSILON);
-        assert!((config.servo.ki - 0.00005).abs() < f64::EPSILON);
+        assert!((config.servo.kp - 0.8).abs() < f64::EPSILON);
+        assert!((config.servo.ki - 0.05).abs() < f64::EPSILON);
+        assert!((config.servo.kp_prod - 0.1).abs() < f64::EPSILON);
+        assert!((config.servo.kp_nano - 0.01).abs() < f64::EPSILON);
+        assert!((config.servo.ki_nano - 0.005).abs() < f64::EPSILON);
         assert!((config.servo.max_freq_adj_ppm - 500.0).abs() < f64::EPSILON);
-        assert!((config.servo.max_integral_ppm - 100.0).abs() < f64::EPSILON);
+        assert!((config.servo.max_integral_ppm - 500.0).abs() < f64::EPSILON);
     }
 
     #[test]
@@ -194,6 +857,52 @@ mod tests {
         assert!((config.filters.warmup_secs - 5.0).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn test_ntp_only_discipline_parsed() {
+        let json = r#"{
+            "servo": {"kp": 0.0005, "ki": 0.00005, "max_freq_adj_ppm": 500.0, "max_integral_ppm": 100.0},
+            "filters": {"sample_window_size": 4, "min_delta_ns": 0, "calibration_samples": 0, "warmup_secs": 0.0},
+            "ntp": {"discipline": "ntp_only", "poll_interval_secs": 8}
+        }"#;
+
+        let config: SystemConfig = serde_json::from_str(json).expect("parse failed");
+
+        assert_eq!(config.ntp.discipline, NtpDiscipline::NtpOnly);
+        assert_eq!(config.ntp.poll_interval_secs, 8);
+        assert_eq!(config.ntp.max_poll_interval_secs, 256);
+    }
+
+    #[test]
+    fn test_kalman_algorithm_parsed() {
+        let json = r#"{
+            "servo": {"kp": 0.0005, "ki": 0.00005, "max_freq_adj_ppm": 500.0, "max_integral_ppm": 100.0,
+                      "algorithm": "kalman", "kalman_measurement_noise_ns": 50000.0},
+            "filters": {"sample_window_size": 4, "min_delta_ns": 0, "calibration_samples": 0, "warmup_secs": 0.0}
+        }"#;
+
+        let config: SystemConfig = serde_json::from_str(json).expect("parse failed");
+
+        assert_eq!(config.servo.algorithm, ServoAlgorithm::Kalman);
+        assert_eq!(config.servo.kalman_measurement_noise_ns, 50_000.0);
+        assert_eq!(config.servo.kalman_process_noise_ppm, 0.01);
+    }
+
+    #[test]
+    fn test_ipv6_multicast_group_parsed() {
+        let json = r#"{
+            "servo": {"kp": 0.0005, "ki": 0.00005, "max_freq_adj_ppm": 500.0, "max_integral_ppm": 100.0},
+            "filters": {"sample_window_size": 4, "min_delta_ns": 0, "calibration_samples": 0, "warmup_secs": 0.0},
+            "ptp": {"multicast_group": "ff0e::181"}
+        }"#;
+
+        let config: SystemConfig = serde_json::from_str(json).expect("parse failed");
+
+        assert_eq!(
+            config.ptp.multicast_group,
+            "ff0e::181".parse::<IpAddr>().unwrap()
+        );
+    }
+
     #[test]
     fn test_servo_config_clone() {
         let config = SystemConfig::default();
@@ -206,6 +915,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_optional_fields_default_when_omitted() {
+        let json = r#"{
+            "servo": {"kp": 0.001, "ki": 0.0001, "max_freq_adj_ppm": 1000.0, "max_integral_ppm": 200.0},
+            "filters": {"sample_window_size": 8, "min_delta_ns": 500000, "calibration_samples": 5, "warmup_secs": 5.0}
+        }"#;
+        let config: SystemConfig = serde_json::from_str(json).expect("parse failed");
+
+        assert_eq!(config.bmca.min_masters, 2);
+        assert!((config.bmca.candidate_window_secs - 10.0).abs() < f64::EPSILON);
+        assert_eq!(config.bmca.switch_coherence_us, 1_000);
+        assert_eq!(config.bmca.master_timeout_secs, 5.0);
+        assert_eq!(config.filters.lock_offset_ns, 5_000);
+        assert_eq!(config.filters.lock_hold_samples, 3);
+        assert_eq!(config.filters.lock_criterion, LockCriterion::Offset);
+        assert_eq!(config.filters.lock_jitter_ns, 10_000);
+        assert_eq!(config.filters.lock_threshold_ns, None);
+        assert_eq!(config.filters.unlock_threshold_ns, None);
+        assert_eq!(config.filters.log_outlier_above_ns, None);
+        assert_eq!(config.filters.arrival_gate_us, None);
+        assert_eq!(config.filters.outlier_mad_k, None);
+        assert_eq!(config.filters.step_threshold_ns, 0);
+        assert_eq!(config.servo.kd, 0.0);
+        assert_eq!(config.servo.algorithm, ServoAlgorithm::Pi);
+        assert_eq!(config.servo.kalman_process_noise_ppm, 0.01);
+        assert_eq!(config.servo.kalman_measurement_noise_ns, 10_000.0);
+        assert_eq!(config.servo.max_freq_slew_ppm_per_sec, 0.0);
+        assert!(!config.ptp.hw_timestamping);
+        assert_eq!(config.ptp.multicast_group.to_string(), "224.0.1.129");
+        assert_eq!(config.ntp.discipline, NtpDiscipline::PtpPrimary);
+        assert_eq!(config.ntp.poll_interval_secs, 16);
+        assert_eq!(config.ntp.max_poll_interval_secs, 256);
+        assert!(!config.filters.sequenced_acquisition);
+        assert_eq!(config.filters.first_adjust_grace_secs, 0.0);
+        assert_eq!(config.filters.settling_threshold, 10);
+        assert!(config.clock.clamp_step_fallback);
+        assert_eq!(config.clock.clamp_step_threshold_us, 500);
+        assert_eq!(config.clock.min_step_interval_secs, 60);
+        assert!(config.clock.force_enable_adjustment);
+        assert_eq!(config.clock.hard_correction_ppm, 100.0);
+        assert_eq!(config.clock.shutdown_ramp_secs, 0.0);
+        assert_eq!(config.clock.rate_audit_secs, 60);
+        assert_eq!(config.clock.rate_audit_tolerance_ppm, 5.0);
+        assert_eq!(config.clock.leap_correction, LeapCorrection::Step);
+        assert!(config.clock.persist_drift);
+        assert_eq!(config.clock.drift_file, None);
+        assert!(config.sequence.restart_detection);
+        assert_eq!(config.sequence.restart_coherence_us, 1_000);
+        assert_eq!(config.sequence.time_regression_us, 1_000);
+        assert!(config.ptp.sync_rate_check);
+        assert_eq!(config.ptp.expected_sync_rate_hz, None);
+        assert_eq!(config.ptp.sync_rate_tolerance_pct, 25.0);
+        assert_eq!(config.ptp.followup_hold_ms, 50);
+        assert_eq!(config.ptp.max_syncs_per_source, 256);
+        assert_eq!(config.ptp.max_pending_syncs, 200);
+        assert_eq!(config.ptp.subdomain, "_DFLT");
+        assert_eq!(config.ptp.domain_number, None);
+        assert_eq!(config.ptp.coarse_t1_ns, 1_000);
+        assert_eq!(config.ptp.coarse_t1_window_factor, 1);
+        assert_eq!(config.ptp.delay_req_interval_secs, 2.0);
+        assert!(config.ptp.apply_utc_offset);
+    }
+
     // ========================================================================
     // NTP SERVER CONFIG TESTS
     // ========================================================================
@@ -225,6 +997,8 @@ mod tests {
             enabled: true,
             port: 1123,
             stratum: 2,
+            bind: Some("192.168.1.20".to_string()),
+            time_query_key: Some("studio-secret".to_string()),
         };
 
         let json = serde_json::to_string(&config).expect("serialize failed");
@@ -233,6 +1007,8 @@ mod tests {
         assert_eq!(restored.enabled, config.enabled);
         assert_eq!(restored.port, config.port);
         assert_eq!(restored.stratum, config.stratum);
+        assert_eq!(restored.bind, config.bind);
+        assert_eq!(restored.time_query_key, config.time_query_key);
     }
 
     #[test]
@@ -244,6 +1020,8 @@ mod tests {
         assert!(config.enabled);
         assert_eq!(config.port, 123);
         assert_eq!(config.stratum, 3);
+        assert_eq!(config.bind, None);
+        assert_eq!(config.time_query_key, None);
     }
 
     #[test]
@@ -252,6 +1030,8 @@ mod tests {
             enabled: true,
             port: 8123,
             stratum: 4,
+            bind: None,
+            time_query_key: None,
         };
         let cloned = config.clone();
 
@@ -259,4 +1039,16 @@ mod tests {
         assert_eq!(cloned.port, config.port);
         assert_eq!(cloned.stratum, config.stratum);
     }
+
+    #[test]
+    fn test_validate_rejects_non_positive_sync_rate() {
+        let mut config = SystemConfig::default();
+        assert!(config.validate().is_ok());
+        config.ptp.expected_sync_rate_hz = Some(8.0);
+        assert!(config.validate().is_ok());
+        for hz in [0.0, -8.0, f64::NAN] {
+            config.ptp.expected_sync_rate_hz = Some(hz);
+            assert!(config.validate().is_err(), "{} accepted", hz);
+        }
+    }
 }
@@ -0,0 +1,128 @@
+//! Post-lock convergence verification.
+//!
+//! Lock is declared from the instantaneous drift rate, which can look fine sample by
+//! sample while the offset slowly walks away (subtly wrong correction sign, unstable
+//! master). Fitting a line to the offsets seen while locked exposes that trend: a
+//! converged servo holds the offset flat, a diverging one shows a persistent slope.
+
+use std::collections::VecDeque;
+
+/// Sliding-window least-squares slope of offset over time.
+#[derive(Debug)]
+pub struct ConvergenceMonitor {
+    window_secs: f64,
+    /// (time in seconds, offset in ns)
+    samples: VecDeque<(f64, f64)>,
+}
+
+/// Minimum number of samples before a slope is reported.
+const MIN_FIT_SAMPLES: usize = 5;
+
+impl ConvergenceMonitor {
+    pub fn new(window_secs: f64) -> Self {
+        Self {
+            window_secs,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Add an offset sample. Returns the fitted slope (ns/s) once the samples span
+    /// at least half the window, None before that.
+    pub fn add_sample(&mut self, t_secs: f64, offset_ns: f64) -> Option<f64> {
+        self.samples.push_back((t_secs, offset_ns));
+        while let Some(&(t0, _)) = self.samples.front() {
+            if t_secs - t0 > self.window_secs {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let span = t_secs - self.samples.front().map(|&(t0, _)| t0).unwrap_or(t_secs);
+        if self.samples.len() < MIN_FIT_SAMPLES || span < self.window_secs / 2.0 {
+            return None;
+        }
+        fit_slope(&self.samples)
+    }
+
+    /// Discard all samples (unlock, clock step).
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// Least-squares slope of y over x. None without at least two distinct x values.
+pub fn fit_slope(points: &VecDeque<(f64, f64)>) -> Option<f64> {
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return None;
+    }
+    let mean_x = points.iter().map(|&(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|&(_, y)| y).sum::<f64>() / n;
+
+    let (mut sxy, mut sxx) = (0.0, 0.0);
+    for &(x, y) in points {
+        sxy += (x - mean_x) * (y - mean_y);
+        sxx += (x - mean_x) * (x - mean_x);
+    }
+    if sxx <= f64::EPSILON {
+        return None;
+    }
+    Some(sxy / sxx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_offset_has_no_slope() {
+        let mut monitor = ConvergenceMonitor::new(10.0);
+        let mut slope = None;
+        for i in 0..20 {
+            // ±200ns noise around a constant offset
+            let noise = if i % 2 == 0 { 200.0 } else { -200.0 };
+            slope = monitor.add_sample(i as f64, 50_000.0 + noise);
+        }
+        let slope = slope.expect("window is full");
+        assert!(slope.abs() < 50.0, "Flat offset fitted {} ns/s", slope);
+    }
+
+    #[test]
+    fn test_slow_divergence_detected_through_noise() {
+        let mut monitor = ConvergenceMonitor::new(30.0);
+        let mut slope = None;
+        for i in 0..60 {
+            // 500ns/s walk hidden under ±2µs sample-to-sample noise
+            let noise = if i % 2 == 0 { 2_000.0 } else { -2_000.0 };
+            slope = monitor.add_sample(i as f64, 500.0 * i as f64 + noise);
+        }
+        let slope = slope.unwrap();
+        assert!(
+            (slope - 500.0).abs() < 50.0,
+            "Expected ~500 ns/s, got {}",
+            slope
+        );
+    }
+
+    #[test]
+    fn test_no_slope_until_half_window_spanned() {
+        let mut monitor = ConvergenceMonitor::new(60.0);
+        for i in 0..29 {
+            assert_eq!(monitor.add_sample(i as f64, 0.0), None);
+        }
+        assert!(monitor.add_sample(30.0, 0.0).is_some());
+
+        monitor.clear();
+        assert_eq!(monitor.add_sample(31.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_fit_slope_degenerate_inputs() {
+        assert_eq!(fit_slope(&VecDeque::from(vec![(1.0, 5.0)])), None);
+        assert_eq!(
+            fit_slope(&VecDeque::from(vec![(1.0, 5.0), (1.0, 9.0)])),
+            None
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Delay_Req / Delay_Resp bookkeeping.
+//!
+//! Delay_Resp messages go to the multicast group, so on a busy segment we see every
+//! other slave's delay exchanges too. A response only counts as ours when it names
+//! our port identity and the sequence id of the request we still have outstanding;
+//! everything else (other slaves, late or duplicate responses) is ignored and counted.
+//!
+//! Mean path delay is `((T2 - T1) + (T4 - T3)) / 2`. T1/T4 are master time and
+//! T2/T3 ours, so the (arbitrary, for Dante device uptime) offset between the two
+//! time bases cancels out.
+
+use crate::ptp::PtpV1DelayRespBody;
+use std::collections::VecDeque;
+
+/// A path delay outside 0..10ms is a bad timestamp, not a LAN
+const MAX_PATH_DELAY_NS: i64 = 10_000_000;
+
+/// Exchanges the delay estimate is the median of
+const PATH_DELAY_SAMPLES: usize = 8;
+
+/// Clock UUID + port number, as carried in PTPv1 requestingSource* fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortIdentity {
+    pub uuid: [u8; 6],
+    pub port_id: u16,
+}
+
+/// A completed exchange: our Delay_Req egress time (T3) and the master's
+/// receive time of it (T4).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelayExchange {
+    pub t3_ns: i64,
+    pub t4_ns: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Outstanding {
+    sequence_id: u16,
+    t3_ns: i64,
+}
+
+#[derive(Debug)]
+pub struct DelayReqTracker {
+    own: PortIdentity,
+    outstanding: Option<Outstanding>,
+    unmatched: u64,
+}
+
+impl DelayReqTracker {
+    pub fn new(own: PortIdentity) -> Self {
+        Self {
+            own,
+            outstanding: None,
+            unmatched: 0,
+        }
+    }
+
+    /// Record a Delay_Req we sent. Replaces any request still waiting for its
+    /// response - a response to the older one arriving now is unmatched.
+    pub fn on_request_sent(&mut self, sequence_id: u16, t3_ns: i64) {
+        self.outstanding = Some(Outstanding { sequence_id, t3_ns });
+    }
+
+    /// Match a received Delay_Resp. Returns the exchange if it answers our
+    /// outstanding request; otherwise counts it as unmatched.
+    pub fn on_response(&mut self, body: &PtpV1DelayRespBody) -> Option<DelayExchange> {
+        let requester = PortIdentity {
+            uuid: body.requesting_source_uuid,
+            port_id: body.requesting_source_port_id,
+        };
+        match self.outstanding {
+            Some(req)
+                if requester == self.own
+                    && body.requesting_source_sequence_id == req.sequence_id =>
+            {
+                self.outstanding = None;
+                Some(DelayExchange {
+                    t3_ns: req.t3_ns,
+                    t4_ns: body.delay_receipt_timestamp.to_nanos(),
+                })
+            }
+            _ => {
+                self.unmatched += 1;
+                None
+            }
+        }
+    }
+
+    /// Delay_Resp messages seen that did not answer our outstanding request
+    pub fn unmatched_count(&self) -> u64 {
+        self.unmatched
+    }
+
+    pub fn has_outstanding(&self) -> bool {
+        self.outstanding.is_some()
+    }
+
+    /// Identity our requests are sent with
+    pub fn own(&self) -> PortIdentity {
+        self.own
+    }
+}
+
+/// Mean path delay of one exchange. `master_to_slave_ns` is T2 - T1 of a Sync
+/// pair taken close to the request.
+pub fn mean_path_delay_ns(master_to_slave_ns: i64, exchange: &DelayExchange) -> i64 {
+    (master_to_slave_ns + (exchange.t4_ns - exchange.t3_ns)) / 2
+}
+
+/// Median of the last few plausible path delays.
+#[derive(Debug, Default)]
+pub struct PathDelayEstimator {
+    samples: VecDeque<i64>,
+    rejected: u64,
+}
+
+impl PathDelayEstimator {
+    /// Add one exchange. Returns its path delay, or None if implausible.
+    pub fn add(&mut self, master_to_slave_ns: i64, exchange: &DelayExchange) -> Option<i64> {
+        let delay_ns = mean_path_delay_ns(master_to_slave_ns, exchange);
+        if !(0..=MAX_PATH_DELAY_NS).contains(&delay_ns) {
+            self.rejected += 1;
+            return None;
+        }
+        if self.samples.len() == PATH_DELAY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(delay_ns);
+        Some(delay_ns)
+    }
+
+    /// Current estimate; None until the first plausible exchange
+    pub fn delay_ns(&self) -> Option<i64> {
+        let mut sorted: Vec<i64> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        sorted.get(sorted.len() / 2).copied()
+    }
+
+    /// Exchanges discarded as implausible
+    pub fn rejected_count(&self) -> u64 {
+        self.rejected
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ptp::PtpTimestamp;
+
+    const OWN: PortIdentity = PortIdentity {
+        uuid: [0x00, 0x1D, 0xC1, 0x0A, 0x0B, 0x0C],
+        port_id: 1,
+    };
+
+    fn resp(requester: PortIdentity, seq: u16, t4_ns: i64) -> PtpV1DelayRespBody {
+        PtpV1DelayRespBody {
+            delay_receipt_timestamp: PtpTimestamp::from_nanos(t4_ns),
+            requesting_source_uuid: requester.uuid,
+            requesting_source_port_id: requester.port_id,
+            requesting_source_sequence_id: seq,
+        }
+    }
+
+    #[test]
+    fn test_matching_response_completes_exchange() {
+        let mut tracker = DelayReqTracker::new(OWN);
+        tracker.on_request_sent(42, 1_000_000);
+
+        let exchange = tracker.on_response(&resp(OWN, 42, 1_050_000)).unwrap();
+        assert_eq!(exchange.t3_ns, 1_000_000);
+        assert_eq!(exchange.t4_ns, 1_050_000);
+        assert!(!tracker.has_outstanding());
+        assert_eq!(tracker.unmatched_count(), 0);
+    }
+
+    #[test]
+    fn test_other_slaves_responses_are_ignored() {
+        let mut tracker = DelayReqTracker::new(OWN);
+        tracker.on_request_sent(42, 1_000_000);
+
+        // Same sequence id, different slave
+        let other = PortIdentity {
+            uuid: [0x00, 0x1D, 0xC1, 0x99, 0x99, 0x99],
+            ..OWN
+        };
+        assert!(tracker.on_response(&resp(other, 42, 1_050_000)).is_none());
+        // Our UUID but another port
+        let other_port = PortIdentity { port_id: 2, ..OWN };
+        assert!(tracker
+            .on_response(&resp(other_port, 42, 1_050_000))
+            .is_none());
+
+        assert_eq!(tracker.unmatched_count(), 2);
+        // Still waiting for ours
+        assert!(tracker.has_outstanding());
+        assert!(tracker.on_response(&resp(OWN, 42, 1_060_000)).is_some());
+    }
+
+    #[test]
+    fn test_late_and_duplicate_responses_are_unmatched() {
+        let mut tracker = DelayReqTracker::new(OWN);
+
+        // Nothing outstanding
+        assert!(tracker.on_response(&resp(OWN, 1, 0)).is_none());
+
+        tracker.on_request_sent(1, 100);
+        tracker.on_request_sent(2, 200);
+        // Response to the superseded request
+        assert!(tracker.on_response(&resp(OWN, 1, 150)).is_none());
+        assert_eq!(tracker.on_response(&resp(OWN, 2, 250)).unwrap().t3_ns, 200);
+        // Duplicate
+        assert!(tracker.on_response(&resp(OWN, 2, 250)).is_none());
+
+        assert_eq!(tracker.unmatched_count(), 3);
+    }
+    #[test]
+    fn test_path_delay_cancels_time_base_offset() {
+        // Master time runs 1000s behind ours; 40us each way
+        let base = 1_000_000_000_000;
+        let t1 = 5_000_000_000;
+        let t2 = t1 + base + 40_000;
+        let t3 = t2 + 500_000_000;
+        let t4 = t3 - base + 40_000;
+        let exchange = DelayExchange {
+            t3_ns: t3,
+            t4_ns: t4,
+        };
+        assert_eq!(mean_path_delay_ns(t2 - t1, &exchange), 40_000);
+    }
+
+    #[test]
+    fn test_path_delay_estimator_median_and_rejects() {
+        let mut estimator = PathDelayEstimator::default();
+        assert_eq!(estimator.delay_ns(), None);
+
+        let exchange = |delay_ns: i64| DelayExchange {
+            t3_ns: 0,
+            t4_ns: delay_ns,
+        };
+        assert_eq!(estimator.add(30_000, &exchange(30_000)), Some(30_000));
+        assert_eq!(estimator.add(50_000, &exchange(50_000)), Some(50_000));
+        assert_eq!(estimator.add(40_000, &exchange(40_000)), Some(40_000));
+        assert_eq!(estimator.delay_ns(), Some(40_000));
+
+        // Negative and absurd delays are discarded
+        assert_eq!(estimator.add(-100_000, &exchange(0)), None);
+        assert_eq!(estimator.add(30_000_000, &exchange(30_000_000)), None);
+        assert_eq!(estimator.rejected_count(), 2);
+        assert_eq!(estimator.delay_ns(), Some(40_000));
+
+        estimator.reset();
+        assert_eq!(estimator.delay_ns(), None);
+    }
+}
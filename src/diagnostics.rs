@@ -0,0 +1,242 @@
+//! "Why is nothing locking?" diagnostics.
+//!
+//! When the servo never locks, the cause is almost always on the network, not in
+//! the servo: no PTP traffic reaches us at all, a PTPv2 master sends no usable
+//! Sync, or the master is one-step and never sends FollowUps. Received packets are counted by type; while
+//! unlocked, the counts are turned into a message that names the likely fix.
+//!
+//! A master with coarse T1 timestamps (nanoseconds always 0, or quantized to
+//! microseconds/milliseconds) locks, but its offsets jump by the quantum and look
+//! like jitter. The granularity is measured from the T1 nanoseconds field.
+
+use crate::ptp::{is_ptp_v2, PtpV1Control, PtpV1Header};
+
+/// Received packets by type since the last report.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PacketCensus {
+    pub total: u64,
+    /// Shorter than a PTP header
+    pub too_short: u64,
+    pub ptp_v2: u64,
+    pub sync: u64,
+    pub follow_up: u64,
+    /// Other PTPv1 messages (Delay_Req/Resp, Management)
+    pub other_v1: u64,
+    /// Sync/FollowUp pairs handed to the servo
+    pub pairs: u64,
+}
+
+/// Likely reason nothing locks, from the packet census.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoLockCause {
+    NoPackets,
+    OnlyPtpV2,
+    NoSync,
+    SyncWithoutFollowUp,
+    NotPairing,
+}
+
+impl NoLockCause {
+    /// What to check, for the log.
+    pub fn advice(&self) -> &'static str {
+        match self {
+            NoLockCause::NoPackets => {
+                "no PTP traffic reaches this host - check the interface selection, VLAN and IGMP snooping/querier"
+            }
+            NoLockCause::OnlyPtpV2 => {
+                "only PTPv2 seen and none of it paired - the PTPv2 master (AES67/SMPTE mode?) sends no Sync/Follow_Up here"
+            }
+            NoLockCause::NoSync => {
+                "PTP packets but no Sync - event messages (UDP 319) are blocked or filtered"
+            }
+            NoLockCause::SyncWithoutFollowUp => {
+                "Sync but 0 FollowUp - a one-step master whose Syncs carry no origin timestamp? Try ptp.t1_source = \"sync\""
+            }
+            NoLockCause::NotPairing => {
+                "Sync and FollowUp seen but none paired - sequence ids do not match (several masters, heavy loss or reordering)"
+            }
+        }
+    }
+}
+
+impl PacketCensus {
+    /// Count one received packet.
+    pub fn record(&mut self, data: &[u8]) {
+        self.total += 1;
+        if data.len() < PtpV1Header::SIZE {
+            self.too_short += 1;
+            return;
+        }
+        if is_ptp_v2(data) {
+            self.ptp_v2 += 1;
+            return;
+        }
+        match PtpV1Header::parse(data).map(|h| h.message_type) {
+            Ok(PtpV1Control::Sync) => self.sync += 1,
+            Ok(PtpV1Control::FollowUp) => self.follow_up += 1,
+            _ => self.other_v1 += 1,
+        }
+    }
+
+    pub fn record_pair(&mut self) {
+        self.pairs += 1;
+    }
+
+    /// None when pairs reach the servo (it is just settling).
+    pub fn diagnose(&self) -> Option<NoLockCause> {
+        if self.pairs > 0 {
+            return None;
+        }
+        let v1 = self.sync + self.follow_up + self.other_v1;
+        Some(if self.total == 0 {
+            NoLockCause::NoPackets
+        } else if v1 == 0 && self.ptp_v2 > 0 {
+            NoLockCause::OnlyPtpV2
+        } else if self.sync == 0 {
+            NoLockCause::NoSync
+        } else if self.follow_up == 0 {
+            NoLockCause::SyncWithoutFollowUp
+        } else {
+            NoLockCause::NotPairing
+        })
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} packets: {} Sync, {} FollowUp, {} other PTPv1, {} PTPv2, {} too short; {} pairs",
+            self.total,
+            self.sync,
+            self.follow_up,
+            self.other_v1,
+            self.ptp_v2,
+            self.too_short,
+            self.pairs
+        )
+    }
+}
+
+/// Samples before the T1 granularity is reported
+const T1_GRANULARITY_MIN_SAMPLES: u32 = 16;
+
+/// Resolution of the master's T1 timestamps: the largest quantum all observed
+/// nanosecond fields are multiples of.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct T1Granularity {
+    gcd_ns: u64,
+    samples: u32,
+}
+
+impl T1Granularity {
+    pub fn record(&mut self, t1_ns: i64) {
+        let subsec = t1_ns.rem_euclid(1_000_000_000) as u64;
+        self.gcd_ns = gcd(self.gcd_ns, subsec);
+        self.samples += 1;
+    }
+
+    /// Detected quantum (ns); 1s when the nanoseconds were always 0.
+    /// None until enough T1s were seen.
+    pub fn granularity_ns(&self) -> Option<u64> {
+        if self.samples < T1_GRANULARITY_MIN_SAMPLES {
+            return None;
+        }
+        Some(if self.gcd_ns == 0 {
+            1_000_000_000
+        } else {
+            self.gcd_ns
+        })
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// "250us", "1ms", "1s" - the largest unit that divides `ns` exactly.
+pub fn format_granularity(ns: u64) -> String {
+    if ns % 1_000_000_000 == 0 {
+        format!("{}s", ns / 1_000_000_000)
+    } else if ns % 1_000_000 == 0 {
+        format!("{}ms", ns / 1_000_000)
+    } else if ns % 1_000 == 0 {
+        format!("{}us", ns / 1_000)
+    } else {
+        format!("{}ns", ns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ptp::{encode_follow_up, encode_sync, PtpTimestamp};
+
+    const UUID: [u8; 6] = [0x00, 0x1D, 0xC1, 0x00, 0x00, 0x01];
+
+    fn ptp_v2_sync() -> Vec<u8> {
+        let mut buf = vec![0u8; 44];
+        buf[0] = 0x00; // messageType Sync
+        buf[1] = 0x02; // versionPTP 2
+        buf
+    }
+
+    #[test]
+    fn test_census_classifies_packets() {
+        let t1 = PtpTimestamp::from_nanos(1_000);
+        let mut census = PacketCensus::default();
+        census.record(&encode_sync(UUID, 1, t1, true));
+        census.record(&encode_follow_up(UUID, 1, 1, t1));
+        census.record(&ptp_v2_sync());
+        census.record(&[0u8; 10]);
+
+        assert_eq!(census.total, 4);
+        assert_eq!(census.sync, 1);
+        assert_eq!(census.follow_up, 1);
+        assert_eq!(census.ptp_v2, 1);
+        assert_eq!(census.too_short, 1);
+    }
+
+    #[test]
+    fn test_diagnosis_points_at_the_cause() {
+        let t1 = PtpTimestamp::from_nanos(1_000);
+        let mut census = PacketCensus::default();
+        assert_eq!(census.diagnose(), Some(NoLockCause::NoPackets));
+
+        census.record(&ptp_v2_sync());
+        assert_eq!(census.diagnose(), Some(NoLockCause::OnlyPtpV2));
+
+        census.record(&encode_follow_up(UUID, 1, 1, t1));
+        assert_eq!(census.diagnose(), Some(NoLockCause::NoSync));
+
+        let mut census = PacketCensus::default();
+        census.record(&encode_sync(UUID, 1, t1, true));
+        assert_eq!(census.diagnose(), Some(NoLockCause::SyncWithoutFollowUp));
+
+        census.record(&encode_follow_up(UUID, 1, 7, t1));
+        assert_eq!(census.diagnose(), Some(NoLockCause::NotPairing));
+
+        census.record_pair();
+        assert_eq!(census.diagnose(), None, "Pairs flowing: just settling");
+    }
+    #[test]
+    fn test_t1_granularity() {
+        let mut fine = T1Granularity::default();
+        let mut millis = T1Granularity::default();
+        let mut zero = T1Granularity::default();
+        for i in 0..16i64 {
+            let secs = (100 + i) * 1_000_000_000;
+            fine.record(secs + 123_457 * i + 1);
+            millis.record(secs + 1_000_000 * (i * 37 % 1000 + 1));
+            zero.record(secs);
+            if i < 15 {
+                assert_eq!(fine.granularity_ns(), None);
+            }
+        }
+        assert_eq!(fine.granularity_ns(), Some(1));
+        assert_eq!(millis.granularity_ns(), Some(1_000_000));
+        assert_eq!(zero.granularity_ns(), Some(1_000_000_000));
+        assert_eq!(format_granularity(1_000_000), "1ms");
+        assert_eq!(format_granularity(250_000), "250us");
+    }
+}
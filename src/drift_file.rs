@@ -0,0 +1,100 @@
+//! Learned oscillator drift kept across restarts.
+//!
+//! Without it every restart begins at 0 ppm and the servo has to learn the
+//! crystal's drift again, with a visible offset excursion while it settles. The
+//! estimate is saved while locked and on a clean shutdown, and seeds the servo
+//! at the next start. The file holds a single number (ppm), like ntpd's drift file.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A saved value beyond this (ppm) is not a drift estimate: the file is corrupt.
+const MAX_DRIFT_PPM: f64 = 500.0;
+
+/// Default drift file location per OS.
+pub fn default_path() -> PathBuf {
+    #[cfg(windows)]
+    let path = r"C:\ProgramData\DanteSync\drift";
+    #[cfg(target_os = "macos")]
+    let path = "/var/db/dantesync/drift";
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let path = "/var/lib/dantesync/drift";
+    PathBuf::from(path)
+}
+
+#[derive(Debug)]
+pub struct DriftFile {
+    path: PathBuf,
+}
+
+impl DriftFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The saved drift (ppm). None if there is no file yet; an error if it
+    /// cannot be read or does not hold a plausible value.
+    pub fn load(&self) -> Result<Option<f64>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", self.path.display())),
+        };
+        let ppm: f64 = content
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("{} does not hold a number", self.path.display()))?;
+        if !ppm.is_finite() || ppm.abs() > MAX_DRIFT_PPM {
+            return Err(anyhow!(
+                "{} holds an implausible drift of {}ppm",
+                self.path.display(),
+                ppm
+            ));
+        }
+        Ok(Some(ppm))
+    }
+
+    /// Save `ppm`. Written to a temporary file and renamed, so a crash midway
+    /// leaves the previous value.
+    pub fn save(&self, ppm: f64) -> Result<()> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, format!("{:.6}\n", ppm))
+            .with_context(|| format!("writing {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path).with_context(|| format!("replacing {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = DriftFile::new(dir.path().join("state").join("drift"));
+        assert_eq!(file.load().unwrap(), None, "nothing saved yet");
+
+        file.save(-12.345_678).unwrap();
+        assert_eq!(file.load().unwrap(), Some(-12.345_678));
+        file.save(3.5).unwrap();
+        assert_eq!(file.load().unwrap(), Some(3.5));
+    }
+
+    #[test]
+    fn test_corrupt_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = DriftFile::new(dir.path().join("drift"));
+        for content in ["", "garbage", "NaN", "1e9"] {
+            fs::write(file.path(), content).unwrap();
+            assert!(file.load().is_err(), "{:?}", content);
+        }
+    }
+}
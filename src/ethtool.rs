@@ -0,0 +1,256 @@
+//! NIC timestamping capabilities via the `ETHTOOL_GET_TS_INFO` ioctl (`ethtool -T`).
+//!
+//! Tells whether the interface can do hardware timestamping and which PTP
+//! hardware clock (PHC, `/dev/ptpN`) belongs to it - i.e. whether sub-microsecond
+//! sync is achievable on this hardware before anything is tuned. With
+//! `ptp.hw_timestamping`, `enable_hw_rx_filter` then turns it on (`SIOCSHWTSTAMP`).
+
+use anyhow::{anyhow, Result};
+use log::info;
+use std::net::UdpSocket;
+use std::os::fd::AsRawFd;
+
+const ETHTOOL_GET_TS_INFO: u32 = 0x0000_0041;
+
+// SOF_TIMESTAMPING_* capability bits (linux/net_tstamp.h)
+const SOF_TIMESTAMPING_TX_HARDWARE: u32 = 1 << 0;
+const SOF_TIMESTAMPING_TX_SOFTWARE: u32 = 1 << 1;
+const SOF_TIMESTAMPING_RX_HARDWARE: u32 = 1 << 2;
+const SOF_TIMESTAMPING_RX_SOFTWARE: u32 = 1 << 3;
+const SOF_TIMESTAMPING_SOFTWARE: u32 = 1 << 4;
+const SOF_TIMESTAMPING_RAW_HARDWARE: u32 = 1 << 6;
+
+// HWTSTAMP_FILTER_* values relevant for PTPv1 over UDP (rx_filters is a bitmask of these)
+const HWTSTAMP_FILTER_ALL: u32 = 1;
+const HWTSTAMP_FILTER_PTP_V1_L4_EVENT: u32 = 3;
+const HWTSTAMP_FILTER_PTP_V1_L4_SYNC: u32 = 4;
+
+const HWTSTAMP_TX_OFF: i32 = 0;
+
+/// Kernel `struct ethtool_ts_info`
+#[repr(C)]
+#[derive(Default)]
+struct EthtoolTsInfo {
+    cmd: u32,
+    so_timestamping: u32,
+    phc_index: i32,
+    tx_types: u32,
+    tx_reserved: [u32; 3],
+    rx_filters: u32,
+    rx_reserved: [u32; 3],
+}
+
+/// Timestamping capabilities of one interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimestampingInfo {
+    /// SOF_TIMESTAMPING_* capability flags
+    pub so_timestamping: u32,
+    /// PTP hardware clock index (`/dev/ptpN`), None if the NIC has no PHC
+    pub phc_index: Option<i32>,
+    /// Bitmask of supported HWTSTAMP_TX_* types
+    pub tx_types: u32,
+    /// Bitmask of supported HWTSTAMP_FILTER_* receive filters
+    pub rx_filters: u32,
+}
+
+impl TimestampingInfo {
+    /// NIC can timestamp received packets in hardware (raw PHC time).
+    pub fn supports_hw_rx(&self) -> bool {
+        let required = SOF_TIMESTAMPING_RX_HARDWARE | SOF_TIMESTAMPING_RAW_HARDWARE;
+        self.so_timestamping & required == required
+    }
+
+    /// NIC has a receive filter that catches PTPv1 event messages (Dante).
+    pub fn supports_ptp_v1_filter(&self) -> bool {
+        let mask = (1 << HWTSTAMP_FILTER_ALL)
+            | (1 << HWTSTAMP_FILTER_PTP_V1_L4_EVENT)
+            | (1 << HWTSTAMP_FILTER_PTP_V1_L4_SYNC);
+        self.rx_filters & mask != 0
+    }
+
+    /// Receive filter to request: PTPv1 event messages, else everything.
+    fn ptp_v1_rx_filter(&self) -> Option<u32> {
+        [HWTSTAMP_FILTER_PTP_V1_L4_EVENT, HWTSTAMP_FILTER_ALL]
+            .into_iter()
+            .find(|filter| self.rx_filters & (1 << filter) != 0)
+    }
+
+    /// Hardware timestamping of Dante PTP is possible on this interface.
+    pub fn hardware_usable(&self) -> bool {
+        self.supports_hw_rx() && self.phc_index.is_some() && self.supports_ptp_v1_filter()
+    }
+
+    /// Capability names as printed by `ethtool -T`.
+    pub fn capability_names(&self) -> Vec<&'static str> {
+        [
+            (SOF_TIMESTAMPING_TX_HARDWARE, "hardware-transmit"),
+            (SOF_TIMESTAMPING_TX_SOFTWARE, "software-transmit"),
+            (SOF_TIMESTAMPING_RX_HARDWARE, "hardware-receive"),
+            (SOF_TIMESTAMPING_RX_SOFTWARE, "software-receive"),
+            (SOF_TIMESTAMPING_SOFTWARE, "software-system-clock"),
+            (SOF_TIMESTAMPING_RAW_HARDWARE, "hardware-raw-clock"),
+        ]
+        .iter()
+        .filter(|(bit, _)| self.so_timestamping & bit != 0)
+        .map(|(_, name)| *name)
+        .collect()
+    }
+}
+
+/// Query timestamping capabilities of `ifname` (equivalent of `ethtool -T <ifname>`).
+pub fn query_ts_info(ifname: &str) -> Result<TimestampingInfo> {
+    let mut ifr = ifreq_for(ifname)?;
+    let sock = UdpSocket::bind("0.0.0.0:0")?;
+    let mut info = EthtoolTsInfo {
+        cmd: ETHTOOL_GET_TS_INFO,
+        ..Default::default()
+    };
+
+    ifr.ifr_ifru.ifru_data = &mut info as *mut EthtoolTsInfo as *mut libc::c_char;
+
+    let ret = unsafe { libc::ioctl(sock.as_raw_fd(), libc::SIOCETHTOOL as _, &mut ifr) };
+    if ret < 0 {
+        return Err(anyhow!(
+            "ETHTOOL_GET_TS_INFO on {} failed: {}",
+            ifname,
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    Ok(TimestampingInfo {
+        so_timestamping: info.so_timestamping,
+        phc_index: (info.phc_index >= 0).then_some(info.phc_index),
+        tx_types: info.tx_types,
+        rx_filters: info.rx_filters,
+    })
+}
+
+fn ifreq_for(ifname: &str) -> Result<libc::ifreq> {
+    if ifname.is_empty() || ifname.len() >= libc::IFNAMSIZ {
+        return Err(anyhow!("invalid interface name '{}'", ifname));
+    }
+    let mut ifr: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in ifr.ifr_name.iter_mut().zip(ifname.bytes()) {
+        *dst = src as libc::c_char;
+    }
+    Ok(ifr)
+}
+
+/// Switch on hardware receive timestamping of PTPv1 event messages on `ifname`
+/// (`hwstamp_ctl -r`). NIC-wide and needs CAP_NET_ADMIN. Returns the HWTSTAMP_FILTER_*
+/// the driver applied - it may widen the request, e.g. to all packets.
+pub fn enable_hw_rx_filter(ifname: &str, ts: &TimestampingInfo) -> Result<u32> {
+    let filter = ts
+        .ptp_v1_rx_filter()
+        .ok_or_else(|| anyhow!("{} has no PTPv1 hardware receive filter", ifname))?;
+
+    let sock = UdpSocket::bind("0.0.0.0:0")?;
+    let mut config = libc::hwtstamp_config {
+        flags: 0,
+        tx_type: HWTSTAMP_TX_OFF,
+        rx_filter: filter as i32,
+    };
+    let mut ifr = ifreq_for(ifname)?;
+    ifr.ifr_ifru.ifru_data = &mut config as *mut libc::hwtstamp_config as *mut libc::c_char;
+
+    let ret = unsafe { libc::ioctl(sock.as_raw_fd(), libc::SIOCSHWTSTAMP as _, &mut ifr) };
+    if ret < 0 {
+        return Err(anyhow!(
+            "SIOCSHWTSTAMP on {} failed: {}",
+            ifname,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(config.rx_filter as u32)
+}
+
+/// Log the interface's timestamping capabilities at startup.
+pub fn log_capabilities(ifname: &str) -> Option<TimestampingInfo> {
+    match query_ts_info(ifname) {
+        Ok(ts) => {
+            info!(
+                "[HWTS] {}: {} | PHC: {}",
+                ifname,
+                ts.capability_names().join(", "),
+                ts.phc_index
+                    .map(|i| format!("/dev/ptp{}", i))
+                    .unwrap_or_else(|| "none".to_string())
+            );
+            if ts.hardware_usable() {
+                info!("[HWTS] Hardware timestamping of PTPv1 supported - sub-µs sync achievable");
+            } else {
+                info!("[HWTS] No usable hardware timestamping - software timestamps only");
+            }
+            Some(ts)
+        }
+        Err(e) => {
+            info!("[HWTS] Could not query timestamping capabilities: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hardware_capable_nic() {
+        let ts = TimestampingInfo {
+            so_timestamping: SOF_TIMESTAMPING_TX_HARDWARE
+                | SOF_TIMESTAMPING_RX_HARDWARE
+                | SOF_TIMESTAMPING_RAW_HARDWARE
+                | SOF_TIMESTAMPING_RX_SOFTWARE,
+            phc_index: Some(0),
+            tx_types: 0b11,
+            rx_filters: 1 << HWTSTAMP_FILTER_ALL,
+        };
+        assert!(ts.hardware_usable());
+        assert_eq!(ts.ptp_v1_rx_filter(), Some(HWTSTAMP_FILTER_ALL));
+        assert_eq!(
+            ts.capability_names(),
+            vec![
+                "hardware-transmit",
+                "hardware-receive",
+                "software-receive",
+                "hardware-raw-clock"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_software_only_nic() {
+        let ts = TimestampingInfo {
+            so_timestamping: SOF_TIMESTAMPING_RX_SOFTWARE | SOF_TIMESTAMPING_SOFTWARE,
+            phc_index: None,
+            tx_types: 0,
+            rx_filters: 0,
+        };
+        assert!(!ts.supports_hw_rx());
+        assert_eq!(ts.ptp_v1_rx_filter(), None);
+        assert!(
+            !ts.hardware_usable(),
+            "Software-only NIC is not hardware capable"
+        );
+    }
+
+    #[test]
+    fn test_ptp_v1_event_filter_preferred_over_all() {
+        let ts = TimestampingInfo {
+            so_timestamping: SOF_TIMESTAMPING_RX_HARDWARE | SOF_TIMESTAMPING_RAW_HARDWARE,
+            phc_index: Some(1),
+            tx_types: 0,
+            rx_filters: (1 << HWTSTAMP_FILTER_ALL) | (1 << HWTSTAMP_FILTER_PTP_V1_L4_EVENT),
+        };
+        assert_eq!(ts.ptp_v1_rx_filter(), Some(HWTSTAMP_FILTER_PTP_V1_L4_EVENT));
+    }
+
+    #[test]
+    fn test_loopback_query_does_not_panic() {
+        // lo supports software timestamping only (or the ioctl may be refused) - either is fine
+        if let Ok(ts) = query_ts_info("lo") {
+            assert!(!ts.hardware_usable());
+        }
+        assert!(query_ts_info("").is_err());
+    }
+}
@@ -0,0 +1,29 @@
+//! Sync state transitions for applications embedding the controller.
+//!
+//! The controller logs these transitions; an application that needs to react to
+//! them registers a callback with `PtpController::set_event_callback` instead of
+//! parsing the log. Register it right after `PtpController::new`, before the
+//! initial NTP sync and the receive loop: earlier events are not replayed.
+
+/// A state transition of the controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncEvent {
+    /// The grandmaster identity changed (`previous` is None for the first one seen)
+    GrandmasterChanged {
+        previous: Option<[u8; 6]>,
+        current: [u8; 6],
+    },
+    Locked,
+    Unlocked,
+    /// The system clock was stepped by `ns` (positive = forward)
+    Stepped {
+        ns: i64,
+    },
+    /// PTP packets stopped: the learned frequency is held
+    Holdover,
+    /// NTP keeps UTC alignment while PTP is offline
+    NtpFallback,
+}
+
+/// Receives every `SyncEvent`, on the controller's thread: it must return quickly.
+pub type EventCallback = Box<dyn Fn(SyncEvent) + Send>;
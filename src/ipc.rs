@@ -0,0 +1,102 @@
+//! Framing of the status IPC stream (service -> tray named pipe, Unix status socket).
+//!
+//! Each frame is a little-endian `u32` payload length followed by the JSON payload.
+//! Frames are built in one buffer and written with a single write so a reader never
+//! sees a length without its payload. A length outside `1..=MAX_FRAME_LEN` means
+//! the reader lost sync with the stream; it must drop the connection and reconnect
+//! rather than allocate or wait for bytes that will never come.
+
+use anyhow::{anyhow, Result};
+use std::io::Read;
+use std::time::Duration;
+
+/// Upper bound for one status frame. A status is ~2 KB of JSON; anything far
+/// beyond that is a desynchronized stream, not a real frame.
+pub const MAX_FRAME_LEN: usize = 64 * 1024;
+
+/// Bytes of the length prefix.
+pub const FRAME_HEADER_LEN: usize = 4;
+
+/// A reader waiting longer than this for the rest of a frame should reconnect.
+pub const FRAME_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Length prefix + payload in one buffer (write it with a single `write_all`).
+pub fn encode_frame(payload: &[u8]) -> Result<Vec<u8>> {
+    if payload.is_empty() || payload.len() > MAX_FRAME_LEN {
+        return Err(anyhow!(
+            "IPC frame of {} bytes outside 1..={}",
+            payload.len(),
+            MAX_FRAME_LEN
+        ));
+    }
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+/// Validate a received length prefix. Err means the stream is out of sync.
+pub fn decode_frame_len(header: [u8; FRAME_HEADER_LEN]) -> Result<usize> {
+    let len = u32::from_le_bytes(header) as usize;
+    if len == 0 || len > MAX_FRAME_LEN {
+        return Err(anyhow!(
+            "IPC frame length {} outside 1..={} (stream out of sync)",
+            len,
+            MAX_FRAME_LEN
+        ));
+    }
+    Ok(len)
+}
+
+/// Read one whole frame from a blocking stream and return its payload.
+pub fn read_frame<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    let mut header = [0u8; FRAME_HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    let mut payload = vec![0u8; decode_frame_len(header)?];
+    reader.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_roundtrip() {
+        let payload = br#"{"offset_ns":42}"#;
+        let frame = encode_frame(payload).unwrap();
+        let header: [u8; 4] = frame[..FRAME_HEADER_LEN].try_into().unwrap();
+        let len = decode_frame_len(header).unwrap();
+        assert_eq!(len, payload.len());
+        assert_eq!(&frame[FRAME_HEADER_LEN..], payload);
+    }
+
+    #[test]
+    fn test_read_frame_from_stream() {
+        let mut stream = encode_frame(b"first").unwrap();
+        stream.extend(encode_frame(b"second").unwrap());
+        let mut reader = stream.as_slice();
+        assert_eq!(read_frame(&mut reader).unwrap(), b"first");
+        assert_eq!(read_frame(&mut reader).unwrap(), b"second");
+        assert!(read_frame(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_desynchronized_length_rejected() {
+        // Connecting mid-frame: the "length" is really JSON text ("{\"of")
+        let header: [u8; 4] = br#"{"of"#[..4].try_into().unwrap();
+        assert!(decode_frame_len(header).is_err());
+        assert!(decode_frame_len([0; 4]).is_err());
+        assert!(decode_frame_len(((MAX_FRAME_LEN + 1) as u32).to_le_bytes()).is_err());
+        assert_eq!(
+            decode_frame_len((MAX_FRAME_LEN as u32).to_le_bytes()).unwrap(),
+            MAX_FRAME_LEN
+        );
+    }
+
+    #[test]
+    fn test_oversized_payload_not_framed() {
+        assert!(encode_frame(&vec![b'x'; MAX_FRAME_LEN + 1]).is_err());
+        assert!(encode_frame(b"").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Leap second tracking.
+//!
+//! A master announces a leap second with the leap61/leap59 flags during the last
+//! UTC day before it; the leap takes effect at the following UTC midnight, where
+//! the master's currentUtcOffset changes by one. The system clock runs UTC, so at
+//! that instant it has to move by one second. Done as one deliberate correction,
+//! the servo and NTP tracking never see the second as an offset to chase.
+
+use crate::ptp::{PTP_LEAP_59, PTP_LEAP_61};
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// How close to the leap instant the master's offset change and our correction
+/// are taken as the same leap (clocks a little apart, packets in flight).
+const LEAP_WINDOW_SECS: i64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leap {
+    /// leap61: the last minute of the day has 61 seconds
+    Insert,
+    /// leap59: the last minute of the day has 59 seconds
+    Delete,
+}
+
+impl Leap {
+    /// Leap announced in a PTP flag field (same bits in v1 and v2).
+    pub fn from_flags(flags: u16) -> Option<Self> {
+        if flags & PTP_LEAP_61 != 0 {
+            Some(Leap::Insert)
+        } else if flags & PTP_LEAP_59 != 0 {
+            Some(Leap::Delete)
+        } else {
+            None
+        }
+    }
+
+    /// Direction the UTC clock moves: back for an inserted second.
+    pub fn clock_sign(self) -> i8 {
+        match self {
+            Leap::Insert => -1,
+            Leap::Delete => 1,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LeapTracker {
+    /// Announced leap and the instant it takes effect (Unix seconds)
+    pending: Option<(Leap, i64)>,
+    /// Last leap corrected and when, until the master's offset change confirms it
+    applied: Option<(Leap, i64)>,
+}
+
+impl LeapTracker {
+    /// Leap flags of the sync source at `now_secs` (Unix seconds). Returns the
+    /// newly scheduled leap and its instant, for logging.
+    pub fn announce(&mut self, leap: Option<Leap>, now_secs: i64) -> Option<(Leap, i64)> {
+        match (leap, self.pending) {
+            (Some(leap), Some((pending, _))) if leap == pending => None,
+            (Some(leap), _) => {
+                // Flags are still set right after the leap: that one is done
+                if self.applied.is_some_and(|(applied, at)| {
+                    applied == leap && now_secs - at < SECS_PER_DAY / 2
+                }) {
+                    return None;
+                }
+                let at = (now_secs.div_euclid(SECS_PER_DAY) + 1) * SECS_PER_DAY;
+                self.pending = Some((leap, at));
+                self.pending
+            }
+            // Withdrawn well before the instant. Flags cleared right at it are
+            // the master's own leap: keep it, the instant is a moment away.
+            (None, Some((_, at))) if now_secs < at - LEAP_WINDOW_SECS => {
+                self.pending = None;
+                None
+            }
+            (None, _) => None,
+        }
+    }
+
+    /// The pending leap, once its instant has come.
+    pub fn due(&mut self, now_secs: i64) -> Option<Leap> {
+        let (leap, at) = self.pending?;
+        if now_secs < at {
+            return None;
+        }
+        self.pending = None;
+        self.applied = Some((leap, now_secs));
+        Some(leap)
+    }
+
+    /// The master's currentUtcOffset changed. Returns the leap to correct now:
+    /// an announced one the master reached first, or an unannounced one. None
+    /// if this is not a leap, or the change that follows our own correction.
+    pub fn master_offset_changed(&mut self, old: i16, new: i16, now_secs: i64) -> Option<Leap> {
+        // 0 -> 37 is a master starting to report TAI, not a leap
+        if old == 0 {
+            return None;
+        }
+        // TAI - UTC grows by one with an inserted second
+        let leap = match new.checked_sub(old)? {
+            1 => Leap::Insert,
+            -1 => Leap::Delete,
+            _ => return None,
+        };
+
+        if let Some((applied, at)) = self.applied {
+            if applied == leap && (now_secs - at).abs() <= LEAP_WINDOW_SECS {
+                self.applied = None;
+                return None;
+            }
+        }
+        self.pending = None;
+        self.applied = Some((leap, now_secs));
+        Some(leap)
+    }
+
+    /// A leap is announced and not yet corrected.
+    pub fn pending(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2016-12-31 12:00:00 UTC, half a day before the last leap second
+    const NOON: i64 = 1_483_185_600;
+    const MIDNIGHT: i64 = 1_483_228_800;
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(Leap::from_flags(PTP_LEAP_61), Some(Leap::Insert));
+        assert_eq!(Leap::from_flags(PTP_LEAP_59 | 0x0200), Some(Leap::Delete));
+        assert_eq!(Leap::from_flags(0x0200), None);
+        assert_eq!(Leap::Insert.clock_sign(), -1);
+    }
+
+    #[test]
+    fn test_announced_leap_is_due_at_midnight_once() {
+        let mut leap = LeapTracker::default();
+        assert_eq!(
+            leap.announce(Some(Leap::Insert), NOON),
+            Some((Leap::Insert, MIDNIGHT))
+        );
+        assert_eq!(leap.announce(Some(Leap::Insert), NOON + 1), None);
+        assert!(leap.pending());
+
+        assert_eq!(leap.due(MIDNIGHT - 1), None);
+        assert_eq!(leap.due(MIDNIGHT), Some(Leap::Insert));
+        assert_eq!(leap.due(MIDNIGHT + 1), None);
+
+        // The master's flags linger and then its offset follows: nothing more to do
+        assert_eq!(leap.announce(Some(Leap::Insert), MIDNIGHT + 1), None);
+        assert_eq!(leap.master_offset_changed(36, 37, MIDNIGHT + 2), None);
+        assert!(!leap.pending());
+    }
+
+    #[test]
+    fn test_master_offset_change() {
+        let mut leap = LeapTracker::default();
+        // Startup and implausible jumps are not leaps
+        assert_eq!(leap.master_offset_changed(0, 37, NOON), None);
+        assert_eq!(leap.master_offset_changed(37, 35, NOON), None);
+
+        // Master reaches the announced instant first
+        leap.announce(Some(Leap::Insert), NOON);
+        assert_eq!(
+            leap.master_offset_changed(36, 37, MIDNIGHT - 1),
+            Some(Leap::Insert)
+        );
+        assert_eq!(leap.due(MIDNIGHT), None);
+
+        // Unannounced
+        let mut leap = LeapTracker::default();
+        assert_eq!(leap.master_offset_changed(37, 36, NOON), Some(Leap::Delete));
+    }
+
+    #[test]
+    fn test_withdrawn_announcement() {
+        let mut leap = LeapTracker::default();
+        leap.announce(Some(Leap::Insert), NOON);
+        leap.announce(None, NOON + 60);
+        assert!(!leap.pending());
+
+        // Cleared at the instant itself: the master has just leapt
+        leap.announce(Some(Leap::Insert), NOON);
+        leap.announce(None, MIDNIGHT - 1);
+        assert_eq!(leap.due(MIDNIGHT), Some(Leap::Insert));
+    }
+}
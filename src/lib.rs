@@ -1,17 +1,51 @@
+pub mod arrival_gate;
+pub mod bmca;
 pub mod clock;
 pub mod config;
 pub mod controller;
+pub mod convergence;
+pub mod delay;
+pub mod diagnostics;
+pub mod drift_file;
+#[cfg(target_os = "linux")]
+pub mod ethtool;
+pub mod events;
+pub mod ipc;
+pub mod leap;
+pub mod lock_detector;
+pub mod loop_timing;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod monitor;
 pub mod net;
 pub mod ntp;
+pub mod ntp_check;
 pub mod ntp_server;
+pub mod one_step;
+pub mod phase_outlier;
 pub mod ptp;
+pub mod rate_audit;
+pub mod recorder;
+#[cfg(target_os = "linux")]
+pub mod rtc;
+pub mod selftest;
+pub mod servo;
+pub mod servo_trace;
+pub mod source_limit;
 pub mod spike_filter;
 pub mod status;
+pub mod status_bus;
+#[cfg(unix)]
+pub mod status_socket;
+pub mod sync_rate;
 pub mod time_server;
 pub mod traits;
 
-#[cfg(windows)]
+#[cfg(all(windows, feature = "net-pcap"))]
 pub mod net_pcap;
 
-#[cfg(windows)]
+#[cfg(all(windows, feature = "net-winsock"))]
 pub mod net_winsock;
+
+#[cfg(windows)]
+pub mod w32time;
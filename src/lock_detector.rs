@@ -0,0 +1,122 @@
+//! Offset lock detector with hysteresis.
+//!
+//! Lock is declared once the filtered phase offset has stayed within the lock
+//! threshold for a number of consecutive samples, and dropped only when it leaves
+//! the wider unlock threshold. An offset wandering around a single threshold would
+//! otherwise toggle the state (and the tray icon) on every sample.
+
+#[derive(Debug)]
+pub struct LockDetector {
+    lock_ns: i64,
+    unlock_ns: i64,
+    hold_samples: usize,
+    hold: usize,
+    locked: bool,
+}
+
+impl LockDetector {
+    /// `unlock_ns` below `lock_ns` would leave no hysteresis band; it is raised to `lock_ns`.
+    pub fn new(lock_ns: i64, unlock_ns: i64, hold_samples: usize) -> Self {
+        Self {
+            lock_ns,
+            unlock_ns: unlock_ns.max(lock_ns),
+            hold_samples,
+            hold: 0,
+            locked: false,
+        }
+    }
+
+    /// Feed the next filtered offset (ns). Returns the new state on a transition.
+    pub fn update(&mut self, offset_ns: i64) -> Option<bool> {
+        let abs = offset_ns.unsigned_abs();
+        if self.locked {
+            if abs > self.unlock_ns as u64 {
+                self.locked = false;
+                self.hold = 0;
+                return Some(false);
+            }
+            return None;
+        }
+
+        if abs <= self.lock_ns as u64 {
+            self.hold += 1;
+        } else {
+            self.hold = 0;
+        }
+        if self.hold >= self.hold_samples.max(1) {
+            self.locked = true;
+            return Some(true);
+        }
+        None
+    }
+
+    /// Consecutive samples within the lock threshold while unlocked.
+    pub fn hold(&self) -> usize {
+        self.hold
+    }
+
+    /// Offset (ns) beyond which a lock is dropped.
+    pub fn unlock_ns(&self) -> i64 {
+        self.unlock_ns
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locks_after_hold_and_unlocks_beyond_unlock_threshold() {
+        let mut lock = LockDetector::new(1_000, 5_000, 3);
+        assert_eq!(lock.update(800), None);
+        assert_eq!(lock.update(-900), None);
+        // Leaving the band restarts the hold
+        assert_eq!(lock.update(1_200), None);
+        assert_eq!(lock.hold(), 0);
+        assert_eq!(lock.update(500), None);
+        assert_eq!(lock.update(-500), None);
+        assert_eq!(lock.update(0), Some(true));
+
+        // Between the thresholds: stays locked
+        assert_eq!(lock.update(4_999), None);
+        assert_eq!(lock.update(-5_000), None);
+        assert_eq!(lock.update(-5_001), Some(false));
+        assert!(!lock.is_locked());
+    }
+
+    #[test]
+    fn test_no_flapping_at_the_boundary() {
+        let mut lock = LockDetector::new(1_000, 5_000, 3);
+        for _ in 0..3 {
+            lock.update(0);
+        }
+        assert!(lock.is_locked());
+
+        // Offset dithering across the lock threshold: one state throughout
+        for i in 0..100 {
+            let offset = if i % 2 == 0 { 900 } else { 1_100 };
+            assert_eq!(lock.update(offset), None, "sample {}", i);
+        }
+        assert!(lock.is_locked());
+
+        // Unlocked, dithering across the unlock threshold never re-locks
+        lock.update(6_000);
+        for i in 0..100 {
+            let offset = if i % 2 == 0 { 4_900 } else { 5_100 };
+            assert_eq!(lock.update(offset), None, "sample {}", i);
+        }
+        assert!(!lock.is_locked());
+    }
+
+    #[test]
+    fn test_unlock_threshold_below_lock_is_raised() {
+        let mut lock = LockDetector::new(2_000, 500, 1);
+        assert_eq!(lock.update(1_500), Some(true));
+        assert_eq!(lock.update(1_900), None, "no band would unlock here");
+        assert_eq!(lock.update(2_001), Some(false));
+    }
+}
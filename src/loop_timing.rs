@@ -0,0 +1,115 @@
+//! Per-iteration loop timing instrumentation.
+//!
+//! A stalled main loop (slow syscall, scheduler preemption) delays T2 and shows up
+//! as an offset spike that looks exactly like network jitter. Timing each iteration
+//! and its phases separates local scheduling jitter from network jitter.
+//!
+//! Phases are exclusive: `parse` excludes the servo, `servo` excludes the clock write.
+//! Anything not covered (NTP tracking, status updates) lands in `other`.
+
+use log::warn;
+use std::time::Duration;
+
+/// Time spent in each phase of one loop iteration.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseTimes {
+    /// Waiting for / reading a packet from the network
+    pub recv: Duration,
+    /// Header parsing and Sync/FollowUp handling
+    pub parse: Duration,
+    /// Servo calculation
+    pub servo: Duration,
+    /// Clock write syscall (adjust_frequency) - main suspect on Windows
+    pub clock_write: Duration,
+    /// Everything else (NTP tracking, cleanup)
+    pub other: Duration,
+}
+
+/// Tracks iteration times and flags iterations exceeding a threshold.
+#[derive(Debug)]
+pub struct LoopTiming {
+    warn_threshold: Duration,
+    last_iteration: Duration,
+    max_iteration: Duration,
+    slow_count: u64,
+}
+
+impl LoopTiming {
+    pub fn new(warn_threshold: Duration) -> Self {
+        Self {
+            warn_threshold,
+            last_iteration: Duration::ZERO,
+            max_iteration: Duration::ZERO,
+            slow_count: 0,
+        }
+    }
+
+    /// Record a finished iteration. Returns true if it exceeded the warn threshold.
+    pub fn finish(&mut self, total: Duration, phases: PhaseTimes) -> bool {
+        self.last_iteration = total;
+        if total > self.max_iteration {
+            self.max_iteration = total;
+        }
+
+        if total <= self.warn_threshold {
+            return false;
+        }
+
+        self.slow_count += 1;
+        warn!(
+            "[Loop] Slow iteration #{}: {}us (recv:{}us parse:{}us servo:{}us clock:{}us other:{}us)",
+            self.slow_count,
+            total.as_micros(),
+            phases.recv.as_micros(),
+            phases.parse.as_micros(),
+            phases.servo.as_micros(),
+            phases.clock_write.as_micros(),
+            phases.other.as_micros()
+        );
+        true
+    }
+
+    pub fn last_iteration(&self) -> Duration {
+        self.last_iteration
+    }
+
+    pub fn max_iteration(&self) -> Duration {
+        self.max_iteration
+    }
+
+    pub fn slow_count(&self) -> u64 {
+        self.slow_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fast_iteration_not_flagged() {
+        let mut timing = LoopTiming::new(Duration::from_millis(1));
+        assert!(!timing.finish(Duration::from_micros(200), PhaseTimes::default()));
+        assert_eq!(timing.slow_count(), 0);
+        assert_eq!(timing.last_iteration(), Duration::from_micros(200));
+    }
+
+    #[test]
+    fn test_slow_iteration_flagged_and_max_kept() {
+        let mut timing = LoopTiming::new(Duration::from_millis(1));
+        let phases = PhaseTimes {
+            clock_write: Duration::from_millis(4),
+            ..Default::default()
+        };
+        assert!(timing.finish(Duration::from_millis(5), phases));
+        assert!(!timing.finish(Duration::from_micros(100), PhaseTimes::default()));
+
+        assert_eq!(timing.slow_count(), 1);
+        assert_eq!(timing.last_iteration(), Duration::from_micros(100));
+        assert_eq!(
+            timing.max_iteration(),
+            Duration::from_millis(5),
+            "Max should survive later fast iterations"
+        );
+    }
+}
@@ -2,6 +2,9 @@ use anyhow::Result;
 use clap::Parser;
 use log::{error, info, warn};
 use std::fs::File;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::Path;
+#[cfg(unix)]
 use std::process::Command;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
@@ -12,19 +15,17 @@ use std::time::{Duration, Instant};
 use anyhow::anyhow;
 #[cfg(unix)]
 use nix::fcntl::{flock, FlockArg};
-#[cfg(unix)]
-use std::io::ErrorKind;
-#[cfg(unix)]
+#[cfg(any(unix, feature = "net-socket"))]
 use std::net::UdpSocket;
 #[cfg(unix)]
 use std::os::unix::io::AsRawFd;
-#[cfg(unix)]
+#[cfg(any(unix, feature = "net-socket"))]
 use std::time::SystemTime;
 
 #[cfg(windows)]
 use windows::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE};
 #[cfg(windows)]
-use windows::Win32::Media::timeBeginPeriod;
+use windows::Win32::Media::{timeBeginPeriod, timeEndPeriod};
 #[cfg(windows)]
 use windows::Win32::System::Threading::{
     CreateMutexW, GetCurrentProcess, SetPriorityClass, HIGH_PRIORITY_CLASS, REALTIME_PRIORITY_CLASS,
@@ -76,13 +77,19 @@ use windows_service::{
 };
 
 // Use library crate modules
-#[cfg(windows)]
+#[cfg(all(windows, feature = "net-pcap"))]
 use dantesync::net_pcap;
-#[cfg(unix)]
+#[cfg(all(windows, feature = "net-winsock"))]
+use dantesync::net_winsock;
+#[cfg(any(unix, feature = "net-socket"))]
 use dantesync::ptp;
-use dantesync::{clock, config, controller, net, ntp, ntp_server, status, time_server, traits};
+use dantesync::{
+    clock, config, controller, drift_file, monitor, net, ntp, ntp_check, ntp_server, recorder,
+    selftest, servo_trace, status, status_bus, time_server, traits,
+};
 
-use config::{NtpServerConfig, SystemConfig};
+use clock::SystemClock;
+use config::{NtpServerConfig, PtpConfig, SystemConfig, LEGACY_KI, LEGACY_KP};
 use controller::PtpController;
 use serde::{Deserialize, Serialize};
 use status::SyncStatus;
@@ -94,8 +101,18 @@ use traits::PtpNetwork;
 /// All other parameters auto-adjust based on platform defaults
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Config {
+    /// NTP server, or a comma-separated list tried in order (failover)
     ntp_server: String,
 
+    /// Source address for NTP client queries: "interface" (the PTP interface IP)
+    /// or an IP address. Omitted = OS-chosen route.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ntp_bind: Option<String>,
+
+    /// Interface (name or IPv4 address) to receive PTP on. Omitted = auto-detect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ptp_interface: Option<String>,
+
     /// NTP server mode configuration (optional - disabled by default)
     /// When enabled, this machine becomes an NTP server for the network
     #[serde(default)]
@@ -110,47 +127,72 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             ntp_server: "10.77.8.2".to_string(),
+            ntp_bind: None,
+            ptp_interface: None,
             ntp_server_mode: NtpServerConfig::default(),
             system: SystemConfig::default(),
         }
     }
 }
 
+/// Bring a config file written by an older version up to the current schema.
+/// Returns true if anything changed.
+fn migrate_config(json: &mut serde_json::Value) -> bool {
+    let mut needs_migration = false;
+
+    // Migrate: add _ntp_server_examples if missing
+    if json.get("_ntp_server_examples").is_none() {
+        json["_ntp_server_examples"] = serde_json::Value::String(
+            "sk.pool.ntp.org, europe.pool.ntp.org, time.google.com, time.cloudflare.com"
+                .to_string(),
+        );
+        needs_migration = true;
+    }
+
+    // Migrate: add ntp_server_mode if missing
+    if json.get("ntp_server_mode").is_none() {
+        json["ntp_server_mode"] = serde_json::json!({
+            "enabled": false,
+            "port": 123,
+            "stratum": 3
+        });
+        needs_migration = true;
+    }
+
+    // Migrate: servo gains written while kp/ki were unused placeholders
+    if let Some(servo) = json.pointer_mut("/system/servo") {
+        let legacy = servo.get("kp").and_then(|v| v.as_f64()) == Some(LEGACY_KP)
+            && servo.get("ki").and_then(|v| v.as_f64()) == Some(LEGACY_KI);
+        if legacy {
+            let defaults = SystemConfig::default().servo;
+            servo["kp"] = serde_json::json!(defaults.kp);
+            servo["ki"] = serde_json::json!(defaults.ki);
+            needs_migration = true;
+        }
+    }
+
+    needs_migration
+}
+
+#[cfg(windows)]
+const CONFIG_PATH: &str = r"C:\ProgramData\DanteSync\config.json";
+#[cfg(not(windows))]
+const CONFIG_PATH: &str = "/etc/dantesync/config.json";
+
 fn load_config() -> Config {
-    #[cfg(windows)]
-    let path = r"C:\ProgramData\DanteSync\config.json";
-    #[cfg(not(windows))]
-    let path = "/etc/dantesync/config.json";
+    load_config_from(Path::new(CONFIG_PATH))
+}
 
+/// Read (and migrate) the config file at `path`, or create it with the defaults.
+fn load_config_from(path: &Path) -> Config {
     if let Ok(content) = std::fs::read_to_string(path) {
         // Try to parse as JSON Value first to check for missing fields
         if let Ok(mut json) = serde_json::from_str::<serde_json::Value>(&content) {
-            let mut needs_migration = false;
-
-            // Migrate: add _ntp_server_examples if missing
-            if json.get("_ntp_server_examples").is_none() {
-                json["_ntp_server_examples"] = serde_json::Value::String(
-                    "sk.pool.ntp.org, europe.pool.ntp.org, time.google.com, time.cloudflare.com"
-                        .to_string(),
-                );
-                needs_migration = true;
-            }
-
-            // Migrate: add ntp_server_mode if missing
-            if json.get("ntp_server_mode").is_none() {
-                json["ntp_server_mode"] = serde_json::json!({
-                    "enabled": false,
-                    "port": 123,
-                    "stratum": 3
-                });
-                needs_migration = true;
-            }
-
             // Write back migrated config
-            if needs_migration {
+            if migrate_config(&mut json) {
                 if let Ok(pretty) = serde_json::to_string_pretty(&json) {
                     let _ = std::fs::write(path, pretty);
-                    log::info!("Config migrated: added ntp_server_mode and NTP examples");
+                    log::info!("Config migrated to the current schema");
                 }
             }
 
@@ -176,25 +218,169 @@ fn load_config() -> Config {
     cfg
 }
 
+/// Merge the CLI overrides into the config (CLI > config file), so `args` and
+/// `config` both hold the effective values.
+fn apply_cli_overrides(args: &mut Args, config: &mut Config) {
+    args.ntp_server = Some(resolve_ntp_server(&args.ntp_server, config));
+    args.ntp_bind = args.ntp_bind.take().or_else(|| config.ntp_bind.clone());
+    args.interface = args
+        .interface
+        .take()
+        .or_else(|| config.ptp_interface.clone());
+    config.ntp_server = args.ntp_server.clone().unwrap_or_default();
+    config.ntp_bind = args.ntp_bind.clone();
+    config.ptp_interface = args.interface.clone();
+    if args.serve_bind.is_some() {
+        config.ntp_server_mode.bind = args.serve_bind.clone();
+    }
+    if let Some(force) = args.force_enable_adjustment {
+        config.system.clock.force_enable_adjustment = force;
+    }
+}
+
 /// Resolve the NTP server address: CLI arg takes priority, then config file value.
 fn resolve_ntp_server(cli_ntp: &Option<String>, config: &Config) -> String {
     cli_ntp.clone().unwrap_or_else(|| config.ntp_server.clone())
 }
 
+/// Resolve the NTP client source address ("interface" = the PTP interface IP).
+fn resolve_ntp_bind(spec: &str, iface_ip: Ipv4Addr) -> Result<IpAddr> {
+    if spec.eq_ignore_ascii_case("interface") {
+        return Ok(IpAddr::V4(iface_ip));
+    }
+    spec.parse()
+        .map_err(|_| anyhow::anyhow!("invalid NTP bind address '{}'", spec))
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    #[arg(short, long)]
+    /// Receive PTP on this interface (name or IPv4 address) instead of auto-detecting
+    #[arg(short, long, value_name = "NAME|IP")]
     interface: Option<String>,
 
-    #[arg(long)]
+    /// NTP server for UTC alignment. A comma-separated list fails over in order
+    #[arg(long, value_name = "SERVER[,SERVER...]")]
     ntp_server: Option<String>,
 
     #[arg(long, default_value_t = false)]
     skip_ntp: bool,
 
+    /// PTP frequency only - never step the wall clock (no initial NTP step, no NTP tracking)
+    #[arg(long, default_value_t = false)]
+    no_ntp: bool,
+
+    /// Never step the clock: slew every correction (including the initial NTP offset)
+    #[arg(long, default_value_t = false)]
+    slew_only: bool,
+
     #[arg(long, default_value_t = false)]
     service: bool,
+
+    /// Run the startup NTP sync on a background thread so the PTP loop keeps receiving
+    #[arg(long, default_value_t = false)]
+    background_ntp_sync: bool,
+
+    /// Initial NTP sync: take N samples and use the one with the lowest round-trip delay
+    #[arg(long, default_value_t = 4, value_name = "N")]
+    ntp_burst: usize,
+
+    /// Send NTP queries from this address: "interface" (the PTP interface) or an IP
+    #[arg(long, value_name = "IP|interface")]
+    ntp_bind: Option<String>,
+
+    /// Serve NTP / time queries only on this address or interface (dual-homed hosts)
+    #[arg(long, value_name = "IP|NAME")]
+    serve_bind: Option<String>,
+
+    /// Print the network interfaces usable with --interface and exit
+    #[arg(long, default_value_t = false)]
+    list_interfaces: bool,
+
+    /// Print the effective configuration (config file + CLI overrides + platform defaults) as config.json and exit
+    #[arg(long, default_value_t = false)]
+    dump_config: bool,
+
+    /// Print the offset of every Sync pair like `ping`, then statistics. Never adjusts the clock
+    #[arg(long, default_value_t = false)]
+    monitor: bool,
+
+    /// Run the full sync loop but never adjust the clock: frequency changes and
+    /// steps are only logged (validation before deployment, no admin rights needed)
+    #[arg(long, default_value_t = false, conflicts_with = "monitor")]
+    dry_run: bool,
+
+    /// With --monitor: exit after N samples
+    #[arg(short = 'c', long, value_name = "N", requires = "monitor")]
+    count: Option<usize>,
+
+    /// Run the built-in end-to-end check (simulated grandmaster + clock, no network), print PASS/FAIL and exit
+    #[arg(long, default_value_t = false)]
+    selftest: bool,
+
+    /// (Windows) Do not request 1ms timer resolution (timeBeginPeriod) - saves power, coarser loop sleep
+    #[arg(long, default_value_t = false)]
+    no_high_res_timer: bool,
+
+    /// (Windows) Enable time adjustment if found disabled at startup (default: config, true).
+    /// `false` respects a managed time policy and fails with an error instead
+    #[arg(long, value_name = "BOOL")]
+    force_enable_adjustment: Option<bool>,
+
+    /// (Windows) Stop the Windows Time service (w32time) for this session and restart it on exit
+    #[arg(long, default_value_t = false)]
+    stop_w32time: bool,
+
+    /// (Windows) Refuse to start while the Windows Time service competes for the clock
+    #[arg(long, default_value_t = false)]
+    require_exclusive: bool,
+
+    /// Retries (exponential backoff from 500ms) when joining the PTP multicast group fails
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    multicast_join_retries: u32,
+
+    /// Keep running if the multicast join still fails after the retries (no IGMP membership;
+    /// PTP only arrives if the switch floods multicast)
+    #[arg(long, default_value_t = false)]
+    multicast_join_optional: bool,
+
+    /// Time each loop iteration (recv/parse/servo/clock write) and warn when one exceeds this (microseconds)
+    #[arg(long, value_name = "US")]
+    loop_timing_warn_us: Option<u64>,
+
+    /// Independent NTP server to cross-check our time against (monitoring only, never steps)
+    #[arg(long, value_name = "SERVER")]
+    check_ntp: Option<String>,
+
+    /// Alert threshold for --check-ntp disagreement (microseconds)
+    #[arg(long, default_value_t = ntp_check::DEFAULT_CHECK_THRESHOLD_US)]
+    check_ntp_threshold_us: i64,
+
+    /// Record every received PTP packet (kernel/driver + app timestamps) to a JSON-lines file
+    #[arg(long, value_name = "FILE")]
+    record: Option<std::path::PathBuf>,
+
+    /// Append every status update to a JSON-lines file
+    #[arg(long, value_name = "FILE")]
+    status_log: Option<std::path::PathBuf>,
+
+    /// Write the servo internals of every sample to a CSV file (for offline tuning)
+    #[arg(long, value_name = "FILE")]
+    servo_trace: Option<std::path::PathBuf>,
+
+    /// Accept PTP multicast sent from this host (testing with the `ptpgen` generator)
+    #[arg(long, default_value_t = false)]
+    allow_loopback: bool,
+
+    /// (Linux/macOS) Serve the live status on this Unix socket (read it with `dantesync-status`)
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", default_value = dantesync::status_socket::DEFAULT_STATUS_SOCKET)]
+    status_socket: std::path::PathBuf,
+
+    /// Serve Prometheus metrics at http://ADDR/metrics (e.g. 0.0.0.0:9469). Off by default
+    #[cfg(feature = "metrics")]
+    #[arg(long, value_name = "ADDR")]
+    metrics_addr: Option<std::net::SocketAddr>,
 }
 
 // Concrete Implementations for Traits
@@ -208,23 +394,44 @@ impl NtpSource for RealNtpSource {
     }
 }
 
-// Legacy UDP-based PTP network (used on Linux with kernel timestamping)
-#[cfg(unix)]
+// UDP-based PTP network (kernel timestamping on Unix; net-socket backend on Windows)
+#[cfg(any(unix, feature = "net-socket"))]
 struct RealPtpNetwork {
     sock_event: UdpSocket,
     sock_general: UdpSocket,
+    /// Set when `ptp.hw_timestamping` enabled NIC timestamps on the event socket
+    hw_ts: Option<net::HwTimestampSelector>,
+    /// The PTP multicast group on the event port (Delay_Req)
+    delay_req_dest: std::net::SocketAddr,
+    /// Other sockets served by the main loop (time server): waiting for PTP
+    /// packets also wakes up for these
+    #[cfg(unix)]
+    wake_sockets: Vec<UdpSocket>,
 }
 
 #[cfg(unix)]
+impl RealPtpNetwork {
+    /// Also wake `poll_readable` when `socket` has data.
+    fn wake_on(&mut self, socket: UdpSocket) {
+        self.wake_sockets.push(socket);
+    }
+}
+
+#[cfg(any(unix, feature = "net-socket"))]
 impl PtpNetwork for RealPtpNetwork {
     fn recv_packet(
         &mut self,
     ) -> Result<Option<(Vec<u8>, usize, SystemTime, Option<std::net::Ipv4Addr>)>> {
         let mut buf = [0u8; 2048];
 
-        // Check Event Socket first
-        match net::recv_with_timestamp(&self.sock_event, &mut buf) {
-            Ok(Some((size, ts, source_ip))) => {
+        // Check Event Socket first (the only one with hardware timestamps - T2 of Sync)
+        match net::recv_with_timestamps(&self.sock_event, &mut buf) {
+            Ok(Some((size, timestamps, source_ip))) => {
+                let ts = match &mut self.hw_ts {
+                    Some(hw_ts) => hw_ts.select(timestamps),
+                    None => timestamps.software,
+                }
+                .unwrap_or_else(SystemTime::now);
                 return Ok(Some((buf[..size].to_vec(), size, ts, source_ip)));
             }
             Ok(None) => {} // Continue to check general
@@ -245,45 +452,68 @@ impl PtpNetwork for RealPtpNetwork {
 
     fn reset(&mut self) -> Result<()> {
         // Drain buffers to prevent processing old packets after a clock step
-        let mut buf = [0u8; 2048];
-        loop {
-            match self.sock_event.recv_from(&mut buf) {
-                Ok(_) => continue,
-                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
-                Err(_) => break,
-            }
-        }
-        loop {
-            match self.sock_general.recv_from(&mut buf) {
-                Ok(_) => continue,
-                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
-                Err(_) => break,
-            }
+        let drained = net::drain_socket(&self.sock_event) + net::drain_socket(&self.sock_general);
+        if drained > 0 {
+            log::debug!("[Net] Discarded {} queued packets", drained);
         }
         Ok(())
     }
-}
 
-// Windows uses Npcap for precise packet timestamps with HostHighPrec mode
-// See net_pcap::NpcapPtpNetwork - uses KeQuerySystemTimePrecise() for synchronized timestamps
+    fn send_packet(&mut self, data: &[u8]) -> Result<()> {
+        self.sock_event.send_to(data, self.delay_req_dest)?;
+        Ok(())
+    }
 
-fn stop_conflicting_services() {
-    #[cfg(windows)]
-    {
-        info!("Attempting to stop W32Time service...");
-        match Command::new("net").args(["stop", "w32time"]).output() {
-            Ok(out) => {
-                if out.status.success() {
-                    info!("W32Time stopped successfully.");
-                } else {
-                    // Ignore errors if already stopped
-                }
-            }
-            Err(e) => warn!("Failed to execute 'net stop w32time': {}", e),
-        }
+    fn hw_timestamping(&self) -> bool {
+        self.hw_ts.as_ref().is_some_and(|hw_ts| hw_ts.active())
     }
 
     #[cfg(unix)]
+    fn poll_readable(&mut self, timeout: Duration) -> Result<bool> {
+        let mut sockets = vec![&self.sock_event, &self.sock_general];
+        sockets.extend(&self.wake_sockets);
+        net::wait_readable(&sockets, timeout)
+    }
+}
+
+/// `ptp.hw_timestamping`: NIC receive filter plus SO_TIMESTAMPING on the event
+/// socket. None (software timestamps) if the NIC or driver cannot do it.
+#[cfg(target_os = "linux")]
+fn enable_hw_timestamping(
+    iface_name: &str,
+    sock_event: &UdpSocket,
+) -> Option<net::HwTimestampSelector> {
+    use dantesync::ethtool;
+
+    let result = ethtool::query_ts_info(iface_name).and_then(|ts| {
+        if !ts.hardware_usable() {
+            anyhow::bail!("{} cannot timestamp PTPv1 in hardware", iface_name);
+        }
+        let filter = ethtool::enable_hw_rx_filter(iface_name, &ts)?;
+        net::enable_hw_timestamping(sock_event)?;
+        Ok(filter)
+    });
+    match result {
+        Ok(filter) => {
+            info!(
+                "[HWTS] Hardware receive timestamping enabled on {} (HWTSTAMP_FILTER {})",
+                iface_name, filter
+            );
+            Some(net::HwTimestampSelector::new())
+        }
+        Err(e) => {
+            warn!("[HWTS] Using software timestamps: {}", e);
+            None
+        }
+    }
+}
+
+// Windows receive backend is chosen at compile time (net-pcap default, net-winsock, net-socket)
+// See net_pcap::NpcapPtpNetwork - uses KeQuerySystemTimePrecise() for synchronized timestamps
+
+/// Disable the OS time sync on Linux/macOS. Windows: see `w32time::claim_clock`.
+fn stop_conflicting_services() {
+    #[cfg(target_os = "linux")]
     {
         info!("Ensuring system NTP is disabled (timedatectl set-ntp false)...");
         match Command::new("timedatectl")
@@ -294,10 +524,25 @@ fn stop_conflicting_services() {
             Err(e) => warn!("Failed to disable NTP via timedatectl (ignoring): {}", e),
         }
     }
+
+    #[cfg(target_os = "macos")]
+    {
+        info!("Ensuring network time is disabled (systemsetup -setusingnetworktime off)...");
+        match Command::new("systemsetup")
+            .args(["-setusingnetworktime", "off"])
+            .output()
+        {
+            Ok(_) => info!("Network time disabled via systemsetup."),
+            Err(e) => warn!(
+                "Failed to disable network time via systemsetup (ignoring): {}",
+                e
+            ),
+        }
+    }
 }
 
 fn enable_realtime_priority() {
-    #[cfg(unix)]
+    #[cfg(target_os = "linux")]
     {
         unsafe {
             let policy = libc::SCHED_FIFO;
@@ -327,13 +572,55 @@ fn enable_realtime_priority() {
                     warn!("Failed to set Windows priority.");
                 }
             }
+        }
+    }
+}
+
+/// Restores the default Windows timer resolution when dropped.
+#[cfg(windows)]
+struct HighResTimerGuard;
+
+#[cfg(windows)]
+impl Drop for HighResTimerGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = timeEndPeriod(1);
+        }
+        info!("Windows High-Res Timer released (timeEndPeriod).");
+    }
+}
+
+#[cfg(not(windows))]
+struct HighResTimerGuard;
+
+/// Request 1ms Windows timer resolution (timeBeginPeriod) for the lifetime of the guard.
+///
+/// This is system-wide and increases power usage, so it can be turned off with
+/// --no-high-res-timer. It tightens the main loop sleep granularity (~15.6ms default).
+/// Must run BEFORE WindowsClock::new() so the nominal increment it captures for
+/// adjust_frequency() is read with the resolution that stays in effect while running.
+fn enable_high_res_timer(enabled: bool) -> Option<HighResTimerGuard> {
+    #[cfg(windows)]
+    {
+        if !enabled {
+            info!("Windows High-Res Timer disabled (--no-high-res-timer).");
+            return None;
+        }
+        unsafe {
             if timeBeginPeriod(1) == 0 {
                 info!("Windows High-Res Timer (1ms) enabled.");
+                Some(HighResTimerGuard)
             } else {
                 warn!("Failed to set Windows High-Res Timer.");
+                None
             }
         }
     }
+    #[cfg(not(windows))]
+    {
+        let _ = enabled;
+        None
+    }
 }
 
 // --- Single Instance Lock ---
@@ -509,10 +796,15 @@ fn start_ipc_server(status: Arc<RwLock<SyncStatus>>) {
                             continue;
                         }
                     };
-                    if let Ok(bytes) = serde_json::to_vec(&s) {
-                        let len = (bytes.len() as u32).to_le_bytes();
-                        let _ = server.write_all(&len).await;
-                        let _ = server.write_all(&bytes).await;
+                    // One write per frame: the client never sees a length without its payload
+                    match serde_json::to_vec(&s)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|bytes| dantesync::ipc::encode_frame(&bytes))
+                    {
+                        Ok(frame) => {
+                            let _ = server.write_all(&frame).await;
+                        }
+                        Err(e) => error!("IPC status not sent: {}", e),
                     }
                 }
             }
@@ -520,18 +812,292 @@ fn start_ipc_server(status: Arc<RwLock<SyncStatus>>) {
     });
 }
 
-#[cfg(not(windows))]
-fn start_ipc_server(_status: Arc<RwLock<SyncStatus>>) {
-    // No-op on Linux for now (or implement Unix Domain Socket)
+// --- IPC Server (Unix) ---
+#[cfg(unix)]
+fn start_ipc_server(args: &Args, status: Arc<RwLock<SyncStatus>>) {
+    match dantesync::status_socket::StatusSocketServer::bind(&args.status_socket) {
+        Ok(server) => server.spawn(status),
+        Err(e) => warn!(
+            "Status socket not available: {:#} (continuing without it)",
+            e
+        ),
+    }
+}
+
+#[cfg(target_os = "linux")]
+const RTC_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Longest the main loop blocks waiting for packets before running its periodic
+/// tasks (status log, NTP tracking, Delay_Req)
+const LOOP_IDLE_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// Publish the system-vs-RTC offset (None without a readable RTC).
+#[cfg(target_os = "linux")]
+fn update_rtc_offset(status: &RwLock<SyncStatus>) {
+    let offset = match dantesync::rtc::system_offset_ms() {
+        Ok(ms) => Some(ms),
+        Err(e) => {
+            log::debug!("[RTC] Offset unavailable: {}", e);
+            None
+        }
+    };
+    if let Ok(mut s) = status.write() {
+        s.rtc_offset_ms = offset;
+    }
+}
+
+/// Wait for the PTP interface: `--interface` if given, otherwise auto-detect.
+/// None if shut down while waiting.
+fn select_interface(args: &Args, running: &AtomicBool) -> Option<(String, Ipv4Addr)> {
+    loop {
+        let found = match args.interface.as_deref() {
+            Some(spec) => net::find_interface(spec),
+            None => net::get_default_interface(),
+        };
+        match found {
+            Ok(res) => return Some(res),
+            Err(e) => {
+                if !running.load(Ordering::SeqCst) {
+                    return None;
+                }
+                warn!("Waiting for network interface... ({})", e);
+                thread::sleep(Duration::from_secs(5));
+            }
+        }
+    }
+}
+
+#[cfg(any(unix, feature = "net-socket"))]
+type PlatformNetwork = RealPtpNetwork;
+#[cfg(all(windows, feature = "net-winsock"))]
+type PlatformNetwork = net_winsock::WinsockPtpNetwork;
+#[cfg(all(windows, feature = "net-pcap"))]
+type PlatformNetwork = net_pcap::NpcapPtpNetwork;
+
+/// Open the PTP receive path on the selected interface with the platform backend.
+fn open_ptp_network(
+    args: &Args,
+    iface_name: &str,
+    iface_ip: Ipv4Addr,
+    ptp_config: &PtpConfig,
+) -> Result<PlatformNetwork> {
+    if args.allow_loopback {
+        info!("[Net] Accepting PTP multicast sent from this host (--allow-loopback)");
+    }
+    #[cfg(not(target_os = "linux"))]
+    if ptp_config.hw_timestamping {
+        warn!("[HWTS] ptp.hw_timestamping is only supported on Linux");
+    }
+
+    let join_policy = net::JoinPolicy {
+        retries: args.multicast_join_retries,
+        required: !args.multicast_join_optional,
+        ..Default::default()
+    };
+
+    // Platform-specific network setup
+    info!("[Net] Receive backend: {}", net::RECEIVE_BACKEND);
+    let group = ptp_config.multicast_group;
+    #[cfg(any(unix, feature = "net-socket"))]
+    let network = {
+        // IPv6 joins by interface index; IPv4 by address
+        let iface_index = match group {
+            IpAddr::V4(_) => 0,
+            IpAddr::V6(_) => net::interface_index(iface_name)?,
+        };
+        // Create sockets to join multicast groups (IGMP/MLD), kernel timestamping on Unix
+        let sock_event = net::create_multicast_socket(
+            ptp::PTP_EVENT_PORT,
+            group,
+            iface_ip,
+            iface_index,
+            &join_policy,
+        )?;
+        let sock_general = net::create_multicast_socket(
+            ptp::PTP_GENERAL_PORT,
+            group,
+            iface_ip,
+            iface_index,
+            &join_policy,
+        )?;
+        info!(
+            "PTP multicast sockets for {} on {} ({})",
+            group, iface_name, iface_ip
+        );
+        if args.allow_loopback {
+            net::set_multicast_loop(&sock_event, group, true)?;
+            net::set_multicast_loop(&sock_general, group, true)?;
+        }
+
+        #[cfg(target_os = "linux")]
+        let hw_ts = ptp_config
+            .hw_timestamping
+            .then(|| enable_hw_timestamping(iface_name, &sock_event))
+            .flatten();
+        #[cfg(not(target_os = "linux"))]
+        let hw_ts = None;
+
+        RealPtpNetwork {
+            sock_event,
+            sock_general,
+            hw_ts,
+            delay_req_dest: net::group_socket_addr(group, ptp::PTP_EVENT_PORT, iface_index),
+            #[cfg(unix)]
+            wake_sockets: Vec::new(),
+        }
+    };
+
+    #[cfg(all(windows, feature = "net-winsock"))]
+    let network = {
+        // The Winsock backend joins the groups itself (no retry policy)
+        let _ = join_policy;
+        let IpAddr::V4(group) = group else {
+            return Err(anyhow::anyhow!(
+                "IPv6 PTP ({}) needs the net-socket backend on Windows",
+                group
+            ));
+        };
+        let winsock_net = net_winsock::WinsockPtpNetwork::new(iface_ip, group)?;
+        if args.allow_loopback {
+            warn!("[Net] --allow-loopback is not supported by the Winsock backend");
+        }
+        info!("Using Winsock timestamps on {} ({})", iface_name, iface_ip);
+        winsock_net
+    };
+
+    #[cfg(all(windows, feature = "net-pcap"))]
+    let network = {
+        // Use Npcap with HostHighPrec timestamps (KeQuerySystemTimePrecise)
+        // This provides driver-level timestamps that are both precise AND synced with system time
+        let IpAddr::V4(group) = group else {
+            return Err(anyhow::anyhow!(
+                "IPv6 PTP ({}) needs the net-socket backend on Windows",
+                group
+            ));
+        };
+        match net_pcap::NpcapPtpNetwork::new(iface_name, Some(iface_ip), group, &join_policy) {
+            Ok(npcap_net) => {
+                info!(
+                    "Using Npcap HostHighPrec timestamps on {} ({})",
+                    iface_name, iface_ip
+                );
+                npcap_net
+            }
+            Err(e) => {
+                error!(
+                    "Failed to initialize Npcap: {}. Npcap is required on Windows.",
+                    e
+                );
+                return Err(e);
+            }
+        }
+    };
+
+    Ok(network)
+}
+
+// --- Monitor ---
+/// `--monitor`: run the receive/offset pipeline against a clock that is never
+/// adjusted and print every sample.
+fn run_monitor(
+    args: Args,
+    running: Arc<AtomicBool>,
+    mut system_config: SystemConfig,
+) -> Result<()> {
+    // Nothing is adjusted, so there is nothing to audit
+    system_config.clock.rate_audit_secs = 0;
+
+    let Some((iface_name, iface_ip)) = select_interface(&args, &running) else {
+        return Ok(());
+    };
+    let network = open_ptp_network(&args, &iface_name, iface_ip, &system_config.ptp)?;
+    let status_shared = Arc::new(RwLock::new(SyncStatus::default()));
+    let mut controller = PtpController::new(
+        clock::NullClock::new(),
+        network,
+        traits::NoopNtpSource,
+        status_shared,
+        system_config,
+    );
+    controller.set_ntp_tracking(false);
+
+    println!(
+        "Monitoring PTP on {} ({}) - the clock is not adjusted. Ctrl+C to stop.",
+        iface_name, iface_ip
+    );
+    let mut monitor = monitor::Monitor::new();
+    while running.load(Ordering::SeqCst) && args.count.map_or(true, |n| monitor.count() < n) {
+        if let Err(e) = controller.process_loop_iteration() {
+            warn!("Error in loop: {}", e);
+        }
+        if let Some(sample) = controller.take_last_pair() {
+            println!("{}", monitor.record(&sample));
+        }
+        if let Err(e) = controller.wait_for_packet(LOOP_IDLE_TIMEOUT) {
+            warn!("Error waiting for packets: {}", e);
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    println!("\n{}", monitor.summary());
+    Ok(())
+}
+
+/// The OS clock adjustment backend, seeded from the RTC if configured.
+// macOS has neither an adjustment policy nor an RTC cold start to configure
+#[cfg_attr(target_os = "macos", allow(unused_variables))]
+fn open_platform_clock(args: &Args, system_config: &SystemConfig) -> Result<clock::PlatformClock> {
+    #[cfg(windows)]
+    let sys_clock = {
+        let force_enable = args
+            .force_enable_adjustment
+            .unwrap_or(system_config.clock.force_enable_adjustment);
+        info!(
+            "[Clock] Time adjustment found disabled at startup will be {}",
+            if force_enable {
+                "re-enabled (--force-enable-adjustment)"
+            } else {
+                "left alone - startup fails (policy respected)"
+            }
+        );
+        clock::PlatformClock::with_force_enable(force_enable)
+    };
+    #[cfg(unix)]
+    let sys_clock = clock::PlatformClock::new();
+    let sys_clock = match sys_clock {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Failed to initialize system clock adjustment: {}", e);
+            return Err(e);
+        }
+    };
+    info!("System clock control initialized.");
+
+    // Optional cold start: get roughly right from the RTC before NTP/PTP
+    #[cfg(target_os = "linux")]
+    let sys_clock = {
+        let mut sys_clock = sys_clock;
+        if system_config.clock.rtc_cold_start && args.slew_only {
+            warn!("[RTC] Cold start disabled by --slew-only (it would step the clock)");
+        } else if system_config.clock.rtc_cold_start {
+            let threshold = Duration::from_secs(system_config.clock.rtc_cold_start_threshold_secs);
+            dantesync::rtc::cold_start(&mut sys_clock, threshold);
+        }
+        sys_clock
+    };
+
+    Ok(sys_clock)
 }
 
 // --- Sync Loop ---
 fn run_sync_loop(
     args: Args,
     running: Arc<AtomicBool>,
-    system_config: SystemConfig,
+    mut system_config: SystemConfig,
     ntp_server_config: NtpServerConfig,
 ) -> Result<()> {
+    system_config.validate()?;
+
     // Notify systemd (Linux) that we are starting
     #[cfg(unix)]
     {
@@ -547,11 +1113,40 @@ fn run_sync_loop(
     let status_shared = Arc::new(RwLock::new(SyncStatus::default()));
 
     // Start IPC Server immediately (so Tray App can connect even if network is down)
+    #[cfg(unix)]
+    start_ipc_server(&args, status_shared.clone());
+    #[cfg(windows)]
     start_ipc_server(status_shared.clone());
 
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = args.metrics_addr {
+        match dantesync::metrics::MetricsServer::bind(addr) {
+            Ok(server) => server.spawn(status_shared.clone()),
+            Err(e) => warn!("Metrics not available: {:#} (continuing without it)", e),
+        }
+    }
+
+    // Optional independent NTP cross-check (monitoring only)
+    if let Some(ref check_server) = args.check_ntp {
+        let check_source = RealNtpSource {
+            client: ntp::NtpClient::new(check_server),
+        };
+        ntp_check::NtpCheckMonitor::new(check_source, check_server, args.check_ntp_threshold_us)
+            .spawn(status_shared.clone(), running.clone());
+    }
+
+    // NTP / time query servers listen here (all interfaces unless dual-homed)
+    let serve_ip = match ntp_server_config.bind.as_deref() {
+        Some(spec) => net::resolve_bind_address(spec)?,
+        None => Ipv4Addr::UNSPECIFIED,
+    };
+
     // Start UDP Time Query Server for network time verification
-    let time_server = match time_server::TimeServer::new() {
-        Ok(ts) => Some(ts),
+    let time_server = match time_server::TimeServer::bind(serve_ip) {
+        Ok(ts) => match ntp_server_config.time_query_key.as_deref() {
+            Some(key) => Some(ts.with_auth_key(key.as_bytes())),
+            None => Some(ts),
+        },
         Err(e) => {
             warn!(
                 "Failed to start Time Query Server on port {}: {} (continuing without it)",
@@ -562,96 +1157,222 @@ fn run_sync_loop(
         }
     };
 
-    stop_conflicting_services();
+    // A dry run leaves the OS time services alone as well
+    if !args.dry_run {
+        stop_conflicting_services();
+    }
+    // Restarts w32time on exit if --stop-w32time stopped it
+    #[cfg(windows)]
+    let _w32time = dantesync::w32time::claim_clock(
+        args.stop_w32time && !args.dry_run,
+        args.require_exclusive,
+    )?;
     enable_realtime_priority();
-
-    let sys_clock = match clock::PlatformClock::new() {
-        Ok(c) => c,
-        Err(e) => {
-            error!("Failed to initialize system clock adjustment: {}", e);
-            return Err(e);
-        }
+    let _timer_guard = enable_high_res_timer(!args.no_high_res_timer);
+
+    let sys_clock: Box<dyn SystemClock> = if args.dry_run {
+        warn!("[DryRun] The clock is never adjusted: frequency changes and steps are only logged");
+        // Nothing is adjusted, so there is nothing to audit
+        system_config.clock.rate_audit_secs = 0;
+        Box::new(clock::NullClock::new())
+    } else {
+        Box::new(open_platform_clock(&args, &system_config)?)
     };
-    info!("System clock control initialized.");
 
     // Network Interface Selection (Retry Loop)
-    let (iface_name, iface_ip) = loop {
-        match net::get_default_interface() {
-            Ok(res) => break res,
-            Err(e) => {
-                if !running.load(Ordering::SeqCst) {
-                    return Ok(());
-                }
-                warn!("Waiting for network interface... ({})", e);
-                thread::sleep(Duration::from_secs(5));
-            }
-        }
+    let Some((iface_name, iface_ip)) = select_interface(&args, &running) else {
+        return Ok(());
     };
 
-    // Platform-specific network setup
-    #[cfg(unix)]
-    let network = {
-        // Create sockets to join multicast groups (IGMP) with kernel timestamping
-        let sock_event = net::create_multicast_socket(ptp::PTP_EVENT_PORT, iface_ip)?;
-        let sock_general = net::create_multicast_socket(ptp::PTP_GENERAL_PORT, iface_ip)?;
+    if !serve_ip.is_unspecified() && serve_ip != iface_ip {
         info!(
-            "Joined Multicast Groups on {} ({}) - Kernel timestamping",
-            iface_name, iface_ip
+            "[Net] Dual-homed: PTP on {} ({}), serving time on {}",
+            iface_name, iface_ip, serve_ip
         );
+    }
 
-        RealPtpNetwork {
-            sock_event,
-            sock_general,
+    // NIC timestamping capabilities (ethtool -T)
+    #[cfg(target_os = "linux")]
+    dantesync::ethtool::log_capabilities(&iface_name);
+
+    let network = open_ptp_network(&args, &iface_name, iface_ip, &system_config.ptp)?;
+    // Time queries wake the loop as promptly as PTP packets
+    #[cfg(unix)]
+    let network = {
+        let mut network = network;
+        if let Some(ts) = &time_server {
+            match ts.socket().try_clone() {
+                Ok(socket) => network.wake_on(socket),
+                Err(e) => warn!("[TimeServer] Requests wait for the next loop pass: {}", e),
+            }
         }
+        network
     };
 
-    #[cfg(windows)]
-    let network = {
-        // Use Npcap with HostHighPrec timestamps (KeQuerySystemTimePrecise)
-        // This provides driver-level timestamps that are both precise AND synced with system time
-        match net_pcap::NpcapPtpNetwork::new(&iface_name) {
-            Ok(npcap_net) => {
-                info!(
-                    "Using Npcap HostHighPrec timestamps on {} ({})",
-                    iface_name, iface_ip
-                );
-                npcap_net
+    // Optional packet recorder (for offline timestamp-source analysis with ptpreplay)
+    #[cfg(target_os = "linux")]
+    let timestamp_source = if network.hw_timestamping() {
+        "SO_TIMESTAMPING (NIC hardware)"
+    } else {
+        "SO_TIMESTAMPNS"
+    };
+    #[cfg(all(unix, not(target_os = "linux")))]
+    let timestamp_source = "SO_TIMESTAMP";
+    #[cfg(all(windows, feature = "net-pcap"))]
+    let timestamp_source = "Npcap HostHighPrec";
+    #[cfg(all(windows, feature = "net-winsock"))]
+    let timestamp_source = "Winsock SIO_TIMESTAMPING";
+    #[cfg(all(windows, feature = "net-socket"))]
+    let timestamp_source = "user-space recv";
+    let packet_recorder = match args.record {
+        Some(ref path) => match recorder::PacketRecorder::create(path, timestamp_source) {
+            Ok(rec) => {
+                info!("[Recorder] Recording PTP packets to {}", path.display());
+                Some(rec)
             }
             Err(e) => {
-                error!(
-                    "Failed to initialize Npcap: {}. Npcap is required on Windows.",
+                warn!(
+                    "[Recorder] Failed to create {}: {} (continuing without recording)",
+                    path.display(),
                     e
                 );
-                return Err(e);
+                None
             }
-        }
+        },
+        None => None,
     };
+    let network = recorder::RecordingNetwork::new(network, packet_recorder);
 
     let ntp_server = args
         .ntp_server
         .as_deref()
         .expect("ntp_server must be resolved before run_sync_loop");
-    let ntp_source = RealNtpSource {
-        client: ntp::NtpClient::new(ntp_server),
+    let ntp_bind = match args
+        .ntp_bind
+        .as_deref()
+        .map(|b| resolve_ntp_bind(b, iface_ip))
+    {
+        Some(Ok(ip)) => {
+            info!("[NTP] Client queries sent from {}", ip);
+            Some(ip)
+        }
+        Some(Err(e)) => {
+            warn!("[NTP] {} - using the OS-chosen route", e);
+            None
+        }
+        None => None,
+    };
+    let ntp_client = |servers: &str| {
+        let client = ntp::NtpClient::with_servers(&ntp::server_list(servers));
+        match ntp_bind {
+            Some(ip) => client.with_bind_address(ip),
+            None => client,
+        }
+    };
+    if system_config.ntp.discipline == config::NtpDiscipline::NtpOnly {
+        if args.no_ntp {
+            return Err(anyhow::anyhow!(
+                "ntp.discipline \"ntp_only\" needs an NTP server, but --no-ntp is set"
+            ));
+        }
+        info!(
+            "[NTP] NTP-only discipline: polling {} every {}s, PTP packets are ignored",
+            ntp_server, system_config.ntp.poll_interval_secs
+        );
+    }
+
+    // --no-ntp: PTP only, no NTP server needs to be reachable
+    let ntp_source: Box<dyn NtpSource> = if args.no_ntp {
+        Box::new(traits::NoopNtpSource)
+    } else {
+        Box::new(RealNtpSource {
+            client: ntp_client(ntp_server),
+        })
     };
 
+    // Dry runs never adjust the clock, so there is no drift to keep
+    let drift_file = (system_config.clock.persist_drift && !args.dry_run).then(|| {
+        system_config
+            .clock
+            .drift_file
+            .clone()
+            .unwrap_or_else(drift_file::default_path)
+    });
+
     let mut controller =
         PtpController::new(sys_clock, network, ntp_source, status_shared, system_config);
 
-    if !args.skip_ntp {
+    if let Some(path) = drift_file {
+        controller.enable_drift_file(drift_file::DriftFile::new(path));
+    }
+
+    if let Some(warn_us) = args.loop_timing_warn_us {
+        controller.enable_loop_timing(Duration::from_micros(warn_us));
+    }
+
+    if args.slew_only {
+        controller.enable_slew_only();
+    }
+
+    if let Some(ref path) = args.servo_trace {
+        match servo_trace::ServoTrace::create(path) {
+            Ok(trace) => {
+                controller.enable_servo_trace(trace);
+                info!("[Trace] Writing servo state to {}", path.display());
+            }
+            Err(e) => warn!(
+                "[Trace] Failed to create {}: {} (continuing without servo trace)",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    // Status consumers that want every update subscribe to the bus
+    if let Some(ref path) = args.status_log {
+        match status_bus::spawn_json_logger(controller.status_bus().subscribe(), path) {
+            Ok(()) => info!("[StatusLog] Logging status updates to {}", path.display()),
+            Err(e) => warn!(
+                "[StatusLog] Failed to create {}: {} (continuing without status log)",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    let skip_initial_ntp = args.skip_ntp || args.no_ntp;
+    if !skip_initial_ntp {
         info!("Using NTP Server: {}", ntp_server);
     }
-    controller.run_ntp_sync(args.skip_ntp);
+    if args.background_ntp_sync && !skip_initial_ntp {
+        let client = ntp_client(ntp_server);
+        let burst = args.ntp_burst;
+        controller.run_ntp_sync_background(move || client.get_offset_burst(burst));
+    } else if !skip_initial_ntp {
+        let client = ntp_client(ntp_server);
+        controller.run_ntp_sync_with(|| client.get_offset_burst(args.ntp_burst));
+    }
+    if args.no_ntp {
+        // Wall clock is managed elsewhere - only align frequency to PTP
+        controller.set_ntp_tracking(false);
+        info!("[NTP] Disabled (--no-ntp) - PTP frequency only, wall clock is never stepped");
+    }
 
     // Start NTP server if enabled (this machine becomes the time source)
-    if ntp_server_config.enabled {
+    if ntp_server_config.enabled && args.dry_run {
+        warn!("[NTP-Server] Not started in a dry run: this host's clock is not disciplined");
+    } else if ntp_server_config.enabled {
         info!(
             "[NTP-Server] Starting NTP server mode (port {}, stratum {})",
             ntp_server_config.port, ntp_server_config.stratum
         );
 
         // Create NTP server
-        match ntp_server::NtpServer::new(ntp_server_config.port, ntp_server_config.stratum) {
+        match ntp_server::NtpServer::bind(
+            serve_ip,
+            ntp_server_config.port,
+            ntp_server_config.stratum,
+        ) {
             Ok(ntp_srv) => {
                 // Disable periodic NTP queries - this machine IS the time source now
                 controller.disable_ntp_tracking();
@@ -693,11 +1414,20 @@ fn run_sync_loop(
     }
 
     let mut last_log = Instant::now();
+    #[cfg(target_os = "linux")]
+    let mut last_rtc_check: Option<Instant> = None;
 
     while running.load(Ordering::SeqCst) {
         if last_log.elapsed() >= Duration::from_secs(10) {
             controller.log_status();
 
+            // System vs hardware clock, for "time is wrong after reboot" investigations
+            #[cfg(target_os = "linux")]
+            if last_rtc_check.map_or(true, |t| t.elapsed() >= RTC_CHECK_INTERVAL) {
+                update_rtc_offset(&controller.get_status_shared());
+                last_rtc_check = Some(Instant::now());
+            }
+
             // Update systemd status with latest metrics
             #[cfg(unix)]
             {
@@ -710,7 +1440,12 @@ fn run_sync_loop(
                             status.offset_ns as f64 / 1000.0
                         )
                     } else {
-                        format!("v{} | Settling...", env!("CARGO_PKG_VERSION"))
+                        format!(
+                            "v{} | Settling {}/{}...",
+                            env!("CARGO_PKG_VERSION"),
+                            status.settling_count,
+                            status.settling_threshold
+                        )
                     };
                     let _ =
                         sd_notify::notify(false, &[sd_notify::NotifyState::Status(&status_str)]);
@@ -729,19 +1464,24 @@ fn run_sync_loop(
             ts.handle_requests(&controller.get_status_shared());
         }
 
-        // Platform-specific polling intervals:
+        // Platform-specific idle wait:
         // - Windows: 50µs tight polling for lower jitter with software timestamps.
         //   This achieves ~5% CPU usage while maintaining <50µs precision.
         //   Tested across Intel/AMD systems; tighter values increase CPU without benefit.
-        // - Linux: 1ms is sufficient since we use kernel SO_TIMESTAMPNS which provides
-        //   accurate timestamps regardless of polling frequency.
+        // - Linux/macOS: block in poll(2) until a PTP packet or time query arrives.
+        //   Kernel timestamps make T2 independent of the wake-up; the timeout only
+        //   paces the periodic tasks above.
         #[cfg(windows)]
         thread::sleep(Duration::from_micros(50));
         #[cfg(not(windows))]
-        thread::sleep(Duration::from_millis(1));
+        if let Err(e) = controller.wait_for_packet(LOOP_IDLE_TIMEOUT) {
+            warn!("Error waiting for packets: {}", e);
+            thread::sleep(Duration::from_millis(1));
+        }
     }
 
     info!("Sync Loop Exiting.");
+    controller.shutdown();
     #[cfg(unix)]
     {
         let _ = sd_notify::notify(false, &[sd_notify::NotifyState::Stopping]);
@@ -753,6 +1493,15 @@ fn run_sync_loop(
 #[cfg(windows)]
 const SERVICE_NAME: &str = "dantesync";
 
+/// Render the fully-resolved configuration in the `config.json` schema.
+///
+/// Includes every `system` field with its platform-specific default and the CLI
+/// overrides, so the output captures a known-good setup that can be deployed
+/// verbatim as `config.json` on another machine.
+fn dump_config(config: &Config) -> Result<String> {
+    Ok(serde_json::to_string_pretty(config)?)
+}
+
 #[cfg(windows)]
 const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
 
@@ -761,7 +1510,7 @@ fn my_service_main(_arguments: Vec<OsString>) {
     // We need to reload config or pass it?
     // Windows Service entry doesn't allow easy closure capture without unsafe global.
     // But we can just reload it, it's cheap.
-    let config = load_config();
+    let mut config = load_config();
 
     let (shutdown_tx, shutdown_rx) = std::sync::mpsc::channel();
 
@@ -794,7 +1543,7 @@ fn my_service_main(_arguments: Vec<OsString>) {
 
     // Parse CLI args and resolve NTP server from config file
     let mut args = Args::parse();
-    args.ntp_server = Some(resolve_ntp_server(&args.ntp_server, &config));
+    apply_cli_overrides(&mut args, &mut config);
 
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
@@ -837,10 +1586,59 @@ fn run_service_logic(_args: Args, _config: Config) -> Result<()> {
 
 fn main() -> Result<()> {
     let mut args = Args::parse();
-    let config = load_config();
+    let mut config = load_config();
 
     // Resolve NTP server: CLI arg > config file > default
-    args.ntp_server = Some(resolve_ntp_server(&args.ntp_server, &config));
+    apply_cli_overrides(&mut args, &mut config);
+
+    if args.list_interfaces {
+        println!("IPv4 interfaces (--interface NAME|IP):");
+        for line in net::list_interfaces()? {
+            println!("  {}", line);
+        }
+        #[cfg(all(windows, feature = "net-pcap"))]
+        {
+            println!("Npcap devices:");
+            for line in net_pcap::list_npcap_devices()? {
+                println!("  {}", line);
+            }
+        }
+        return Ok(());
+    }
+
+    if args.dump_config {
+        println!("{}", dump_config(&config)?);
+        return Ok(());
+    }
+
+    if args.selftest {
+        let params = selftest::SelftestParams::default();
+        println!(
+            "Self-test: simulated grandmaster {:+.1}ppm, NTP offset {:+.0}ms (timeout {}s)",
+            params.drift_ppm,
+            params.ntp_offset_ns as f64 / 1e6,
+            params.timeout.as_secs()
+        );
+        let report = selftest::run(config.system, &params)?;
+        println!("{}", report.summary());
+        if !report.passed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if args.monitor {
+        // Only problems are logged between the sample lines
+        env_logger::builder()
+            .format_timestamp(None)
+            .format_target(false)
+            .filter_level(log::LevelFilter::Warn)
+            .init();
+        let running = Arc::new(AtomicBool::new(true));
+        let r = running.clone();
+        ctrlc::set_handler(move || r.store(false, Ordering::SeqCst))?;
+        return run_monitor(args, running, config.system);
+    }
 
     #[cfg(windows)]
     if args.service {
@@ -915,9 +1713,20 @@ fn main() -> Result<()> {
 mod tests {
     use super::*;
 
+    /// Compile-time check: every receive backend of this platform, with and
+    /// without the recorder, implements the PtpNetwork the controller expects.
+    #[test]
+    fn platform_networks_implement_ptp_network() {
+        fn assert_ptp_network<N: PtpNetwork>() {}
+        assert_ptp_network::<PlatformNetwork>();
+        assert_ptp_network::<recorder::RecordingNetwork<PlatformNetwork>>();
+    }
+
     fn config_with_ntp(server: &str) -> Config {
         Config {
             ntp_server: server.to_string(),
+            ntp_bind: None,
+            ptp_interface: None,
             ntp_server_mode: NtpServerConfig::default(),
             system: SystemConfig::default(),
         }
@@ -952,6 +1761,47 @@ mod tests {
         assert_eq!(resolve_ntp_server(&cli, &config), "10.77.8.2");
     }
 
+    #[test]
+    fn resolve_ntp_bind_interface_or_ip() {
+        let iface = Ipv4Addr::new(10, 77, 1, 20);
+        assert_eq!(
+            resolve_ntp_bind("interface", iface).unwrap(),
+            IpAddr::V4(iface)
+        );
+        assert_eq!(
+            resolve_ntp_bind("192.168.5.2", iface).unwrap(),
+            "192.168.5.2".parse::<IpAddr>().unwrap()
+        );
+        assert!(resolve_ntp_bind("eth0", iface).is_err());
+    }
+
+    #[test]
+    fn config_ntp_bind_is_optional() {
+        let config: Config = serde_json::from_str(r#"{"ntp_server": "172.16.0.5"}"#).unwrap();
+        assert_eq!(config.ntp_bind, None);
+        let config: Config =
+            serde_json::from_str(r#"{"ntp_server": "172.16.0.5", "ntp_bind": "interface"}"#)
+                .unwrap();
+        assert_eq!(config.ntp_bind.as_deref(), Some("interface"));
+    }
+
+    #[test]
+    fn config_dual_homed_interfaces() {
+        let config: Config = serde_json::from_str(r#"{"ntp_server": "172.16.0.5"}"#).unwrap();
+        assert_eq!(config.ptp_interface, None);
+        assert_eq!(config.ntp_server_mode.bind, None);
+        let config: Config = serde_json::from_str(
+            r#"{
+            "ntp_server": "172.16.0.5",
+            "ptp_interface": "AV",
+            "ntp_server_mode": {"enabled": true, "port": 123, "stratum": 3, "bind": "192.168.1.20"}
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(config.ptp_interface.as_deref(), Some("AV"));
+        assert_eq!(config.ntp_server_mode.bind.as_deref(), Some("192.168.1.20"));
+    }
+
     #[test]
     fn config_default_has_expected_ntp_server() {
         let config = Config::default();
@@ -977,6 +1827,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dump_config_includes_cli_overrides_and_system_defaults() {
+        let mut config = config_with_ntp("10.0.0.1");
+        let mut args = Args::parse_from([
+            "dantesync",
+            "--ntp-server",
+            "192.168.1.100",
+            "--serve-bind",
+            "eth1",
+            "--force-enable-adjustment",
+            "false",
+        ]);
+        apply_cli_overrides(&mut args, &mut config);
+        let json = dump_config(&config).unwrap();
+
+        assert!(json.contains("\"ntp_server\": \"192.168.1.100\""));
+        assert!(json.contains("\"bind\": \"eth1\""));
+        assert!(json.contains("\"force_enable_adjustment\": false"));
+        assert!(json.contains("\"lock_hold_samples\""));
+    }
+
+    #[test]
+    fn dump_config_loads_back_through_load_config() {
+        let mut config = config_with_ntp("10.0.0.1");
+        config.ptp_interface = Some("eth0".to_string());
+        config.system.servo.kp = 0.3;
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), dump_config(&config).unwrap()).unwrap();
+
+        let restored = load_config_from(file.path());
+        assert_eq!(format!("{:?}", restored), format!("{:?}", config));
+    }
+
     // ========================================================================
     // NTP SERVER MODE TESTS
     // ========================================================================
@@ -1059,4 +1942,20 @@ mod tests {
         let lock3 = acquire_singleton_lock();
         assert!(lock3.is_ok(), "Lock after release should succeed");
     }
+
+    #[test]
+    fn test_migrate_config_replaces_legacy_servo_gains() {
+        let mut json = serde_json::to_value(Config::default()).unwrap();
+        json["system"]["servo"]["kp"] = serde_json::json!(0.0005);
+        json["system"]["servo"]["ki"] = serde_json::json!(0.00005);
+        assert!(migrate_config(&mut json));
+        let cfg: Config = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(cfg.system.servo.kp, SystemConfig::default().servo.kp);
+        assert_eq!(cfg.system.servo.ki, SystemConfig::default().servo.ki);
+
+        // Tuned gains are left alone
+        json["system"]["servo"]["kp"] = serde_json::json!(0.3);
+        assert!(!migrate_config(&mut json));
+        assert_eq!(json["system"]["servo"]["kp"], 0.3);
+    }
 }
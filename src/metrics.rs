@@ -0,0 +1,211 @@
+//! Prometheus exporter: `GET /metrics` over plain HTTP (cargo feature `metrics`).
+//!
+//! Reads the shared `SyncStatus` like the other status servers. Each scrape is
+//! answered on its own thread, so a stuck client never reaches the sync loop.
+
+use crate::status::SyncStatus;
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// A scraper that has not sent its request line by then is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct MetricsServer {
+    listener: TcpListener,
+}
+
+impl MetricsServer {
+    pub fn bind(addr: SocketAddr) -> Result<Self> {
+        let listener =
+            TcpListener::bind(addr).with_context(|| format!("binding metrics on {}", addr))?;
+        info!(
+            "[Metrics] Serving Prometheus metrics on http://{}/metrics",
+            addr
+        );
+        Ok(Self { listener })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accept scrapes on a background thread, one thread per connection.
+    pub fn spawn(self, status: Arc<RwLock<SyncStatus>>) {
+        thread::spawn(move || {
+            for stream in self.listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let status = status.clone();
+                        thread::spawn(move || {
+                            if let Err(e) = serve(stream, &status) {
+                                debug!("[Metrics] Request failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("[Metrics] Accept failed: {}", e),
+                }
+            }
+        });
+    }
+}
+
+fn serve(stream: TcpStream, status: &RwLock<SyncStatus>) -> Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers are irrelevant, but read them so the client sees an orderly close
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status_line, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let snapshot = status
+                .read()
+                .map_err(|e| anyhow::anyhow!("status lock poisoned: {}", e))?
+                .clone();
+            ("200 OK", render(&snapshot))
+        }
+        _ => ("404 Not Found", "Not found: try /metrics\n".to_string()),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status_line,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+/// The status in Prometheus text exposition format.
+pub fn render(status: &SyncStatus) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, value: String| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        let _ = writeln!(out, "{} {}", name, value);
+    };
+
+    gauge(
+        "dantesync_offset_ns",
+        "Phase offset from the PTP grandmaster (median of the sample window).",
+        status.offset_ns.to_string(),
+    );
+    gauge(
+        "dantesync_drift_ppm",
+        "Smoothed drift rate against the grandmaster (us/s).",
+        status.smoothed_rate_ppm.to_string(),
+    );
+    gauge(
+        "dantesync_frequency_adj_ppm",
+        "Frequency adjustment applied to the system clock.",
+        status.freq_adj_ppm.to_string(),
+    );
+    gauge(
+        "dantesync_oscillator_drift_ppm",
+        "Estimated intrinsic drift of the local oscillator (slow average of the adjustment).",
+        status.drift_ppm.to_string(),
+    );
+    gauge(
+        "dantesync_locked",
+        "1 while the servo is locked.",
+        u8::from(status.is_locked).to_string(),
+    );
+    gauge(
+        "dantesync_settled",
+        "1 once sync is established.",
+        u8::from(status.settled).to_string(),
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP dantesync_gm_info Grandmaster being followed (absent without one)."
+    );
+    let _ = writeln!(out, "# TYPE dantesync_gm_info gauge");
+    if let Some(uuid) = status.gm_uuid {
+        let uuid: Vec<String> = uuid.iter().map(|b| format!("{:02x}", b)).collect();
+        let _ = writeln!(out, "dantesync_gm_info{{gm_uuid=\"{}\"}} 1", uuid.join(":"));
+    }
+    out
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::Ipv4Addr;
+
+    fn locked_status() -> SyncStatus {
+        SyncStatus {
+            offset_ns: -1500,
+            smoothed_rate_ppm: 0.25,
+            drift_ppm: -12.0,
+            freq_adj_ppm: -12.5,
+            is_locked: true,
+            settled: true,
+            gm_uuid: Some([0x00, 0x1d, 0xc1, 0x0a, 0x0b, 0x0c]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_exposes_status() {
+        let text = render(&locked_status());
+        for line in [
+            "dantesync_offset_ns -1500",
+            "dantesync_drift_ppm 0.25",
+            "dantesync_frequency_adj_ppm -12.5",
+            "dantesync_oscillator_drift_ppm -12",
+            "dantesync_locked 1",
+            "dantesync_settled 1",
+            "dantesync_gm_info{gm_uuid=\"00:1d:c1:0a:0b:0c\"} 1",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing '{}' in\n{}",
+                line,
+                text
+            );
+        }
+
+        let idle = render(&SyncStatus::default());
+        assert!(idle.contains("dantesync_locked 0"));
+        assert!(!idle.contains("dantesync_gm_info{"));
+    }
+
+    #[test]
+    fn test_http_scrape() {
+        let server = MetricsServer::bind((Ipv4Addr::LOCALHOST, 0).into()).unwrap();
+        let addr = server.local_addr().unwrap();
+        server.spawn(Arc::new(RwLock::new(locked_status())));
+
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: test\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("dantesync_locked 1"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+    }
+}
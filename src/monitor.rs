@@ -0,0 +1,180 @@
+//! `--monitor`: ping-like live view of the PTP offset.
+//!
+//! One line per Sync/FollowUp pair with the phase offset and the jitter over the
+//! last few samples, then min/max/median/mean/stddev at the end (as `ptplog`
+//! prints them). The clock is never adjusted.
+
+use std::collections::VecDeque;
+
+/// Samples the per-line jitter is computed over
+const JITTER_WINDOW: usize = 16;
+
+/// One Sync/FollowUp pair as seen by the controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairSample {
+    pub seq: u16,
+    pub source: [u8; 6],
+    /// Phase offset (T2 - T1 within the second, nanoseconds)
+    pub offset_ns: i64,
+}
+
+/// Summary statistics over offsets (microseconds).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffsetStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// 95% of the offsets are within this distance of the mean
+    pub p95_dev: f64,
+}
+
+impl OffsetStats {
+    /// None for an empty slice.
+    pub fn compute(offsets: &[f64]) -> Option<Self> {
+        if offsets.is_empty() {
+            return None;
+        }
+        let mut sorted = offsets.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+        let variance = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / sorted.len() as f64;
+        let mut deviations: Vec<f64> = sorted.iter().map(|x| (x - mean).abs()).collect();
+        deviations.sort_by(|a, b| a.total_cmp(b));
+        // Nearest rank
+        let p95_index = ((deviations.len() as f64 * 0.95).ceil() as usize).max(1) - 1;
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            median: sorted[sorted.len() / 2],
+            mean,
+            std_dev: variance.sqrt(),
+            p95_dev: deviations[p95_index],
+        })
+    }
+
+    pub fn range(&self) -> f64 {
+        self.max - self.min
+    }
+}
+
+/// Collects samples and formats the live lines.
+#[derive(Debug, Default)]
+pub struct Monitor {
+    recent: VecDeque<f64>,
+    offsets_us: Vec<f64>,
+}
+
+impl Monitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a sample and return its line.
+    pub fn record(&mut self, sample: &PairSample) -> String {
+        let offset_us = sample.offset_ns as f64 / 1000.0;
+        self.offsets_us.push(offset_us);
+        if self.recent.len() == JITTER_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(offset_us);
+        let jitter =
+            OffsetStats::compute(self.recent.make_contiguous()).map_or(0.0, |stats| stats.std_dev);
+        let gm: Vec<String> = sample.source.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "seq={} offset={:+.1}µs jitter={:.1}µs gm={}",
+            sample.seq,
+            offset_us,
+            jitter,
+            gm.join(":")
+        )
+    }
+
+    pub fn count(&self) -> usize {
+        self.offsets_us.len()
+    }
+
+    pub fn stats(&self) -> Option<OffsetStats> {
+        OffsetStats::compute(&self.offsets_us)
+    }
+
+    /// End-of-run statistics, one item per line.
+    pub fn summary(&self) -> String {
+        let Some(stats) = self.stats() else {
+            return "No samples received".to_string();
+        };
+        format!(
+            "Statistics ({} samples):\n  Min offset:    {:+.1} us\n  Max offset:    {:+.1} us\n  Median offset: {:+.1} us\n  Mean offset:   {:+.1} us\n  Std deviation: {:.1} us\n  Range:         {:.1} us",
+            stats.count,
+            stats.min,
+            stats.max,
+            stats.median,
+            stats.mean,
+            stats.std_dev,
+            stats.range()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GM: [u8; 6] = [0x00, 0x1D, 0xC1, 0x0A, 0x0B, 0x0C];
+
+    #[test]
+    fn test_offset_stats() {
+        let stats = OffsetStats::compute(&[3.0, -1.0, 1.0, 5.0]).unwrap();
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.min, -1.0);
+        assert_eq!(stats.max, 5.0);
+        assert_eq!(stats.median, 3.0);
+        assert_eq!(stats.mean, 2.0);
+        assert!((stats.std_dev - 5.0f64.sqrt()).abs() < 1e-9);
+        assert_eq!(stats.range(), 6.0);
+        assert_eq!(stats.p95_dev, 3.0);
+        assert!(OffsetStats::compute(&[]).is_none());
+    }
+
+    #[test]
+    fn test_offset_stats_p95_ignores_rare_outliers() {
+        // 19 offsets within 1 of the mean, one far out
+        let mut offsets = vec![0.0; 19];
+        for (i, o) in offsets.iter_mut().enumerate() {
+            *o = if i % 2 == 0 { 1.0 } else { -1.0 };
+        }
+        offsets.push(21.0);
+        let stats = OffsetStats::compute(&offsets).unwrap();
+        assert_eq!(stats.mean, 1.1);
+        assert!((stats.p95_dev - 2.1).abs() < 1e-9, "{}", stats.p95_dev);
+        assert!(stats.max - stats.mean > 19.0);
+    }
+
+    #[test]
+    fn test_monitor_lines_and_summary() {
+        let mut monitor = Monitor::new();
+        assert_eq!(monitor.summary(), "No samples received");
+
+        let line = monitor.record(&PairSample {
+            seq: 1234,
+            source: GM,
+            offset_ns: 23_400,
+        });
+        assert_eq!(
+            line,
+            "seq=1234 offset=+23.4µs jitter=0.0µs gm=00:1d:c1:0a:0b:0c"
+        );
+
+        let line = monitor.record(&PairSample {
+            seq: 1235,
+            source: GM,
+            offset_ns: 13_400,
+        });
+        assert!(line.contains("jitter=5.0µs"), "{}", line);
+        assert_eq!(monitor.count(), 2);
+        assert!(monitor.summary().starts_with("Statistics (2 samples):"));
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Result};
 use socket2::{Domain, Protocol, Socket, Type};
-use std::net::{IpAddr, Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, UdpSocket};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
 #[cfg(unix)]
 use nix::sys::socket::{setsockopt, sockopt};
@@ -46,6 +48,76 @@ pub fn get_default_interface() -> Result<(String, Ipv4Addr)> {
     Err(anyhow!("No suitable IPv4 interface found"))
 }
 
+/// Look up an interface by name (case-insensitive) or IPv4 address.
+///
+/// Unlike `get_default_interface()`, an explicit choice may be any IPv4
+/// interface, loopback and wireless included.
+pub fn find_interface(spec: &str) -> Result<(String, Ipv4Addr)> {
+    let ifaces: Vec<(String, Ipv4Addr)> = if_addrs::get_if_addrs()?
+        .into_iter()
+        .filter_map(|iface| match iface.addr.ip() {
+            IpAddr::V4(ip) => Some((iface.name, ip)),
+            _ => None,
+        })
+        .collect();
+    let (name, ip) = match_interface(&ifaces, spec).ok_or_else(|| {
+        let known: Vec<String> = ifaces
+            .iter()
+            .map(|(name, ip)| format!("{} ({})", name, ip))
+            .collect();
+        anyhow!(
+            "Interface '{}' not found (available: {})",
+            spec,
+            known.join(", ")
+        )
+    })?;
+    if !is_ip_bindable(ip) {
+        return Err(anyhow!("Interface {} ({}) is not bindable", name, ip));
+    }
+    Ok((name, ip))
+}
+
+/// IPv4 interfaces as printed by `--list-interfaces`: name, address and why
+/// auto-detection would skip it.
+pub fn list_interfaces() -> Result<Vec<String>> {
+    let default = get_default_interface().ok();
+    Ok(if_addrs::get_if_addrs()?
+        .into_iter()
+        .filter_map(|iface| match iface.addr.ip() {
+            IpAddr::V4(ip) => {
+                let note = if default.as_ref() == Some(&(iface.name.clone(), ip)) {
+                    " (auto-detected)"
+                } else if ip.is_loopback() {
+                    " (loopback)"
+                } else if !is_ip_bindable(ip) {
+                    " (not bindable)"
+                } else {
+                    ""
+                };
+                Some(format!("{}: {}{}", iface.name, ip, note))
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+fn match_interface(ifaces: &[(String, Ipv4Addr)], spec: &str) -> Option<(String, Ipv4Addr)> {
+    let by_ip = spec.parse::<Ipv4Addr>().ok();
+    ifaces
+        .iter()
+        .find(|(name, ip)| Some(*ip) == by_ip || name.eq_ignore_ascii_case(spec))
+        .cloned()
+}
+
+/// Resolve a server bind address: an IPv4 address (0.0.0.0 = all interfaces)
+/// or an interface name.
+pub fn resolve_bind_address(spec: &str) -> Result<Ipv4Addr> {
+    match spec.parse::<Ipv4Addr>() {
+        Ok(ip) if ip.is_unspecified() => Ok(ip),
+        _ => find_interface(spec).map(|(_, ip)| ip),
+    }
+}
+
 fn is_ip_bindable(ip: Ipv4Addr) -> bool {
     let socket = match Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)) {
         Ok(s) => s,
@@ -55,61 +127,288 @@ fn is_ip_bindable(ip: Ipv4Addr) -> bool {
     socket.bind(&addr.into()).is_ok()
 }
 
-pub fn create_multicast_socket(port: u16, interface_ip: Ipv4Addr) -> Result<UdpSocket> {
-    // Standard UDP socket creation for TX (Transmission) or legacy RX
-    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+#[cfg(all(
+    windows,
+    not(any(feature = "net-pcap", feature = "net-winsock", feature = "net-socket"))
+))]
+compile_error!("No Windows receive backend: enable one of net-pcap, net-winsock, net-socket");
+
+#[cfg(all(
+    windows,
+    any(
+        all(feature = "net-pcap", feature = "net-winsock"),
+        all(feature = "net-pcap", feature = "net-socket"),
+        all(feature = "net-winsock", feature = "net-socket")
+    )
+))]
+compile_error!(
+    "Several Windows receive backends enabled: pick one (--no-default-features --features ...)"
+);
+
+/// Packet receive backend compiled into this build
+#[cfg(target_os = "linux")]
+pub const RECEIVE_BACKEND: &str = "socket (SO_TIMESTAMPNS kernel timestamps)";
+#[cfg(all(unix, not(target_os = "linux")))]
+pub const RECEIVE_BACKEND: &str = "socket (SO_TIMESTAMP microsecond kernel timestamps)";
+#[cfg(all(windows, feature = "net-pcap"))]
+pub const RECEIVE_BACKEND: &str = "net-pcap (Npcap HostHighPrec timestamps)";
+#[cfg(all(windows, feature = "net-winsock"))]
+pub const RECEIVE_BACKEND: &str = "net-winsock (Winsock SIO_TIMESTAMPING)";
+#[cfg(all(windows, feature = "net-socket"))]
+pub const RECEIVE_BACKEND: &str = "net-socket (user-space receive timestamps)";
+
+/// Multicast join retry policy. A join can fail transiently at boot while the
+/// interface is still coming up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinPolicy {
+    /// Attempts after the first one
+    pub retries: u32,
+    /// Delay before the first retry, doubled after each
+    pub initial_backoff: Duration,
+    /// Fail if the join never succeeds. When false, warn and continue without
+    /// membership (multicast may still arrive: promiscuous capture, no IGMP snooping).
+    pub required: bool,
+}
 
-    socket.set_reuse_address(true)?;
+impl Default for JoinPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            required: true,
+        }
+    }
+}
+
+/// Run `join` until it succeeds or the retries are exhausted, with exponential
+/// backoff. Returns whether the group was joined; an error only if it is required.
+pub fn join_with_retry<F>(mut join: F, policy: &JoinPolicy, what: &str) -> Result<bool>
+where
+    F: FnMut() -> std::io::Result<()>,
+{
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 0;
+    loop {
+        let err = match join() {
+            Ok(()) => {
+                if attempt > 0 {
+                    log::info!(
+                        "[Net] {} multicast join succeeded on retry {}",
+                        what,
+                        attempt
+                    );
+                }
+                return Ok(true);
+            }
+            Err(e) => e,
+        };
+
+        if attempt < policy.retries {
+            attempt += 1;
+            log::warn!(
+                "[Net] {} multicast join failed ({}), retry {}/{} in {:?}",
+                what,
+                err,
+                attempt,
+                policy.retries,
+                backoff
+            );
+            thread::sleep(backoff);
+            backoff *= 2;
+            continue;
+        }
 
-    let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port);
-    socket.bind(&addr.into())?;
+        if policy.required {
+            return Err(anyhow!(
+                "{} multicast join failed after {} attempts: {}",
+                what,
+                attempt + 1,
+                err
+            ));
+        }
+        log::warn!(
+            "[Net] {} multicast join failed after {} attempts ({}) - continuing without membership",
+            what,
+            attempt + 1,
+            err
+        );
+        return Ok(false);
+    }
+}
 
-    let multi_addr: Ipv4Addr = "224.0.1.129".parse()?;
-    socket.join_multicast_v4(&multi_addr, &interface_ip)?;
+/// Interface index of `name` (IPv6 multicast joins by index, not address).
+pub fn interface_index(name: &str) -> Result<u32> {
+    if_addrs::get_if_addrs()?
+        .into_iter()
+        .find(|iface| iface.name.eq_ignore_ascii_case(name))
+        .and_then(|iface| iface.index)
+        .ok_or_else(|| anyhow!("no interface index for '{}'", name))
+}
 
-    socket.set_multicast_loop_v4(false)?;
+/// Where to send to `group`: the IPv6 scope is the interface, for link-local groups.
+pub fn group_socket_addr(group: IpAddr, port: u16, interface_index: u32) -> SocketAddr {
+    match group {
+        IpAddr::V4(group) => SocketAddrV4::new(group, port).into(),
+        IpAddr::V6(group) => SocketAddrV6::new(group, port, 0, interface_index).into(),
+    }
+}
+
+/// Receive our own multicast (the family of `group` decides which option).
+pub fn set_multicast_loop(socket: &UdpSocket, group: IpAddr, on: bool) -> std::io::Result<()> {
+    match group {
+        IpAddr::V4(_) => socket.set_multicast_loop_v4(on),
+        IpAddr::V6(_) => socket.set_multicast_loop_v6(on),
+    }
+}
+
+/// Bind `port` and join `group` on the PTP interface: by address for IPv4, by
+/// `interface_index` for IPv6 (see `interface_index`).
+pub fn create_multicast_socket(
+    port: u16,
+    group: IpAddr,
+    interface_ip: Ipv4Addr,
+    interface_index: u32,
+    join_policy: &JoinPolicy,
+) -> Result<UdpSocket> {
+    if !group.is_multicast() {
+        return Err(anyhow!("{} is not a multicast group", group));
+    }
+
+    // Standard UDP socket creation for TX (Transmission) or legacy RX
+    let domain = match group {
+        IpAddr::V4(_) => Domain::IPV4,
+        IpAddr::V6(_) => Domain::IPV6,
+    };
+    let socket = Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?;
+
+    socket.set_reuse_address(true)?;
+
+    let what = format!("Port {}", port);
+    match group {
+        IpAddr::V4(group) => {
+            let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port);
+            socket.bind(&addr.into())?;
+            join_with_retry(
+                || socket.join_multicast_v4(&group, &interface_ip),
+                join_policy,
+                &what,
+            )?;
+            socket.set_multicast_loop_v4(false)?;
+            // Delay_Req goes out on the PTP interface, not the default route
+            socket.set_multicast_if_v4(&interface_ip)?;
+        }
+        IpAddr::V6(group) => {
+            socket.set_only_v6(true)?;
+            let addr = SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, port, 0, 0);
+            socket.bind(&addr.into())?;
+            join_with_retry(
+                || socket.join_multicast_v6(&group, interface_index),
+                join_policy,
+                &what,
+            )?;
+            socket.set_multicast_loop_v6(false)?;
+            socket.set_multicast_if_v6(interface_index)?;
+        }
+    }
     socket.set_nonblocking(true)?;
 
     let udp_socket: UdpSocket = socket.into();
 
-    #[cfg(unix)]
+    #[cfg(target_os = "linux")]
     {
         match setsockopt(&udp_socket, sockopt::ReceiveTimestampns, &true) {
             Ok(_) => log::info!("Kernel timestamping (SO_TIMESTAMPNS) enabled."),
             Err(e) => log::warn!("Failed to enable kernel timestamping: {}", e),
         }
     }
+    // No SO_TIMESTAMPNS on macOS/BSD: microsecond SO_TIMESTAMP
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        match setsockopt(&udp_socket, sockopt::ReceiveTimestamp, &true) {
+            Ok(_) => log::info!("Kernel timestamping (SO_TIMESTAMP) enabled."),
+            Err(e) => log::warn!("Failed to enable kernel timestamping: {}", e),
+        }
+    }
 
     Ok(udp_socket)
 }
 
+/// Kernel receive timestamps of one packet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RxTimestamps {
+    /// Software timestamp on the system clock (SO_TIMESTAMPNS / SO_TIMESTAMP)
+    pub software: Option<SystemTime>,
+    /// Raw NIC hardware timestamp, on the PHC's timescale (SO_TIMESTAMPING only)
+    pub hardware: Option<SystemTime>,
+}
+
+/// Receive one packet with its kernel timestamp (system clock), `now` if the
+/// kernel delivered none.
 #[cfg(unix)]
 pub fn recv_with_timestamp(
     sock: &UdpSocket,
     buf: &mut [u8],
-) -> Result<Option<(usize, std::time::SystemTime, Option<Ipv4Addr>)>> {
+) -> Result<Option<(usize, SystemTime, Option<Ipv4Addr>)>> {
+    Ok(
+        recv_with_timestamps(sock, buf)?.map(|(size, ts, source_ip)| {
+            (size, ts.software.unwrap_or_else(SystemTime::now), source_ip)
+        }),
+    )
+}
+
+/// Receive one packet with every timestamp the kernel delivered for it.
+#[cfg(unix)]
+pub fn recv_with_timestamps(
+    sock: &UdpSocket,
+    buf: &mut [u8],
+) -> Result<Option<(usize, RxTimestamps, Option<Ipv4Addr>)>> {
     use nix::sys::socket::{recvmsg, ControlMessageOwned, MsgFlags, SockaddrStorage};
+    #[cfg(target_os = "linux")]
     use nix::sys::time::TimeSpec;
+    #[cfg(not(target_os = "linux"))]
+    use nix::sys::time::TimeVal;
     use std::os::fd::AsRawFd;
-    use std::time::{Duration, SystemTime};
+    use std::time::UNIX_EPOCH;
+
+    #[cfg(target_os = "linux")]
+    fn to_system_time(ts: &TimeSpec) -> Option<SystemTime> {
+        // SO_TIMESTAMPING leaves the slots it does not fill zeroed
+        (ts.tv_sec() != 0 || ts.tv_nsec() != 0)
+            .then(|| UNIX_EPOCH + Duration::new(ts.tv_sec() as u64, ts.tv_nsec() as u32))
+    }
 
     let fd = sock.as_raw_fd();
     let mut iov = [std::io::IoSliceMut::new(buf)];
-    let mut cmsg_buf = nix::cmsg_space!(TimeSpec);
+    #[cfg(target_os = "linux")]
+    let mut cmsg_buf = nix::cmsg_space!(TimeSpec, [TimeSpec; 3]);
+    #[cfg(not(target_os = "linux"))]
+    let mut cmsg_buf = nix::cmsg_space!(TimeVal);
 
     match recvmsg::<SockaddrStorage>(fd, &mut iov, Some(&mut cmsg_buf), MsgFlags::empty()) {
         Ok(msg) => {
-            let timestamp = msg
-                .cmsgs()
-                .find_map(|cmsg| {
-                    if let ControlMessageOwned::ScmTimestampns(ts) = cmsg {
-                        let duration = Duration::new(ts.tv_sec() as u64, ts.tv_nsec() as u32);
-                        Some(SystemTime::UNIX_EPOCH + duration)
-                    } else {
-                        None
+            let mut timestamps = RxTimestamps::default();
+            for cmsg in msg.cmsgs() {
+                match cmsg {
+                    #[cfg(target_os = "linux")]
+                    ControlMessageOwned::ScmTimestampns(ts) => {
+                        timestamps.software = to_system_time(&ts);
                     }
-                })
-                .unwrap_or_else(SystemTime::now);
+                    // SO_TIMESTAMPING: [software, legacy, raw hardware]
+                    #[cfg(target_os = "linux")]
+                    ControlMessageOwned::ScmTimestampsns(ts) => {
+                        timestamps.software = timestamps.software.or(to_system_time(&ts.system));
+                        timestamps.hardware = to_system_time(&ts.hw_raw);
+                    }
+                    #[cfg(not(target_os = "linux"))]
+                    ControlMessageOwned::ScmTimestamp(tv) => {
+                        timestamps.software = Some(
+                            UNIX_EPOCH
+                                + Duration::new(tv.tv_sec() as u64, tv.tv_usec() as u32 * 1_000),
+                        );
+                    }
+                    _ => {}
+                }
+            }
 
             // Extract source IP from the address field
             let source_ip = msg.address.and_then(|addr| {
@@ -124,13 +423,131 @@ pub fn recv_with_timestamp(
                 })
             });
 
-            Ok(Some((msg.bytes, timestamp, source_ip)))
+            Ok(Some((msg.bytes, timestamps, source_ip)))
         }
         Err(nix::errno::Errno::EAGAIN) => Ok(None),
         Err(e) => Err(e.into()),
     }
 }
 
+/// Most packets `drain_socket` discards: a flood must not keep it looping.
+pub const MAX_DRAIN_PACKETS: usize = 1024;
+
+/// Discard what is queued on the non-blocking `socket` (packets timestamped
+/// before a clock step). Returns how many packets were dropped.
+pub fn drain_socket(socket: &UdpSocket) -> usize {
+    let mut buf = [0u8; 2048];
+    let mut drained = 0;
+    // WouldBlock (empty) and real errors both end the drain
+    while drained < MAX_DRAIN_PACKETS && socket.recv_from(&mut buf).is_ok() {
+        drained += 1;
+    }
+    drained
+}
+
+/// Wait until one of `sockets` has data to read or `timeout` passes (poll(2)).
+/// Returns whether any became readable; a signal counts as a timeout.
+#[cfg(unix)]
+pub fn wait_readable(sockets: &[&UdpSocket], timeout: Duration) -> Result<bool> {
+    use nix::poll::{poll, PollFd, PollFlags};
+
+    let mut fds: Vec<PollFd> = sockets
+        .iter()
+        .map(|sock| PollFd::new(*sock, PollFlags::POLLIN))
+        .collect();
+    // Round up: a sub-millisecond timeout must still block, not spin
+    let timeout_ms = ((timeout.as_micros() + 999) / 1000).min(i32::MAX as u128) as i32;
+    match poll(&mut fds, timeout_ms) {
+        Ok(ready) => Ok(ready > 0),
+        Err(nix::errno::Errno::EINTR) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Request raw hardware receive timestamps on `sock` (SO_TIMESTAMPING), keeping the
+/// software ones as the fallback. The NIC filter must be enabled separately
+/// (`ethtool::enable_hw_rx_filter`).
+#[cfg(target_os = "linux")]
+pub fn enable_hw_timestamping(sock: &UdpSocket) -> Result<()> {
+    use nix::sys::socket::TimestampingFlag;
+
+    let flags = TimestampingFlag::SOF_TIMESTAMPING_RX_HARDWARE
+        | TimestampingFlag::SOF_TIMESTAMPING_RAW_HARDWARE
+        | TimestampingFlag::SOF_TIMESTAMPING_RX_SOFTWARE
+        | TimestampingFlag::SOF_TIMESTAMPING_SOFTWARE;
+    setsockopt(sock, sockopt::Timestamping, &flags)?;
+    Ok(())
+}
+
+/// Hardware and software timestamp of one packet may differ by at most this.
+/// More means the PHC is not on the system timescale (not disciplined by phc2sys).
+const HW_TIMESCALE_TOLERANCE: Duration = Duration::from_millis(10);
+
+/// Consecutive off-timescale hardware timestamps before giving up on them
+const HW_TIMESCALE_MISMATCH_LIMIT: u32 = 8;
+
+/// Chooses between hardware and software receive timestamps.
+///
+/// The hardware timestamp is raw PHC time: the servo can only use it while the
+/// PHC follows the system clock. Otherwise the software timestamp is used, and
+/// hardware timestamping is switched off for good after repeated mismatches.
+#[derive(Debug)]
+pub struct HwTimestampSelector {
+    active: bool,
+    mismatches: u32,
+}
+
+impl Default for HwTimestampSelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HwTimestampSelector {
+    pub fn new() -> Self {
+        Self {
+            active: true,
+            mismatches: 0,
+        }
+    }
+
+    /// Hardware timestamps are (still) being used.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// Timestamp to use for a packet, None if the kernel delivered none.
+    pub fn select(&mut self, ts: RxTimestamps) -> Option<SystemTime> {
+        let (Some(hardware), true) = (ts.hardware, self.active) else {
+            return ts.software;
+        };
+        let Some(software) = ts.software else {
+            return Some(hardware);
+        };
+
+        let (diff, hw_ahead) = match hardware.duration_since(software) {
+            Ok(d) => (d, true),
+            Err(e) => (e.duration(), false),
+        };
+        if diff <= HW_TIMESCALE_TOLERANCE {
+            self.mismatches = 0;
+            return Some(hardware);
+        }
+
+        self.mismatches += 1;
+        if self.mismatches >= HW_TIMESCALE_MISMATCH_LIMIT {
+            self.active = false;
+            log::warn!(
+                "[HWTS] Hardware timestamps are {}{:.3}s off the system clock: the PHC runs on its own timescale. \
+                 Keep it on CLOCK_REALTIME (phc2sys -s CLOCK_REALTIME -c <iface>) - using software timestamps",
+                if hw_ahead { "+" } else { "-" },
+                diff.as_secs_f64()
+            );
+        }
+        Some(software)
+    }
+}
+
 #[cfg(not(unix))]
 pub fn recv_with_timestamp(
     sock: &UdpSocket,
@@ -149,6 +566,23 @@ pub fn recv_with_timestamp(
     }
 }
 
+/// User-space receive time only (no kernel timestamps on this platform).
+#[cfg(not(unix))]
+pub fn recv_with_timestamps(
+    sock: &UdpSocket,
+    buf: &mut [u8],
+) -> Result<Option<(usize, RxTimestamps, Option<Ipv4Addr>)>> {
+    Ok(
+        recv_with_timestamp(sock, buf)?.map(|(size, ts, source_ip)| {
+            let timestamps = RxTimestamps {
+                software: Some(ts),
+                hardware: None,
+            };
+            (size, timestamps, source_ip)
+        }),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +601,18 @@ mod tests {
         // Error case is acceptable on minimal test environments
     }
 
+    /// Loopback is always listed, and never as the auto-detected interface
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_list_interfaces_marks_loopback() {
+        let lines = list_interfaces().unwrap();
+        assert!(
+            lines.iter().any(|l| l.ends_with("127.0.0.1 (loopback)")),
+            "{:?}",
+            lines
+        );
+    }
+
     /// Test is_ip_bindable with loopback (should always work)
     #[test]
     fn test_is_ip_bindable_loopback() {
@@ -194,6 +640,56 @@ mod tests {
         assert_eq!(multi_addr.octets(), [224, 0, 1, 129]);
     }
 
+    fn optional_join() -> JoinPolicy {
+        JoinPolicy {
+            retries: 0,
+            required: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_create_multicast_socket_ipv4() {
+        let group = IpAddr::from(crate::ptp::PTP_MULTICAST_ADDR);
+        let socket =
+            create_multicast_socket(0, group, Ipv4Addr::LOCALHOST, 0, &optional_join()).unwrap();
+        assert!(socket.local_addr().unwrap().is_ipv4());
+
+        let unicast = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        assert!(
+            create_multicast_socket(0, unicast, Ipv4Addr::LOCALHOST, 0, &optional_join()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_create_multicast_socket_ipv6() {
+        // Hosts without IPv6 cannot create the socket at all
+        if Socket::new(Domain::IPV6, Type::DGRAM, None).is_err() {
+            return;
+        }
+        let loopback = if_addrs::get_if_addrs()
+            .unwrap()
+            .into_iter()
+            .find(|iface| iface.is_loopback())
+            .map(|iface| iface.name);
+        let index = loopback.map_or(0, |name| interface_index(&name).unwrap());
+
+        let group: IpAddr = "ff02::181".parse().unwrap();
+        let socket =
+            create_multicast_socket(0, group, Ipv4Addr::LOCALHOST, index, &optional_join())
+                .unwrap();
+        assert!(socket.local_addr().unwrap().is_ipv6());
+        assert_eq!(
+            group_socket_addr(group, 319, index),
+            SocketAddr::V6(SocketAddrV6::new(
+                "ff02::181".parse().unwrap(),
+                319,
+                0,
+                index
+            ))
+        );
+    }
+
     /// Test recv_with_timestamp returns None for non-blocking socket with no data
     #[test]
     fn test_recv_with_timestamp_no_data() {
@@ -213,6 +709,59 @@ mod tests {
         assert!(result.unwrap().is_none());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_wait_readable_wakes_on_any_socket() {
+        let idle = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let target = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+
+        let start = std::time::Instant::now();
+        assert!(!wait_readable(&[&idle, &target], Duration::from_millis(20)).unwrap());
+        assert!(start.elapsed() >= Duration::from_millis(20), "blocked");
+
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        sender
+            .send_to(b"ptp", target.local_addr().unwrap())
+            .unwrap();
+        let start = std::time::Instant::now();
+        assert!(wait_readable(&[&idle, &target], Duration::from_secs(5)).unwrap());
+        assert!(
+            start.elapsed() < Duration::from_secs(1),
+            "woke on the packet"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_drain_socket_discards_queued_packets() {
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        for seq in 0..3u8 {
+            sender
+                .send_to(&[seq], socket.local_addr().unwrap())
+                .unwrap();
+        }
+        assert!(wait_readable(&[&socket], Duration::from_secs(5)).unwrap());
+        // Loopback delivery is immediate, but let all three arrive
+        thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(drain_socket(&socket), 3);
+        let mut buf = [0u8; 16];
+        assert_eq!(
+            socket.recv_from(&mut buf).unwrap_err().kind(),
+            std::io::ErrorKind::WouldBlock,
+            "nothing left"
+        );
+        assert_eq!(drain_socket(&socket), 0);
+
+        // Still receives what arrives afterwards
+        sender.send_to(&[9], socket.local_addr().unwrap()).unwrap();
+        assert!(wait_readable(&[&socket], Duration::from_secs(5)).unwrap());
+        assert_eq!(socket.recv_from(&mut buf).unwrap().0, 1);
+        assert_eq!(buf[0], 9);
+    }
+
     /// Test wireless interface detection keywords
     #[test]
     fn test_wireless_interface_detection() {
@@ -234,4 +783,135 @@ mod tests {
             assert!(!is_wireless, "{} should NOT be detected as wireless", name);
         }
     }
+
+    #[test]
+    fn test_join_with_retry_recovers_from_transient_failure() {
+        let policy = JoinPolicy {
+            retries: 3,
+            initial_backoff: Duration::from_millis(1),
+            required: true,
+        };
+        let mut calls = 0;
+        let joined = join_with_retry(
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err(std::io::Error::from(std::io::ErrorKind::AddrNotAvailable))
+                } else {
+                    Ok(())
+                }
+            },
+            &policy,
+            "test",
+        )
+        .unwrap();
+        assert!(joined);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_join_with_retry_gives_up_after_bounded_retries() {
+        let fail = || Err(std::io::Error::from(std::io::ErrorKind::AddrNotAvailable));
+        let mut policy = JoinPolicy {
+            retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            required: true,
+        };
+        let mut calls = 0;
+        let result = join_with_retry(
+            || {
+                calls += 1;
+                fail()
+            },
+            &policy,
+            "test",
+        );
+        assert!(result.is_err());
+        assert_eq!(calls, 3, "first attempt + 2 retries");
+
+        // Optional join: warn and continue unjoined
+        policy.required = false;
+        assert!(!join_with_retry(fail, &policy, "test").unwrap());
+    }
+    #[test]
+    fn test_match_interface_by_name_or_ip() {
+        let ifaces = vec![
+            ("AV".to_string(), Ipv4Addr::new(10, 77, 8, 20)),
+            ("Management".to_string(), Ipv4Addr::new(192, 168, 1, 20)),
+        ];
+        assert_eq!(
+            match_interface(&ifaces, "management").unwrap().0,
+            "Management"
+        );
+        assert_eq!(match_interface(&ifaces, "10.77.8.20").unwrap().0, "AV");
+        assert!(match_interface(&ifaces, "10.77.8.21").is_none());
+        assert!(match_interface(&ifaces, "eth9").is_none());
+    }
+
+    #[test]
+    fn test_resolve_bind_address() {
+        assert_eq!(
+            resolve_bind_address("0.0.0.0").unwrap(),
+            Ipv4Addr::UNSPECIFIED
+        );
+        assert_eq!(
+            resolve_bind_address("127.0.0.1").unwrap(),
+            Ipv4Addr::LOCALHOST
+        );
+        assert!(resolve_bind_address("no-such-nic").is_err());
+    }
+
+    /// Hardware timestamps are used while they track the system clock; a PHC on its
+    /// own timescale falls back to software and gives up after a run of mismatches
+    #[test]
+    fn test_hw_timestamp_selector() {
+        let sw = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let ts = |hw_offset: Option<Duration>| RxTimestamps {
+            software: Some(sw),
+            hardware: hw_offset.map(|d| sw + d),
+        };
+        let mut selector = HwTimestampSelector::new();
+
+        let near = sw + Duration::from_micros(40);
+        assert_eq!(
+            selector.select(ts(Some(Duration::from_micros(40)))),
+            Some(near)
+        );
+        assert_eq!(selector.select(ts(None)), Some(sw));
+        let hw_only = RxTimestamps {
+            software: None,
+            hardware: Some(near),
+        };
+        assert_eq!(selector.select(hw_only), Some(near));
+
+        // PHC 37s ahead (TAI): software until the limit, then hardware is off for good
+        let tai = Some(Duration::from_secs(37));
+        for _ in 0..HW_TIMESCALE_MISMATCH_LIMIT {
+            assert!(selector.active());
+            assert_eq!(selector.select(ts(tai)), Some(sw));
+        }
+        assert!(!selector.active());
+        assert_eq!(
+            selector.select(ts(Some(Duration::from_micros(40)))),
+            Some(sw)
+        );
+    }
+
+    /// A single outlier does not disable hardware timestamps
+    #[test]
+    fn test_hw_timestamp_selector_mismatch_count_resets() {
+        let sw = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut selector = HwTimestampSelector::new();
+        for _ in 0..(HW_TIMESCALE_MISMATCH_LIMIT * 2) {
+            selector.select(RxTimestamps {
+                software: Some(sw),
+                hardware: Some(sw + Duration::from_secs(1)),
+            });
+            selector.select(RxTimestamps {
+                software: Some(sw),
+                hardware: Some(sw),
+            });
+        }
+        assert!(selector.active());
+    }
 }
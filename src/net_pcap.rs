@@ -7,18 +7,125 @@
 //! Key: We use TimestampType::HostHighPrec which maps to PCAP_TSTAMP_HOST_HIPREC
 //! and uses KeQuerySystemTimePrecise() internally - NOT the default UNSYNCED mode.
 
+use crate::net::{join_with_retry, JoinPolicy};
 use anyhow::{anyhow, Result};
 use log::{debug, info, warn};
 use pcap::{Active, Capture, Device, TimestampType};
-use std::net::{Ipv4Addr, UdpSocket};
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const PTP_EVENT_PORT: u16 = 319;
 const PTP_GENERAL_PORT: u16 = 320;
-const PTP_MULTICAST: Ipv4Addr = Ipv4Addr::new(224, 0, 1, 129);
 
-/// Create a socket and join PTP multicast group (for IGMP membership)
-fn join_multicast(port: u16, iface_ip: Ipv4Addr) -> Result<UdpSocket> {
+/// Capture full Ethernet frames (incl. VLAN tag) so PTP payloads are never cut by snaplen
+const PCAP_SNAPLEN: i32 = 1518;
+
+/// Ethernet header length (no VLAN tag)
+const ETH_HEADER_LEN: usize = 14;
+/// Minimum IPv4 header length (IHL = 5)
+const IPV4_MIN_HEADER_LEN: usize = 20;
+/// UDP header length
+const UDP_HEADER_LEN: usize = 8;
+
+/// Result of decoding a captured Ethernet frame
+#[derive(Debug, PartialEq)]
+enum FrameParse<'a> {
+    /// Valid PTP UDP payload (trimmed to the UDP length)
+    Ptp {
+        payload: &'a [u8],
+        source_ip: Ipv4Addr,
+    },
+    /// Not IPv4/UDP/PTP - silently ignored
+    NotPtp,
+    /// Captured fewer bytes than the IP/UDP headers claim (snaplen or driver truncation)
+    Truncated { captured: usize, claimed: usize },
+    /// Inconsistent headers (bad IHL, lengths or checksum)
+    Malformed(&'static str),
+}
+
+/// RFC 1071 Internet checksum over an IPv4 header (0 = valid when checksum field included)
+fn ipv4_header_checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = header
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Validate Ethernet/IPv4/UDP headers against the captured length and extract the PTP payload.
+fn parse_ptp_frame(data: &[u8]) -> FrameParse<'_> {
+    if data.len() < ETH_HEADER_LEN + IPV4_MIN_HEADER_LEN {
+        return FrameParse::NotPtp;
+    }
+
+    // Verify it's an IP packet (EtherType 0x0800)
+    if data[12] != 0x08 || data[13] != 0x00 {
+        return FrameParse::NotPtp;
+    }
+
+    let ip = &data[ETH_HEADER_LEN..];
+    if ip[0] >> 4 != 4 {
+        return FrameParse::NotPtp;
+    }
+
+    // Verify UDP protocol (IP header byte 9 = protocol)
+    if ip[9] != 17 {
+        return FrameParse::NotPtp;
+    }
+
+    let ihl = ((ip[0] & 0x0F) as usize) * 4;
+    if ihl < IPV4_MIN_HEADER_LEN {
+        return FrameParse::Malformed("IPv4 IHL below minimum");
+    }
+
+    let ip_total_len = u16::from_be_bytes([ip[2], ip[3]]) as usize;
+    if ip_total_len < ihl + UDP_HEADER_LEN {
+        return FrameParse::Malformed("IPv4 total length too small for UDP");
+    }
+    if ip.len() < ip_total_len {
+        return FrameParse::Truncated {
+            captured: data.len(),
+            claimed: ETH_HEADER_LEN + ip_total_len,
+        };
+    }
+
+    if ipv4_header_checksum(&ip[..ihl]) != 0 {
+        return FrameParse::Malformed("IPv4 header checksum mismatch");
+    }
+
+    let udp = &ip[ihl..ip_total_len];
+    let dst_port = u16::from_be_bytes([udp[2], udp[3]]);
+    if dst_port != PTP_EVENT_PORT && dst_port != PTP_GENERAL_PORT {
+        return FrameParse::NotPtp;
+    }
+
+    let udp_len = u16::from_be_bytes([udp[4], udp[5]]) as usize;
+    if udp_len < UDP_HEADER_LEN || udp_len > udp.len() {
+        return FrameParse::Malformed("UDP length inconsistent with IPv4 total length");
+    }
+
+    let payload = &udp[UDP_HEADER_LEN..udp_len];
+    if payload.is_empty() {
+        return FrameParse::NotPtp;
+    }
+
+    FrameParse::Ptp {
+        payload,
+        source_ip: Ipv4Addr::new(ip[12], ip[13], ip[14], ip[15]),
+    }
+}
+
+/// Create a socket and join PTP multicast group (for IGMP membership).
+/// Returns whether the group was joined (see `JoinPolicy::required`).
+fn join_multicast(
+    port: u16,
+    group: Ipv4Addr,
+    iface_ip: Ipv4Addr,
+    policy: &JoinPolicy,
+) -> Result<(UdpSocket, bool)> {
     use socket2::{Domain, Protocol, Socket, Type};
     use std::net::SocketAddrV4;
 
@@ -28,39 +135,62 @@ fn join_multicast(port: u16, iface_ip: Ipv4Addr) -> Result<UdpSocket> {
     let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port);
     socket.bind(&addr.into())?;
 
-    socket.join_multicast_v4(&PTP_MULTICAST, &iface_ip)?;
+    let joined = join_with_retry(
+        || socket.join_multicast_v4(&group, &iface_ip),
+        policy,
+        &format!("Port {}", port),
+    )?;
     socket.set_multicast_loop_v4(false)?;
+    socket.set_multicast_if_v4(&iface_ip)?;
     socket.set_nonblocking(true)?;
 
-    Ok(socket.into())
+    Ok((socket.into(), joined))
 }
 
 /// PTP network using Npcap with HostHighPrec timestamps
 pub struct NpcapPtpNetwork {
     capture: Capture<Active>,
-    // Keep sockets alive for IGMP multicast membership
-    _igmp_sock_319: UdpSocket,
+    // Keep sockets alive for IGMP multicast membership (319 also sends Delay_Req)
+    igmp_sock_319: UdpSocket,
     _igmp_sock_320: UdpSocket,
+    /// PTP multicast group (`ptp.multicast_group`)
+    group: Ipv4Addr,
     using_hiprec: bool,
+    /// Frames dropped because the capture was shorter than the IP/UDP lengths
+    truncated_count: u64,
 }
 
 impl NpcapPtpNetwork {
-    pub fn new(interface_name: &str) -> Result<Self> {
+    /// Open the capture on the device carrying `iface_ip` (exact match, the address
+    /// the interface was selected by), else the first device whose name or
+    /// description contains `interface_name`.
+    pub fn new(
+        interface_name: &str,
+        iface_ip: Option<Ipv4Addr>,
+        group: Ipv4Addr,
+        join_policy: &JoinPolicy,
+    ) -> Result<Self> {
         info!(
             "Initializing Npcap capture on interface: {}",
             interface_name
         );
 
-        // Find the device by name or description
         let devices = Device::list()?;
-        let device = devices
-            .iter()
-            .find(|d| {
-                d.name.contains(interface_name)
-                    || d.desc
-                        .as_ref()
-                        .map(|desc| desc.contains(interface_name))
-                        .unwrap_or(false)
+        let by_ip = iface_ip.and_then(|ip| {
+            devices
+                .iter()
+                .find(|d| d.addresses.iter().any(|a| a.addr == IpAddr::V4(ip)))
+        });
+        let device = by_ip
+            .or_else(|| {
+                // Find the device by name or description
+                devices.iter().find(|d| {
+                    d.name.contains(interface_name)
+                        || d.desc
+                            .as_ref()
+                            .map(|desc| desc.contains(interface_name))
+                            .unwrap_or(false)
+                })
             })
             .or_else(|| {
                 // Try matching by IP address in description
@@ -84,42 +214,49 @@ impl NpcapPtpNetwork {
 
         info!("Found device: {} ({:?})", device.name, device.desc);
 
-        // Extract interface IP for multicast join
-        let iface_ip = device
-            .addresses
-            .iter()
-            .find_map(|a| {
-                if let std::net::IpAddr::V4(ip) = a.addr {
-                    if !ip.is_loopback() {
-                        return Some(ip);
-                    }
-                }
-                None
+        // Extract interface IP for multicast join (the selected one if it is on this device)
+        let iface_ip = by_ip
+            .and(iface_ip)
+            .or_else(|| {
+                device.addresses.iter().find_map(|a| match a.addr {
+                    IpAddr::V4(ip) if !ip.is_loopback() => Some(ip),
+                    _ => None,
+                })
             })
             .ok_or_else(|| anyhow!("No IPv4 address found on device"))?;
 
         info!("Using interface IP {} for multicast join", iface_ip);
 
         // CRITICAL: Join multicast group via sockets to trigger IGMP
-        let igmp_sock_319 = join_multicast(PTP_EVENT_PORT, iface_ip)?;
-        let igmp_sock_320 = join_multicast(PTP_GENERAL_PORT, iface_ip)?;
-        info!("Joined PTP multicast group 224.0.1.129 on ports 319 and 320");
+        let (igmp_sock_319, joined_319) =
+            join_multicast(PTP_EVENT_PORT, group, iface_ip, join_policy)?;
+        let (igmp_sock_320, joined_320) =
+            join_multicast(PTP_GENERAL_PORT, group, iface_ip, join_policy)?;
+        let joined = joined_319 && joined_320;
+        if joined {
+            info!("Joined PTP multicast group {} on ports 319 and 320", group);
+        } else {
+            warn!(
+                "[Net] Not a member of {} - capturing promiscuously instead",
+                group
+            );
+        }
 
         // Create capture handle with HostHighPrec timestamps
         // HostHighPrec uses KeQuerySystemTimePrecise() which is both high-precision AND synced with system time
         info!("[TS] Requesting HostHighPrec timestamps (KeQuerySystemTimePrecise)");
 
         let mut capture = Capture::from_device(device.clone())?
-            .promisc(false) // Don't use promiscuous - rely on IGMP multicast join
+            .promisc(!joined) // Rely on the IGMP join; promiscuous only without membership
             .immediate_mode(true) // Critical: disable buffering for lowest latency
-            .snaplen(256) // PTP packets are small
+            .snaplen(PCAP_SNAPLEN) // Full frames - never truncate PTP payloads
             .timeout(1) // 1ms timeout for responsiveness
             .tstamp_type(TimestampType::HostHighPrec)
             .open()?;
 
         // Apply BPF filter to only capture PTP multicast - reduces conflict with DVS
-        let ptp_filter = "udp and dst host 224.0.1.129 and (dst port 319 or dst port 320)";
-        capture.filter(ptp_filter, true)?;
+        let ptp_filter = ptp_bpf_filter(group);
+        capture.filter(&ptp_filter, true)?;
         info!("[Filter] Applied BPF: {}", ptp_filter);
 
         // Assume HostHighPrec is available on modern Npcap (1.20+)
@@ -134,9 +271,11 @@ impl NpcapPtpNetwork {
 
         Ok(NpcapPtpNetwork {
             capture,
-            _igmp_sock_319: igmp_sock_319,
+            igmp_sock_319,
             _igmp_sock_320: igmp_sock_320,
+            group,
             using_hiprec,
+            truncated_count: 0,
         })
     }
 
@@ -172,49 +311,36 @@ impl crate::traits::PtpNetwork for NpcapPtpNetwork {
                     SystemTime::now()
                 };
 
-                // Extract UDP payload from Ethernet frame
-                // Ethernet (14) + IP (20) + UDP (8) = 42 bytes header
-                const ETH_IP_UDP_HEADER: usize = 42;
-
-                if data.len() < ETH_IP_UDP_HEADER {
-                    return Ok(None);
-                }
-
-                // Verify it's an IP packet (EtherType 0x0800)
-                if data[12] != 0x08 || data[13] != 0x00 {
-                    return Ok(None);
-                }
-
-                // Verify UDP protocol (IP header byte 9 = protocol)
-                if data[23] != 17 {
-                    return Ok(None);
-                }
-
-                // Check destination port for PTP (319 or 320)
-                let dst_port = ((data[36] as u16) << 8) | data[37] as u16;
-                if dst_port != 319 && dst_port != 320 {
-                    return Ok(None);
-                }
-
-                // Extract source IP from IP header (Ethernet 14 bytes + IP src at offset 12)
-                // Source IP is at bytes 26-29 of the Ethernet frame
-                let source_ip = Ipv4Addr::new(data[26], data[27], data[28], data[29]);
-
-                // Extract UDP payload
-                let payload = &data[ETH_IP_UDP_HEADER..];
-                let payload_len = payload.len();
-
-                if payload_len > 0 {
-                    let mut result = vec![0u8; payload_len];
-                    result.copy_from_slice(payload);
-
-                    debug!(
-                        "[Npcap] PTP payload {} bytes from {}",
-                        payload_len, source_ip
-                    );
-                    Ok(Some((result, payload_len, ts, Some(source_ip))))
-                } else {
-                    Ok(None)
+                match parse_ptp_frame(data) {
+                    FrameParse::Ptp { payload, source_ip } => {
+                        debug!(
+                            "[Npcap] PTP payload {} bytes from {}",
+                            payload.len(),
+                            source_ip
+                        );
+                        Ok(Some((payload.to_vec(), payload.len(), ts, Some(source_ip))))
+                    }
+                    FrameParse::NotPtp => Ok(None),
+                    FrameParse::Truncated { captured, claimed } => {
+                        self.truncated_count += 1;
+                        // Warn once, then keep it at debug level to avoid log spam
+                        if self.truncated_count == 1 {
+                            warn!(
+                                "[Npcap] Packet truncated by capture: {} of {} bytes (caplen={}, snaplen={})",
+                                captured, claimed, header.caplen, PCAP_SNAPLEN
+                            );
+                        } else {
+                            debug!(
+                                "[Npcap] Packet truncated: {} of {} bytes ({} total)",
+                                captured, claimed, self.truncated_count
+                            );
+                        }
+                        Ok(None)
+                    }
+                    FrameParse::Malformed(reason) => {
+                        debug!("[Npcap] Dropping malformed frame: {}", reason);
+                        Ok(None)
+                    }
                 }
             }
             Err(pcap::Error::TimeoutExpired) => {
@@ -229,11 +355,33 @@ impl crate::traits::PtpNetwork for NpcapPtpNetwork {
     }
 
     fn reset(&mut self) -> Result<()> {
-        // Npcap doesn't need explicit reset
+        // Discard captured frames: their timestamps are from before a clock step.
+        // The capture's 1ms read timeout ends the drain once it is empty
+        let mut drained = 0;
+        while drained < crate::net::MAX_DRAIN_PACKETS && self.capture.next_packet().is_ok() {
+            drained += 1;
+        }
+        if drained > 0 {
+            debug!("[Npcap] Discarded {} captured frames", drained);
+        }
+        Ok(())
+    }
+
+    fn send_packet(&mut self, data: &[u8]) -> Result<()> {
+        self.igmp_sock_319
+            .send_to(data, (self.group, crate::ptp::PTP_EVENT_PORT))?;
         Ok(())
     }
 }
 
+/// BPF filter passing only PTP traffic to `group`.
+fn ptp_bpf_filter(group: Ipv4Addr) -> String {
+    format!(
+        "udp and dst host {} and (dst port {} or dst port {})",
+        group, PTP_EVENT_PORT, PTP_GENERAL_PORT
+    )
+}
+
 /// Get list of available Npcap devices
 pub fn list_npcap_devices() -> Result<Vec<String>> {
     let devices = Device::list()?;
@@ -252,8 +400,18 @@ mod tests {
     fn test_ptp_constants() {
         assert_eq!(PTP_EVENT_PORT, 319);
         assert_eq!(PTP_GENERAL_PORT, 320);
-        assert_eq!(PTP_MULTICAST, Ipv4Addr::new(224, 0, 1, 129));
-        assert!(PTP_MULTICAST.is_multicast());
+    }
+
+    #[test]
+    fn test_ptp_bpf_filter() {
+        assert_eq!(
+            ptp_bpf_filter(Ipv4Addr::from(crate::ptp::PTP_MULTICAST_ADDR)),
+            "udp and dst host 224.0.1.129 and (dst port 319 or dst port 320)"
+        );
+        assert_eq!(
+            ptp_bpf_filter(Ipv4Addr::new(224, 0, 0, 107)),
+            "udp and dst host 224.0.0.107 and (dst port 319 or dst port 320)"
+        );
     }
 
     /// Test pcap timestamp to SystemTime conversion
@@ -362,4 +520,87 @@ mod tests {
         let dst_port = ((frame[36] as u16) << 8) | frame[37] as u16;
         assert!(dst_port == 319 || dst_port == 320, "Should be PTP port");
     }
+
+    /// Build an Ethernet/IPv4/UDP frame carrying `payload` to `dst_port` with a valid checksum
+    fn build_frame(payload: &[u8], dst_port: u16) -> Vec<u8> {
+        let ip_total = (IPV4_MIN_HEADER_LEN + UDP_HEADER_LEN + payload.len()) as u16;
+        let udp_len = (UDP_HEADER_LEN + payload.len()) as u16;
+        let mut frame = vec![0u8; ETH_HEADER_LEN];
+        frame[12] = 0x08;
+        frame[13] = 0x00;
+
+        let mut ip = vec![0x45, 0, 0, 0, 0, 0, 0, 0, 1, 17, 0, 0];
+        ip[2..4].copy_from_slice(&ip_total.to_be_bytes());
+        ip.extend_from_slice(&[10, 0, 0, 5]); // src
+        ip.extend_from_slice(&[224, 0, 1, 129]); // dst
+        let csum = ipv4_header_checksum(&ip);
+        ip[10..12].copy_from_slice(&csum.to_be_bytes());
+        frame.extend_from_slice(&ip);
+
+        frame.extend_from_slice(&dst_port.to_be_bytes()); // src port
+        frame.extend_from_slice(&dst_port.to_be_bytes());
+        frame.extend_from_slice(&udp_len.to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn test_parse_ptp_frame_valid() {
+        let payload = vec![0x10u8; 124];
+        let frame = build_frame(&payload, 319);
+        match parse_ptp_frame(&frame) {
+            FrameParse::Ptp {
+                payload: p,
+                source_ip,
+            } => {
+                assert_eq!(p, &payload[..]);
+                assert_eq!(source_ip, Ipv4Addr::new(10, 0, 0, 5));
+            }
+            other => panic!("Expected PTP payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_ptp_frame_truncated_by_snaplen() {
+        let frame = build_frame(&[0x10u8; 124], 319);
+        let truncated = &frame[..100];
+        assert_eq!(
+            parse_ptp_frame(truncated),
+            FrameParse::Truncated {
+                captured: 100,
+                claimed: frame.len()
+            },
+            "Short capture should be reported as truncated"
+        );
+    }
+
+    #[test]
+    fn test_parse_ptp_frame_ignores_ethernet_padding() {
+        // Short frames are padded to 60 bytes - payload must be trimmed to UDP length
+        let mut frame = build_frame(&[0xAB; 4], 320);
+        frame.resize(60, 0);
+        match parse_ptp_frame(&frame) {
+            FrameParse::Ptp { payload, .. } => assert_eq!(payload, &[0xAB; 4]),
+            other => panic!("Expected PTP payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_ptp_frame_rejects_bad_lengths_and_checksum() {
+        let mut frame = build_frame(&[0x10u8; 44], 319);
+        frame[ETH_HEADER_LEN + 10] ^= 0xFF; // corrupt checksum
+        assert!(matches!(parse_ptp_frame(&frame), FrameParse::Malformed(_)));
+
+        let mut frame = build_frame(&[0x10u8; 44], 319);
+        let udp_len_off = ETH_HEADER_LEN + IPV4_MIN_HEADER_LEN + 4;
+        frame[udp_len_off..udp_len_off + 2].copy_from_slice(&200u16.to_be_bytes());
+        assert!(matches!(parse_ptp_frame(&frame), FrameParse::Malformed(_)));
+    }
+
+    #[test]
+    fn test_parse_ptp_frame_non_ptp_port() {
+        let frame = build_frame(&[0x10u8; 44], 5353);
+        assert_eq!(parse_ptp_frame(&frame), FrameParse::NotPtp);
+    }
 }
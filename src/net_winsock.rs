@@ -29,7 +29,6 @@ use windows::Win32::System::IO::OVERLAPPED;
 
 const PTP_EVENT_PORT: u16 = 319;
 const PTP_GENERAL_PORT: u16 = 320;
-const PTP_MULTICAST: Ipv4Addr = Ipv4Addr::new(224, 0, 1, 129);
 
 // SIO_TIMESTAMPING constants (not in windows crate, defined per MS docs)
 const SIO_TIMESTAMPING: u32 = 0x88000025;
@@ -48,12 +47,24 @@ struct CmsgHdr {
 }
 
 /// IP multicast membership request
+///
+/// Both addresses are in network byte order (as `in_addr.s_addr`), see `to_in_addr`.
 #[repr(C)]
 struct IpMreq {
     imr_multiaddr: u32,
     imr_interface: u32,
 }
 
+/// Convert an address to `in_addr.s_addr` form (network byte order in memory).
+fn to_in_addr(ip: Ipv4Addr) -> u32 {
+    u32::from(ip).to_be()
+}
+
+/// Convert `in_addr.s_addr` (network byte order in memory) back to an address.
+fn from_in_addr(s_addr: u32) -> Ipv4Addr {
+    Ipv4Addr::from(u32::from_be(s_addr))
+}
+
 /// Timestamping configuration structure
 #[repr(C)]
 struct TimestampingConfig {
@@ -81,7 +92,7 @@ pub struct WinsockPtpNetwork {
 }
 
 impl WinsockPtpNetwork {
-    pub fn new(interface_ip: Ipv4Addr) -> Result<Self> {
+    pub fn new(interface_ip: Ipv4Addr, group: Ipv4Addr) -> Result<Self> {
         info!("Initializing Winsock PTP network with SO_TIMESTAMP");
 
         // Initialize Winsock
@@ -102,8 +113,8 @@ impl WinsockPtpNetwork {
         };
 
         // Create and configure sockets
-        let socket_319 = Self::create_ptp_socket(PTP_EVENT_PORT, interface_ip)?;
-        let socket_320 = Self::create_ptp_socket(PTP_GENERAL_PORT, interface_ip)?;
+        let socket_319 = Self::create_ptp_socket(PTP_EVENT_PORT, interface_ip, group)?;
+        let socket_320 = Self::create_ptp_socket(PTP_GENERAL_PORT, interface_ip, group)?;
 
         // Get WSARecvMsg function pointer
         let recv_msg_fn = Self::get_wsarecvmsg_fn(socket_319)?;
@@ -133,7 +144,7 @@ impl WinsockPtpNetwork {
         })
     }
 
-    fn create_ptp_socket(port: u16, interface_ip: Ipv4Addr) -> Result<SOCKET> {
+    fn create_ptp_socket(port: u16, interface_ip: Ipv4Addr, group: Ipv4Addr) -> Result<SOCKET> {
         unsafe {
             // Create UDP socket
             let sock = socket(AF_INET.0 as i32, SOCK_DGRAM, IPPROTO_UDP.0 as i32);
@@ -179,8 +190,8 @@ impl WinsockPtpNetwork {
 
             // Join PTP multicast group
             let mreq = IpMreq {
-                imr_multiaddr: u32::from_ne_bytes(PTP_MULTICAST.octets()),
-                imr_interface: u32::from_ne_bytes(interface_ip.octets()),
+                imr_multiaddr: to_in_addr(group),
+                imr_interface: to_in_addr(interface_ip),
             };
 
             if setsockopt(
@@ -212,7 +223,7 @@ impl WinsockPtpNetwork {
                 warn!("Failed to set non-blocking mode: {}", WSAGetLastError().0);
             }
 
-            info!("PTP socket created on port {} (joined 224.0.1.129)", port);
+            info!("PTP socket created on port {} (joined {})", port, group);
             Ok(sock)
         }
     }
@@ -355,13 +366,7 @@ impl WinsockPtpNetwork {
 
             // Extract source IP from sockaddr
             let source_ip = if sockaddr.sin_family == AF_INET {
-                let ip_bytes = sockaddr.sin_addr.S_un.S_addr.to_ne_bytes();
-                Some(Ipv4Addr::new(
-                    ip_bytes[0],
-                    ip_bytes[1],
-                    ip_bytes[2],
-                    ip_bytes[3],
-                ))
+                Some(from_in_addr(sockaddr.sin_addr.S_un.S_addr))
             } else {
                 None
             };
@@ -505,13 +510,7 @@ impl WinsockPtpNetwork {
 
             // Extract source IP
             let source_ip = if sockaddr.sin_family == AF_INET {
-                let ip_bytes = sockaddr.sin_addr.S_un.S_addr.to_ne_bytes();
-                Some(Ipv4Addr::new(
-                    ip_bytes[0],
-                    ip_bytes[1],
-                    ip_bytes[2],
-                    ip_bytes[3],
-                ))
+                Some(from_in_addr(sockaddr.sin_addr.S_un.S_addr))
             } else {
                 None
             };
@@ -523,6 +522,21 @@ impl WinsockPtpNetwork {
     }
 }
 
+impl WinsockPtpNetwork {
+    /// Discard what is queued on `sock` (see `net::drain_socket`).
+    fn drain(&self, sock: SOCKET) -> usize {
+        let mut drained = 0;
+        while drained < crate::net::MAX_DRAIN_PACKETS {
+            match self.recv_fallback(sock) {
+                Ok(Some(_)) => drained += 1,
+                // WouldBlock (empty) and errors both end the drain
+                _ => break,
+            }
+        }
+        drained
+    }
+}
+
 impl Drop for WinsockPtpNetwork {
     fn drop(&mut self) {
         unsafe {
@@ -545,7 +559,11 @@ impl crate::traits::PtpNetwork for WinsockPtpNetwork {
     }
 
     fn reset(&mut self) -> Result<()> {
-        // No state to reset for Winsock sockets
+        // Drain both sockets: queued packets carry timestamps from before a clock step
+        let drained = self.drain(self.socket_319) + self.drain(self.socket_320);
+        if drained > 0 {
+            debug!("[Winsock] Discarded {} queued packets", drained);
+        }
         Ok(())
     }
 }
@@ -554,6 +572,8 @@ impl crate::traits::PtpNetwork for WinsockPtpNetwork {
 mod tests {
     use super::*;
 
+    const PTP_MULTICAST: Ipv4Addr = Ipv4Addr::new(224, 0, 1, 129);
+
     /// Test PTP port constants
     #[test]
     fn test_ptp_port_constants() {
@@ -564,7 +584,10 @@ mod tests {
     /// Test PTP multicast address constant
     #[test]
     fn test_ptp_multicast_constant() {
-        assert_eq!(PTP_MULTICAST, Ipv4Addr::new(224, 0, 1, 129));
+        assert_eq!(
+            PTP_MULTICAST,
+            Ipv4Addr::from(crate::ptp::PTP_MULTICAST_ADDR)
+        );
         assert!(PTP_MULTICAST.is_multicast());
     }
 
@@ -635,10 +658,34 @@ mod tests {
         assert_eq!(bytes, [224, 0, 1, 129]);
 
         // As u32 in network byte order
-        let u32_val = u32::from_ne_bytes(bytes);
+        let u32_val = to_in_addr(addr);
         assert_ne!(u32_val, 0);
     }
 
+    /// in_addr must hold the address in network byte order on any target endianness
+    #[test]
+    fn test_in_addr_network_byte_order() {
+        let s_addr = to_in_addr(PTP_MULTICAST);
+        assert_eq!(
+            s_addr.to_ne_bytes(),
+            [224, 0, 1, 129],
+            "In-memory bytes must be the octets in network order"
+        );
+        assert_eq!(from_in_addr(s_addr), PTP_MULTICAST);
+
+        let mreq = IpMreq {
+            imr_multiaddr: to_in_addr(PTP_MULTICAST),
+            imr_interface: to_in_addr(Ipv4Addr::new(192, 168, 1, 20)),
+        };
+        let raw = unsafe {
+            std::slice::from_raw_parts(
+                &mreq as *const IpMreq as *const u8,
+                mem::size_of::<IpMreq>(),
+            )
+        };
+        assert_eq!(raw, &[224, 0, 1, 129, 192, 168, 1, 20]);
+    }
+
     /// Test WSA error code constants
     #[test]
     fn test_wsa_error_codes() {
@@ -1,24 +1,76 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use log::{debug, info};
 use rsntp::SntpClient;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 use std::time::Duration;
 
+/// Pause between burst queries (public servers rate-limit rapid queries)
+const BURST_SPACING: Duration = Duration::from_millis(250);
+
+/// One NTP measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NtpSample {
+    pub offset: Duration,
+    pub sign: i8,
+    /// Round-trip delay of the exchange. The offset error is bounded by half of it
+    /// (path asymmetry), so the sample with the lowest delay is the most trustworthy.
+    pub round_trip: Duration,
+}
+
+/// No server has answered yet (`NtpClient::answered`).
+const NO_ANSWER: usize = usize::MAX;
+
 pub struct NtpClient {
-    server: String,
+    /// Queried in order until one answers
+    servers: Vec<String>,
+    /// Local source address; None = ephemeral socket on the OS-chosen route
+    bind: Option<IpAddr>,
+    /// Index of the server that answered last (failover logging)
+    answered: AtomicUsize,
 }
 
 impl NtpClient {
     pub fn new(server: &str) -> Self {
+        Self::with_servers(&[server.to_string()])
+    }
+
+    /// Several servers for resilience: each query tries them in order and uses
+    /// the first that answers.
+    pub fn with_servers(servers: &[String]) -> Self {
         NtpClient {
-            server: server.to_string(),
+            servers: servers.to_vec(),
+            bind: None,
+            answered: AtomicUsize::new(NO_ANSWER),
         }
     }
 
-    /// Fetches the current time from the NTP server.
-    /// Returns the offset required to apply to the local system time (Local + Offset = True Time).
-    /// Positive offset means local clock is behind (needs to step forward).
-    pub fn get_offset(&self) -> Result<(Duration, i8)> {
-        let client = SntpClient::new();
-        let result = client.synchronize(&self.server)?;
+    /// Send queries from `ip`, so they leave through that interface (multi-homed
+    /// hosts: reach the NTP server over the AV network, not the management one).
+    pub fn with_bind_address(mut self, ip: IpAddr) -> Self {
+        self.bind = Some(ip);
+        self
+    }
+
+    /// Single query including the round-trip delay, from the first server that answers.
+    pub fn query(&self) -> Result<NtpSample> {
+        let (index, sample) = first_answer(&self.servers, |server| self.query_server(server))?;
+        let previous = self.answered.swap(index, Ordering::Relaxed);
+        if previous != index && (previous != NO_ANSWER || index != 0) {
+            info!("[NTP] Using server {}", self.servers[index]);
+        } else {
+            debug!("[NTP] Answered by {}", self.servers[index]);
+        }
+        Ok(sample)
+    }
+
+    fn query_server(&self, server: &str) -> Result<NtpSample> {
+        let mut client = SntpClient::new();
+        if let Some(ip) = self.bind {
+            client.set_bind_address(SocketAddr::new(ip, 0));
+        }
+        let result = client.synchronize(server)?;
 
         let offset = result.clock_offset();
         let offset_secs = offset.as_secs_f64();
@@ -30,8 +82,89 @@ impl NtpClient {
         let secs = abs_secs.trunc() as u64;
         let nanos = (abs_secs.fract() * 1_000_000_000.0) as u32;
 
-        Ok((Duration::new(secs, nanos), sign))
+        let round_trip = Duration::from_secs_f64(result.round_trip_delay().as_secs_f64().max(0.0));
+
+        Ok(NtpSample {
+            offset: Duration::new(secs, nanos),
+            sign,
+            round_trip,
+        })
+    }
+
+    /// Fetches the current time from the NTP server.
+    /// Returns the offset required to apply to the local system time (Local + Offset = True Time).
+    /// Positive offset means local clock is behind (needs to step forward).
+    pub fn get_offset(&self) -> Result<(Duration, i8)> {
+        let sample = self.query()?;
+        Ok((sample.offset, sample.sign))
+    }
+
+    /// Best-of-N: query `count` times and keep the sample with the lowest round-trip
+    /// delay. Fails only if every query fails. `count <= 1` is a single query.
+    pub fn get_offset_burst(&self, count: usize) -> Result<(Duration, i8)> {
+        let mut samples = Vec::with_capacity(count);
+        let mut last_err = None;
+        for i in 0..count.max(1) {
+            if i > 0 {
+                thread::sleep(BURST_SPACING);
+            }
+            match self.query() {
+                Ok(sample) => {
+                    debug!(
+                        "[NTP] Burst {}/{}: offset {}{:?} rtt {:?}",
+                        i + 1,
+                        count,
+                        if sample.sign > 0 { "+" } else { "-" },
+                        sample.offset,
+                        sample.round_trip
+                    );
+                    samples.push(sample);
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        let best = best_sample(&samples)
+            .ok_or_else(|| last_err.unwrap_or_else(|| anyhow!("no NTP samples")))?;
+        Ok((best.offset, best.sign))
+    }
+}
+
+/// Servers of a comma-separated list ("10.77.8.2, 10.77.8.3").
+pub fn server_list(spec: &str) -> Vec<String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Index and result of the first server `query` succeeds for. If every server
+/// fails, the error names each one with its failure.
+fn first_answer<T>(
+    servers: &[String],
+    mut query: impl FnMut(&str) -> Result<T>,
+) -> Result<(usize, T)> {
+    let mut failures = Vec::with_capacity(servers.len());
+    for (index, server) in servers.iter().enumerate() {
+        match query(server) {
+            Ok(answer) => return Ok((index, answer)),
+            Err(e) => {
+                debug!("[NTP] {} failed: {}", server, e);
+                failures.push(format!("{}: {}", server, e));
+            }
+        }
     }
+    match failures.len() {
+        0 => Err(anyhow!("no NTP server configured")),
+        1 => Err(anyhow!("{}", failures[0])),
+        _ => Err(anyhow!("all NTP servers failed ({})", failures.join("; "))),
+    }
+}
+
+/// Sample with the lowest round-trip delay (classic NTP best-of-N).
+pub fn best_sample(samples: &[NtpSample]) -> Option<NtpSample> {
+    samples.iter().min_by_key(|s| s.round_trip).copied()
 }
 
 // ============================================================================
@@ -120,9 +253,71 @@ mod tests {
         assert_eq!(duration.subsec_micros(), 500);
     }
 
+    #[test]
+    fn test_best_sample_picks_lowest_round_trip() {
+        use super::{best_sample, NtpSample};
+        let sample = |offset_us, rtt_us| NtpSample {
+            offset: Duration::from_micros(offset_us),
+            sign: 1,
+            round_trip: Duration::from_micros(rtt_us),
+        };
+        // The queued exchange (40ms RTT) carries up to 20ms asymmetry error
+        let samples = [
+            sample(21_000, 40_000),
+            sample(1_200, 900),
+            sample(3_000, 5_000),
+        ];
+        assert_eq!(best_sample(&samples), Some(samples[1]));
+        assert_eq!(best_sample(&[]), None);
+    }
+
     #[test]
     fn test_ntp_client_new() {
         let client = super::NtpClient::new("pool.ntp.org");
-        assert_eq!(client.server, "pool.ntp.org");
+        assert_eq!(client.servers, vec!["pool.ntp.org".to_string()]);
+    }
+
+    #[test]
+    fn test_server_list_parsing() {
+        assert_eq!(
+            super::server_list("10.77.8.2, 10.77.8.3,,time.google.com "),
+            vec!["10.77.8.2", "10.77.8.3", "time.google.com"]
+        );
+        assert_eq!(super::server_list("10.77.8.2"), vec!["10.77.8.2"]);
+    }
+
+    #[test]
+    fn test_failover_to_second_server() {
+        let servers = super::server_list("down.lan, up.lan, never.lan");
+        let mut asked = Vec::new();
+        let answer = super::first_answer(&servers, |server| {
+            asked.push(server.to_string());
+            match server {
+                "down.lan" => Err(anyhow::anyhow!("timed out")),
+                _ => Ok(42),
+            }
+        })
+        .unwrap();
+        assert_eq!(answer, (1, 42));
+        assert_eq!(
+            asked,
+            vec!["down.lan", "up.lan"],
+            "Stops at the first answer"
+        );
+    }
+
+    #[test]
+    fn test_all_servers_failing_reports_each() {
+        let servers = super::server_list("a.lan, b.lan");
+        let err = super::first_answer::<()>(&servers, |server| {
+            Err(anyhow::anyhow!("{} unreachable", server))
+        })
+        .unwrap_err()
+        .to_string();
+        assert_eq!(
+            err,
+            "all NTP servers failed (a.lan: a.lan unreachable; b.lan: b.lan unreachable)"
+        );
+        assert!(super::first_answer::<()>(&[], |_| Ok(())).is_err());
     }
 }
@@ -0,0 +1,170 @@
+//! Independent NTP cross-check (monitoring only).
+//!
+//! Queries a second, authoritative NTP server (e.g. a GPS NTP appliance) that is
+//! separate from the sync source used for stepping. The result is NEVER used to
+//! step or slew the clock - it only detects a grandmaster/NTP chain serving wrong
+//! wall time while PTP lock looks perfect.
+
+use crate::status::SyncStatus;
+use crate::traits::NtpSource;
+use log::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default interval between check queries
+pub const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Default disagreement threshold before alerting (10ms)
+pub const DEFAULT_CHECK_THRESHOLD_US: i64 = 10_000;
+
+/// Periodic monitor against an independent NTP server.
+pub struct NtpCheckMonitor<S: NtpSource> {
+    source: S,
+    server: String,
+    threshold_us: i64,
+    alarm: bool,
+}
+
+impl<S: NtpSource> NtpCheckMonitor<S> {
+    pub fn new(source: S, server: &str, threshold_us: i64) -> Self {
+        Self {
+            source,
+            server: server.to_string(),
+            threshold_us,
+            alarm: false,
+        }
+    }
+
+    /// Query the check server once and publish the result to `status`.
+    /// Returns the measured offset in microseconds, or None if the query failed.
+    pub fn check_once(&mut self, status: &Arc<RwLock<SyncStatus>>) -> Option<i64> {
+        match self.source.get_offset() {
+            Ok((offset, sign)) => {
+                let offset_us = if sign > 0 {
+                    offset.as_micros() as i64
+                } else {
+                    -(offset.as_micros() as i64)
+                };
+                let alarm = offset_us.abs() > self.threshold_us;
+
+                if alarm {
+                    warn!(
+                        "[NTP-Check] {} disagrees: {:+}us (threshold {}us)",
+                        self.server, offset_us, self.threshold_us
+                    );
+                } else if self.alarm {
+                    info!(
+                        "[NTP-Check] {} agrees again: {:+}us",
+                        self.server, offset_us
+                    );
+                } else {
+                    info!("[NTP-Check] {} offset:{:+}us", self.server, offset_us);
+                }
+                self.alarm = alarm;
+
+                if let Ok(mut s) = status.write() {
+                    s.check_ntp_offset_us = Some(offset_us);
+                    s.check_ntp_alarm = alarm;
+                }
+                Some(offset_us)
+            }
+            Err(e) => {
+                warn!("[NTP-Check] {} query failed: {}", self.server, e);
+                // No measurement: an alarm without an offset would be stale
+                self.alarm = false;
+                if let Ok(mut s) = status.write() {
+                    s.check_ntp_offset_us = None;
+                    s.check_ntp_alarm = false;
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<S: NtpSource + Send + 'static> NtpCheckMonitor<S> {
+    /// Run the monitor on a background thread until `running` is cleared.
+    pub fn spawn(mut self, status: Arc<RwLock<SyncStatus>>, running: Arc<AtomicBool>) {
+        thread::spawn(move || {
+            info!(
+                "[NTP-Check] Monitoring against {} every {}s (never steps)",
+                self.server, CHECK_INTERVAL_SECS
+            );
+            let mut last_check: Option<Instant> = None;
+            while running.load(Ordering::SeqCst) {
+                let due = last_check
+                    .map(|t| t.elapsed() >= Duration::from_secs(CHECK_INTERVAL_SECS))
+                    .unwrap_or(true);
+                if due {
+                    self.check_once(&status);
+                    last_check = Some(Instant::now());
+                }
+                thread::sleep(Duration::from_millis(500));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::MockNtpSource;
+
+    #[test]
+    fn test_check_within_threshold_no_alarm() {
+        let mut ntp = MockNtpSource::new();
+        ntp.expect_get_offset()
+            .returning(|| Ok((Duration::from_micros(250), -1)));
+        let status = Arc::new(RwLock::new(SyncStatus::default()));
+
+        let mut monitor = NtpCheckMonitor::new(ntp, "gps.local", 1_000);
+        assert_eq!(monitor.check_once(&status), Some(-250));
+
+        let s = status.read().unwrap();
+        assert_eq!(s.check_ntp_offset_us, Some(-250));
+        assert!(!s.check_ntp_alarm, "Offset within threshold must not alarm");
+    }
+
+    #[test]
+    fn test_check_beyond_threshold_raises_alarm() {
+        let mut ntp = MockNtpSource::new();
+        ntp.expect_get_offset()
+            .returning(|| Ok((Duration::from_millis(50), 1)));
+        let status = Arc::new(RwLock::new(SyncStatus::default()));
+
+        let mut monitor = NtpCheckMonitor::new(ntp, "gps.local", 1_000);
+        monitor.check_once(&status);
+
+        let s = status.read().unwrap();
+        assert_eq!(s.check_ntp_offset_us, Some(50_000));
+        assert!(s.check_ntp_alarm, "50ms disagreement should alarm");
+    }
+
+    #[test]
+    fn test_check_failure_clears_offset_and_alarm() {
+        let mut ntp = MockNtpSource::new();
+        let mut results = vec![
+            Ok((Duration::from_millis(50), 1)),
+            Err(anyhow::anyhow!("timeout")),
+        ]
+        .into_iter();
+        ntp.expect_get_offset()
+            .times(2)
+            .returning(move || results.next().unwrap());
+        let status = Arc::new(RwLock::new(SyncStatus::default()));
+
+        let mut monitor = NtpCheckMonitor::new(ntp, "gps.local", 1_000);
+        monitor.check_once(&status);
+        assert!(status.read().unwrap().check_ntp_alarm);
+
+        assert_eq!(monitor.check_once(&status), None);
+        let s = status.read().unwrap();
+        assert_eq!(s.check_ntp_offset_us, None);
+        assert!(
+            !s.check_ntp_alarm,
+            "Failed check must not keep a stale alarm"
+        );
+    }
+}
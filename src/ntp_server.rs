@@ -15,7 +15,7 @@
 
 use anyhow::{anyhow, Result};
 use log::{debug, error, info, warn};
-use std::net::{SocketAddr, UdpSocket};
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -67,7 +67,13 @@ impl NtpServer {
     /// * `port` - UDP port to listen on (usually 123, requires elevated privileges)
     /// * `stratum` - Stratum level to report (typically 2-4 for LAN servers)
     pub fn new(port: u16, stratum: u8) -> Result<Self> {
-        let bind_addr = format!("0.0.0.0:{}", port);
+        Self::bind(Ipv4Addr::UNSPECIFIED, port, stratum)
+    }
+
+    /// Create a new NTP server listening on one local address only
+    /// (dual-homed hosts: serve the LAN, not the PTP network).
+    pub fn bind(ip: Ipv4Addr, port: u16, stratum: u8) -> Result<Self> {
+        let bind_addr = format!("{}:{}", ip, port);
         let socket = UdpSocket::bind(&bind_addr).map_err(|e| {
             anyhow!(
                 "Failed to bind NTP server to {}: {} (hint: port 123 requires root/admin)",
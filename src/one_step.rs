@@ -0,0 +1,106 @@
+//! One-step master detection.
+//!
+//! A two-step master (Dante) sends the precise T1 in a FollowUp after each Sync.
+//! A one-step master puts it into the Sync's origin timestamp and sends no
+//! FollowUp at all, so waiting for one never pairs anything. A master is taken
+//! as one-step once it has sent a number of Syncs carrying an origin timestamp
+//! and not a single FollowUp; any FollowUp from it makes it two-step again.
+
+/// Syncs with an origin timestamp (and no FollowUp) before a master counts as one-step.
+const DETECT_AFTER_SYNCS: u32 = 8;
+
+#[derive(Debug, Default)]
+pub struct OneStepDetector {
+    /// Master the state below belongs to (the current sync source)
+    source: Option<[u8; 6]>,
+    syncs: u32,
+    followup_seen: bool,
+    one_step: bool,
+}
+
+impl OneStepDetector {
+    /// A Sync from `source`; `has_origin` if its origin timestamp is non-zero.
+    /// Returns true when this Sync gets `source` detected as one-step.
+    pub fn observe_sync(&mut self, source: [u8; 6], has_origin: bool) -> bool {
+        if self.source != Some(source) {
+            *self = Self {
+                source: Some(source),
+                ..Default::default()
+            };
+        }
+        if self.followup_seen || self.one_step || !has_origin {
+            return false;
+        }
+        self.syncs += 1;
+        self.one_step = self.syncs >= DETECT_AFTER_SYNCS;
+        self.one_step
+    }
+
+    /// A FollowUp from `source`. Returns true if `source` was taken for one-step
+    /// until now.
+    pub fn observe_followup(&mut self, source: [u8; 6]) -> bool {
+        if self.source != Some(source) {
+            return false;
+        }
+        self.followup_seen = true;
+        std::mem::take(&mut self.one_step)
+    }
+
+    /// Whether `source` was detected as one-step.
+    pub fn is_one_step(&self, source: [u8; 6]) -> bool {
+        self.one_step && self.source == Some(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASTER: [u8; 6] = [0x00, 0x1D, 0xC1, 0x00, 0x00, 0x01];
+    const OTHER: [u8; 6] = [0x00, 0x1D, 0xC1, 0x00, 0x00, 0x02];
+
+    #[test]
+    fn test_sync_only_master_detected_as_one_step() {
+        let mut detector = OneStepDetector::default();
+        for _ in 1..DETECT_AFTER_SYNCS {
+            assert!(!detector.observe_sync(MASTER, true));
+        }
+        // Syncs without an origin timestamp are no evidence either way
+        assert!(!detector.observe_sync(MASTER, false));
+        assert!(!detector.is_one_step(MASTER));
+
+        assert!(detector.observe_sync(MASTER, true), "detected once");
+        assert!(!detector.observe_sync(MASTER, true));
+        assert!(detector.is_one_step(MASTER));
+        assert!(!detector.is_one_step(OTHER));
+    }
+
+    #[test]
+    fn test_two_step_master_never_detected() {
+        let mut detector = OneStepDetector::default();
+        detector.observe_sync(MASTER, true);
+        assert!(!detector.observe_followup(MASTER));
+        // FollowUps lost for a while: still two-step
+        for _ in 0..100 {
+            assert!(!detector.observe_sync(MASTER, true));
+        }
+        assert!(!detector.is_one_step(MASTER));
+    }
+
+    #[test]
+    fn test_followup_or_new_master_restarts_detection() {
+        let mut detector = OneStepDetector::default();
+        for _ in 0..DETECT_AFTER_SYNCS {
+            detector.observe_sync(MASTER, true);
+        }
+        assert!(!detector.observe_followup(OTHER), "not the tracked master");
+        assert!(detector.observe_followup(MASTER));
+        assert!(!detector.is_one_step(MASTER));
+
+        // A new master starts over
+        for _ in 0..DETECT_AFTER_SYNCS {
+            detector.observe_sync(OTHER, true);
+        }
+        assert!(detector.is_one_step(OTHER));
+    }
+}
@@ -0,0 +1,137 @@
+//! Phase offset outlier rejection.
+//!
+//! A Sync whose T2 was taken late (the receiving thread was preempted) yields a
+//! single phase offset far off the stream around it. The sample window is only a
+//! few offsets wide, so its median does not reliably hide it. Each offset is compared
+//! with the median of the recent ones and dropped if it deviates more than k times
+//! their median absolute deviation (MAD), before it reaches the servo.
+
+use std::collections::VecDeque;
+
+/// Recent accepted offsets the median and MAD are taken from.
+const WINDOW: usize = 32;
+/// Offsets needed before rejection starts.
+const WARMUP_SAMPLES: usize = 8;
+/// MAD floor (ns): on a very quiet stream a few µs of ordinary jitter must not
+/// count as outliers.
+const MIN_MAD_NS: f64 = 2_000.0;
+/// Consecutive rejections after which the offset is taken to have really moved
+/// (phase step, master change) and the window restarts from it.
+const SHIFT_AFTER_REJECTS: u32 = 8;
+
+#[derive(Debug)]
+pub struct PhaseOutlierFilter {
+    /// Rejection threshold in MADs
+    k: f64,
+    window: VecDeque<i64>,
+    consecutive_rejects: u32,
+    rejected: u64,
+}
+
+impl PhaseOutlierFilter {
+    pub fn new(k: f64) -> Self {
+        Self {
+            k,
+            window: VecDeque::with_capacity(WINDOW),
+            consecutive_rejects: 0,
+            rejected: 0,
+        }
+    }
+
+    /// Check the next phase offset (ns). Returns false if it is an outlier that
+    /// should not reach the servo.
+    pub fn accept(&mut self, offset_ns: i64) -> bool {
+        if self.window.len() >= WARMUP_SAMPLES {
+            let (median, mad) = median_and_mad(&self.window);
+            let deviation = (offset_ns - median).unsigned_abs() as f64;
+            if deviation > self.k * mad.max(MIN_MAD_NS) {
+                self.consecutive_rejects += 1;
+                if self.consecutive_rejects < SHIFT_AFTER_REJECTS {
+                    self.rejected += 1;
+                    return false;
+                }
+                // Not a spike: the offset moved. Start over from here.
+                self.window.clear();
+            }
+        }
+
+        self.consecutive_rejects = 0;
+        if self.window.len() >= WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back(offset_ns);
+        true
+    }
+
+    /// Forget the recent offsets (clock stepped, sync source changed).
+    pub fn reset(&mut self) {
+        self.window.clear();
+        self.consecutive_rejects = 0;
+    }
+
+    /// Total number of rejected offsets.
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+}
+
+/// Median and median absolute deviation of `values` (not empty).
+fn median_and_mad(values: &VecDeque<i64>) -> (i64, f64) {
+    let mut sorted: Vec<i64> = values.iter().copied().collect();
+    sorted.sort_unstable();
+    let median = sorted[sorted.len() / 2];
+    let mut deviations: Vec<u64> = sorted.iter().map(|v| (v - median).unsigned_abs()).collect();
+    deviations.sort_unstable();
+    (median, deviations[deviations.len() / 2] as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic ±50µs jitter around `center_ns`
+    fn jittery(i: usize, center_ns: i64) -> i64 {
+        center_ns + ((i as i64 * 7_919) % 101 - 50) * 1_000
+    }
+
+    #[test]
+    fn test_rejects_5ms_spike_in_50us_stream() {
+        let mut filter = PhaseOutlierFilter::new(5.0);
+        for i in 0..100 {
+            let offset = if i == 40 {
+                jittery(i, 5_000_000) // T2 captured 5ms late
+            } else {
+                jittery(i, 0)
+            };
+            assert_eq!(filter.accept(offset), i != 40, "sample {}", i);
+        }
+        assert_eq!(filter.rejected(), 1);
+    }
+
+    #[test]
+    fn test_lasting_shift_is_accepted() {
+        let mut filter = PhaseOutlierFilter::new(5.0);
+        for i in 0..20 {
+            assert!(filter.accept(jittery(i, 0)));
+        }
+        let accepted: Vec<bool> = (20..40)
+            .map(|i| filter.accept(jittery(i, 3_000_000)))
+            .collect();
+        let first = SHIFT_AFTER_REJECTS as usize - 1;
+        assert!(accepted[..first].iter().all(|a| !a));
+        assert!(accepted[first..].iter().all(|a| *a), "{:?}", accepted);
+        assert_eq!(filter.rejected(), first as u64);
+    }
+
+    #[test]
+    fn test_warmup_and_reset() {
+        let mut filter = PhaseOutlierFilter::new(5.0);
+        // Nothing to compare with yet: everything passes
+        for i in 0..WARMUP_SAMPLES {
+            assert!(filter.accept(jittery(i, i as i64 * 1_000_000)));
+        }
+        filter.reset();
+        assert!(filter.accept(50_000_000));
+        assert_eq!(filter.rejected(), 0);
+    }
+}
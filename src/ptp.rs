@@ -1,10 +1,35 @@
 use anyhow::{anyhow, Result};
-use byteorder::{BigEndian, ReadBytesExt};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use std::io::Cursor;
 
 pub const PTP_EVENT_PORT: u16 = 319;
 pub const PTP_GENERAL_PORT: u16 = 320;
 
+/// PTPv1 header flag: a FollowUp carries the precise origin timestamp (two-step)
+pub const PTP_ASSIST: u16 = 0x0008;
+
+/// Leap second announced for the end of the current UTC day: leap61 inserts a
+/// second, leap59 deletes one. Same bits in the PTPv1 flags and PTPv2 flagField.
+pub const PTP_LEAP_61: u16 = 0x0001;
+pub const PTP_LEAP_59: u16 = 0x0002;
+
+/// PTP multicast group (default domain)
+pub const PTP_MULTICAST_ADDR: [u8; 4] = [224, 0, 1, 129];
+
+/// Full PTPv1 message sizes on the wire
+pub const SYNC_MESSAGE_SIZE: usize = 124;
+pub const FOLLOW_UP_MESSAGE_SIZE: usize = 52;
+
+// PTPv1 messageType field: event messages go to port 319, general to 320
+const MESSAGE_TYPE_EVENT: u8 = 1;
+const MESSAGE_TYPE_GENERAL: u8 = 2;
+/// PTPv1 communicationTechnology PTP_ETHER: UUIDs are Ethernet MAC addresses (Dante)
+pub const COMM_TECH_ETHERNET: u8 = 1;
+/// PTPv1 subdomain of Dante (and of PTPv1 masters left at their default)
+pub const DEFAULT_SUBDOMAIN: &str = "_DFLT";
+/// subdomain field size: a NUL-padded name
+const SUBDOMAIN_SIZE: usize = 16;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum PtpV1Control {
     Sync = 0,
@@ -28,14 +53,35 @@ impl From<u8> for PtpV1Control {
     }
 }
 
+impl PtpV1Control {
+    /// Body bytes a message of this type needs after the header. PTPv1 has no
+    /// messageLength field: the length is implied by the control type.
+    ///
+    /// A Sync from a two-step master is usable without its body (T1 comes in the
+    /// FollowUp), so its body is checked only where it is parsed.
+    pub fn min_body_size(self) -> usize {
+        match self {
+            PtpV1Control::FollowUp => PtpV1FollowUpBody::SIZE,
+            PtpV1Control::DelayResp => PtpV1DelayRespBody::SIZE,
+            _ => 0,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct PtpV1Header {
     pub version_ptp: u8,
-    pub message_length: u16,
+    /// versionNetwork (bytes 2-3; a PTPv1 header carries no message length)
+    pub version_network: u16,
+    /// subdomain name, NUL-padded
+    pub subdomain: [u8; SUBDOMAIN_SIZE],
     pub message_type: PtpV1Control,
+    /// sourceCommunicationTechnology - what `source_uuid` identifies
+    pub source_communication_technology: u8,
     pub source_uuid: [u8; 6],
     pub sequence_id: u16,
     pub control: u8,
+    pub flags: u16,
 }
 
 impl PtpV1Header {
@@ -50,14 +96,16 @@ impl PtpV1Header {
         let v_r1 = rdr.read_u8()?;
         let version_ptp = (v_r1 >> 4) & 0x0F;
 
-        let _v_n_r2 = rdr.read_u8()?; // versionNetwork
-        let message_length = rdr.read_u16::<BigEndian>()?;
+        let _v_n_r2 = rdr.read_u8()?;
+        let version_network = rdr.read_u16::<BigEndian>()?;
 
-        // Skip subdomain (16 bytes)
-        rdr.set_position(rdr.position() + 16);
+        let mut subdomain = [0u8; SUBDOMAIN_SIZE];
+        for byte in &mut subdomain {
+            *byte = rdr.read_u8()?;
+        }
 
         let _msg_type_val = rdr.read_u8()?;
-        let _src_comm_tech = rdr.read_u8()?;
+        let source_communication_technology = rdr.read_u8()?;
 
         let mut source_uuid = [0u8; 6];
         for byte in &mut source_uuid {
@@ -67,21 +115,57 @@ impl PtpV1Header {
         let _source_port_id = rdr.read_u16::<BigEndian>()?;
         let sequence_id = rdr.read_u16::<BigEndian>()?;
         let control = rdr.read_u8()?;
+        let _reserved = rdr.read_u8()?;
+        let flags = rdr.read_u16::<BigEndian>()?;
 
         let message_type = PtpV1Control::from(control);
+        // Truncated messages must not reach the body parsers or the timestamp math
+        let required = Self::SIZE + message_type.min_body_size();
+        if data.len() < required {
+            return Err(anyhow!(
+                "Truncated PTPv1 {:?}: {} bytes, need {}",
+                message_type,
+                data.len(),
+                required
+            ));
+        }
 
         Ok(PtpV1Header {
             version_ptp,
-            message_length,
+            version_network,
+            subdomain,
             message_type,
+            source_communication_technology,
             source_uuid,
             sequence_id,
             control,
+            flags,
         })
     }
+
+    /// Sender is two-step: the Sync origin timestamp is only an estimate and a
+    /// FollowUp with the precise one follows.
+    pub fn is_two_step(&self) -> bool {
+        self.flags & PTP_ASSIST != 0
+    }
+
+    /// The subdomain name without its NUL padding.
+    pub fn subdomain_name(&self) -> String {
+        let len = self
+            .subdomain
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(SUBDOMAIN_SIZE);
+        String::from_utf8_lossy(&self.subdomain[..len]).into_owned()
+    }
+
+    /// Sent over Ethernet, so `source_uuid` is a MAC address.
+    pub fn is_ethernet(&self) -> bool {
+        self.source_communication_technology == COMM_TECH_ETHERNET
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct PtpTimestamp {
     pub seconds: u32,
     pub nanoseconds: u32,
@@ -97,21 +181,184 @@ impl PtpTimestamp {
             .saturating_mul(1_000_000_000)
             .saturating_add(self.nanoseconds as i64)
     }
+
+    /// Inverse of `to_nanos` (negative values clamp to zero).
+    pub fn from_nanos(ns: i64) -> Self {
+        let ns = ns.max(0);
+        PtpTimestamp {
+            seconds: (ns / 1_000_000_000) as u32,
+            nanoseconds: (ns % 1_000_000_000) as u32,
+        }
+    }
+}
+
+// ============================================================================
+// ENCODING (test harness transmit path - see the `ptpgen` binary)
+// ============================================================================
+
+/// Write a PTPv1 common header (36 bytes) into the start of `buf`.
+fn write_header(
+    buf: &mut [u8],
+    message_type: u8,
+    control: PtpV1Control,
+    source_uuid: [u8; 6],
+    sequence_id: u16,
+    flags: u16,
+) {
+    let mut w = Cursor::new(buf);
+    // versionPTP = 1, versionNetwork = 1
+    let _ = w.write_u16::<BigEndian>(1);
+    let _ = w.write_u16::<BigEndian>(1);
+    write_subdomain(w.get_mut(), DEFAULT_SUBDOMAIN);
+    w.set_position(20);
+    let _ = w.write_u8(message_type);
+    let _ = w.write_u8(COMM_TECH_ETHERNET);
+    let pos = w.position() as usize;
+    w.get_mut()[pos..pos + 6].copy_from_slice(&source_uuid);
+    w.set_position(28);
+    let _ = w.write_u16::<BigEndian>(1); // sourcePortId
+    let _ = w.write_u16::<BigEndian>(sequence_id);
+    let _ = w.write_u8(control as u8);
+    let _ = w.write_u8(0);
+    let _ = w.write_u16::<BigEndian>(flags);
 }
 
+/// Write `subdomain` (truncated to 16 bytes, NUL-padded) into a PTPv1 header.
+fn write_subdomain(header: &mut [u8], subdomain: &str) {
+    let field = &mut header[4..4 + SUBDOMAIN_SIZE];
+    let name = &subdomain.as_bytes()[..subdomain.len().min(SUBDOMAIN_SIZE)];
+    field.fill(0);
+    field[..name.len()].copy_from_slice(name);
+}
+
+/// Encode a Sync message. The sender acts as its own grandmaster. With
+/// `two_step`, the PTP_ASSIST flag announces a FollowUp with the precise T1.
+pub fn encode_sync(
+    source_uuid: [u8; 6],
+    sequence_id: u16,
+    origin: PtpTimestamp,
+    two_step: bool,
+) -> Vec<u8> {
+    let mut buf = vec![0u8; SYNC_MESSAGE_SIZE];
+    let flags = if two_step { PTP_ASSIST } else { 0 };
+    write_header(
+        &mut buf,
+        MESSAGE_TYPE_EVENT,
+        PtpV1Control::Sync,
+        source_uuid,
+        sequence_id,
+        flags,
+    );
+
+    let mut w = Cursor::new(&mut buf[PtpV1Header::SIZE..]);
+    w.set_position(4); // reserved
+    let _ = w.write_u32::<BigEndian>(origin.seconds);
+    let _ = w.write_u32::<BigEndian>(origin.nanoseconds);
+    let _ = w.write_u16::<BigEndian>(0); // epochNumber
+    let _ = w.write_i16::<BigEndian>(0); // currentUtcOffset
+    let _ = w.write_u8(0); // reserved
+    let _ = w.write_u8(COMM_TECH_ETHERNET); // grandmasterCommunicationTechnology
+    let pos = w.position() as usize;
+    w.get_mut()[pos..pos + 6].copy_from_slice(&source_uuid);
+    w.set_position(pos as u64 + 6);
+    let _ = w.write_u16::<BigEndian>(1); // grandmasterPortId
+    let _ = w.write_u16::<BigEndian>(sequence_id); // grandmasterSequenceId
+    buf
+}
+
+/// Encode the FollowUp carrying the precise T1 of Sync `associated_sequence_id`.
+pub fn encode_follow_up(
+    source_uuid: [u8; 6],
+    sequence_id: u16,
+    associated_sequence_id: u16,
+    precise_origin: PtpTimestamp,
+) -> Vec<u8> {
+    let mut buf = vec![0u8; FOLLOW_UP_MESSAGE_SIZE];
+    write_header(
+        &mut buf,
+        MESSAGE_TYPE_GENERAL,
+        PtpV1Control::FollowUp,
+        source_uuid,
+        sequence_id,
+        0,
+    );
+
+    let mut w = Cursor::new(&mut buf[PtpV1Header::SIZE..]);
+    w.set_position(6); // padding
+    let _ = w.write_u16::<BigEndian>(associated_sequence_id);
+    let _ = w.write_u32::<BigEndian>(precise_origin.seconds);
+    let _ = w.write_u32::<BigEndian>(precise_origin.nanoseconds);
+    buf
+}
+
+/// Encode a Delay_Req (same layout as Sync) in `subdomain`. `origin` is our
+/// estimate of its egress time; the master answers with its receive time in a
+/// Delay_Resp.
+pub fn encode_delay_req(
+    source_uuid: [u8; 6],
+    sequence_id: u16,
+    origin: PtpTimestamp,
+    subdomain: &str,
+) -> Vec<u8> {
+    let mut buf = encode_sync(source_uuid, sequence_id, origin, false);
+    buf[32] = PtpV1Control::DelayReq as u8;
+    write_subdomain(&mut buf, subdomain);
+    buf
+}
+
+/// Encode the Delay_Resp answering `requester`'s Delay_Req `requesting_sequence_id`,
+/// received at `receipt`.
+pub fn encode_delay_resp(
+    source_uuid: [u8; 6],
+    sequence_id: u16,
+    receipt: PtpTimestamp,
+    requester: ([u8; 6], u16),
+    requesting_sequence_id: u16,
+) -> Vec<u8> {
+    let mut buf = vec![0u8; PtpV1Header::SIZE + PtpV1DelayRespBody::SIZE];
+    write_header(
+        &mut buf,
+        MESSAGE_TYPE_GENERAL,
+        PtpV1Control::DelayResp,
+        source_uuid,
+        sequence_id,
+        0,
+    );
+
+    let mut w = Cursor::new(&mut buf[PtpV1Header::SIZE..]);
+    w.set_position(4); // reserved
+    let _ = w.write_u32::<BigEndian>(receipt.seconds);
+    let _ = w.write_u32::<BigEndian>(receipt.nanoseconds);
+    let _ = w.write_u8(0); // padding
+    let _ = w.write_u8(COMM_TECH_ETHERNET); // requestingSourceCommunicationTechnology
+    let pos = w.position() as usize;
+    w.get_mut()[pos..pos + 6].copy_from_slice(&requester.0);
+    w.set_position(pos as u64 + 6);
+    let _ = w.write_u16::<BigEndian>(requester.1);
+    let _ = w.write_u16::<BigEndian>(requesting_sequence_id);
+    buf
+}
+
+/// Sync body, from the end of the 36-byte header: reserved (4), originTimestamp
+/// (8, absolute byte 40), epochNumber (2), currentUTCOffset (2, byte 50), reserved
+/// (1), grandmasterCommunicationTechnology (1, byte 53), grandmasterClockUuid (6,
+/// byte 54), ...
 #[derive(Debug)]
 pub struct PtpV1SyncMessageBody {
-    // originTimestamp (8)
-    // epochNumber (2)
-    // currentUtcOffset (2)
-    // grandmasterCommTech (1)
+    /// Precise T1 from one-step masters (estimate only if two-step)
+    pub origin_timestamp: PtpTimestamp,
+    /// TAI - UTC in seconds as the master reports it (37 since 2017; 0 from
+    /// masters that distribute UTC or, like Dante, uptime)
+    pub current_utc_offset: i16,
+    /// grandmasterCommunicationTechnology - what `grandmaster_clock_uuid` identifies
+    pub grandmaster_communication_technology: u8,
     pub grandmaster_clock_uuid: [u8; 6],
     // ... others ignored
 }
 
 impl PtpV1SyncMessageBody {
-    // We only need up to GM UUID (offset 13 + 6 = 19 bytes)
-    pub const MIN_SIZE: usize = 19;
+    // We only need up to GM UUID (offset 18 + 6 = 24 bytes)
+    pub const MIN_SIZE: usize = 24;
 
     pub fn parse(data: &[u8]) -> Result<Self> {
         if data.len() < Self::MIN_SIZE {
@@ -119,8 +366,17 @@ impl PtpV1SyncMessageBody {
         }
         let mut rdr = Cursor::new(data);
 
-        // Skip originTimestamp (8), epoch (2), utcOffset (2), commTech (1) = 13 bytes
-        rdr.set_position(13);
+        // Skip reserved (4)
+        rdr.set_position(4);
+        let seconds = rdr.read_u32::<BigEndian>()?;
+        let nanoseconds = rdr.read_u32::<BigEndian>()?;
+
+        // Skip epochNumber (2)
+        rdr.set_position(14);
+        let current_utc_offset = rdr.read_i16::<BigEndian>()?;
+        // Skip reserved (1)
+        rdr.set_position(17);
+        let grandmaster_communication_technology = rdr.read_u8()?;
 
         let mut gm_uuid = [0u8; 6];
         for byte in &mut gm_uuid {
@@ -128,11 +384,45 @@ impl PtpV1SyncMessageBody {
         }
 
         Ok(PtpV1SyncMessageBody {
+            origin_timestamp: PtpTimestamp {
+                seconds,
+                nanoseconds,
+            },
+            current_utc_offset,
+            grandmaster_communication_technology,
             grandmaster_clock_uuid: gm_uuid,
         })
     }
 }
 
+/// Delay_Req body: laid out like Sync; only the origin timestamp (the sender's
+/// estimate of T3) is of interest.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PtpV1DelayReqBody {
+    pub origin_timestamp: PtpTimestamp,
+}
+
+impl PtpV1DelayReqBody {
+    pub const MIN_SIZE: usize = 12;
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < Self::MIN_SIZE {
+            return Err(anyhow!("Packet too short for DelayReq body"));
+        }
+        let mut rdr = Cursor::new(data);
+        // Skip reserved (4)
+        rdr.set_position(4);
+        let seconds = rdr.read_u32::<BigEndian>()?;
+        let nanoseconds = rdr.read_u32::<BigEndian>()?;
+        Ok(PtpV1DelayReqBody {
+            origin_timestamp: PtpTimestamp {
+                seconds,
+                nanoseconds,
+            },
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct PtpV1FollowUpBody {
     pub associated_sequence_id: u16,
@@ -165,6 +455,341 @@ impl PtpV1FollowUpBody {
     }
 }
 
+/// Delay_Resp body: the master's receive time (T4) of a Delay_Req, and the identity
+/// of the slave that sent it. Sent to the multicast group, so every slave sees
+/// every other slave's responses.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PtpV1DelayRespBody {
+    pub delay_receipt_timestamp: PtpTimestamp,
+    pub requesting_source_uuid: [u8; 6],
+    pub requesting_source_port_id: u16,
+    pub requesting_source_sequence_id: u16,
+}
+
+impl PtpV1DelayRespBody {
+    pub const SIZE: usize = 24;
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < Self::SIZE {
+            return Err(anyhow!("Packet too short for DelayResp body"));
+        }
+        let mut rdr = Cursor::new(data);
+
+        // Skip reserved (4)
+        rdr.set_position(4);
+        let seconds = rdr.read_u32::<BigEndian>()?;
+        let nanoseconds = rdr.read_u32::<BigEndian>()?;
+
+        // Skip padding (1), requestingSourceCommunicationTechnology (1)
+        rdr.set_position(14);
+
+        let mut requesting_source_uuid = [0u8; 6];
+        for byte in &mut requesting_source_uuid {
+            *byte = rdr.read_u8()?;
+        }
+        let requesting_source_port_id = rdr.read_u16::<BigEndian>()?;
+        let requesting_source_sequence_id = rdr.read_u16::<BigEndian>()?;
+
+        Ok(PtpV1DelayRespBody {
+            delay_receipt_timestamp: PtpTimestamp {
+                seconds,
+                nanoseconds,
+            },
+            requesting_source_uuid,
+            requesting_source_port_id,
+            requesting_source_sequence_id,
+        })
+    }
+}
+
+// ============================================================================
+// PTPv2 (IEEE 1588-2008) - AES67/SMPTE masters on the same multicast group
+// ============================================================================
+
+/// PTPv2 flagField twoStepFlag: a Follow_Up carries the precise origin timestamp
+pub const PTP_V2_TWO_STEP: u16 = 0x0200;
+
+/// True if `data` is a PTPv2 message (versionPTP in the low nibble of byte 1).
+/// PTPv1 has versionPTP = 1 as a 16-bit field, so its byte 1 reads 1.
+pub fn is_ptp_v2(data: &[u8]) -> bool {
+    data.len() > 1 && data[1] & 0x0F == 2
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PtpV2MessageType {
+    Sync,
+    DelayReq,
+    FollowUp,
+    DelayResp,
+    Announce,
+    Other(u8),
+}
+
+impl From<u8> for PtpV2MessageType {
+    fn from(v: u8) -> Self {
+        match v {
+            0x0 => PtpV2MessageType::Sync,
+            0x1 => PtpV2MessageType::DelayReq,
+            0x8 => PtpV2MessageType::FollowUp,
+            0x9 => PtpV2MessageType::DelayResp,
+            0xB => PtpV2MessageType::Announce,
+            other => PtpV2MessageType::Other(other),
+        }
+    }
+}
+
+/// PTPv2 common header (34 bytes).
+#[derive(Debug, PartialEq, Eq)]
+pub struct PtpV2Header {
+    pub message_type: PtpV2MessageType,
+    pub version_ptp: u8,
+    pub message_length: u16,
+    pub domain_number: u8,
+    pub flags: u16,
+    /// Scaled nanoseconds (ns * 2^16), signed
+    pub correction_field: i64,
+    pub clock_identity: [u8; 8],
+    pub port_number: u16,
+    pub sequence_id: u16,
+    pub control: u8,
+    pub log_message_interval: i8,
+}
+
+impl PtpV2Header {
+    pub const SIZE: usize = 34;
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < Self::SIZE {
+            return Err(anyhow!("Packet too short for PTPv2 header"));
+        }
+        let mut rdr = Cursor::new(data);
+
+        let message_type = PtpV2MessageType::from(rdr.read_u8()? & 0x0F);
+        let version_ptp = rdr.read_u8()? & 0x0F;
+        if version_ptp != 2 {
+            return Err(anyhow!("Not a PTPv2 message (version {})", version_ptp));
+        }
+        let message_length = rdr.read_u16::<BigEndian>()?;
+        // Anyone on the LAN can send to the group: the claimed length must fit
+        if (message_length as usize) < Self::SIZE || message_length as usize > data.len() {
+            return Err(anyhow!(
+                "PTPv2 messageLength {} invalid for a {} byte packet",
+                message_length,
+                data.len()
+            ));
+        }
+        let domain_number = rdr.read_u8()?;
+        let _reserved = rdr.read_u8()?;
+        let flags = rdr.read_u16::<BigEndian>()?;
+        let correction_field = rdr.read_i64::<BigEndian>()?;
+
+        // Skip reserved (4 bytes)
+        rdr.set_position(20);
+        let mut clock_identity = [0u8; 8];
+        for byte in &mut clock_identity {
+            *byte = rdr.read_u8()?;
+        }
+        let port_number = rdr.read_u16::<BigEndian>()?;
+        let sequence_id = rdr.read_u16::<BigEndian>()?;
+        let control = rdr.read_u8()?;
+        let log_message_interval = rdr.read_i8()?;
+
+        Ok(PtpV2Header {
+            message_type,
+            version_ptp,
+            message_length,
+            domain_number,
+            flags,
+            correction_field,
+            clock_identity,
+            port_number,
+            sequence_id,
+            control,
+            log_message_interval,
+        })
+    }
+
+    pub fn is_two_step(&self) -> bool {
+        self.flags & PTP_V2_TWO_STEP != 0
+    }
+
+    /// correctionField in whole nanoseconds (sub-ns part truncated toward -inf).
+    pub fn correction_ns(&self) -> i64 {
+        self.correction_field >> 16
+    }
+
+    /// 6-byte source id in the form the v1 path uses: the MAC address inside an
+    /// EUI-64 clockIdentity (xx:xx:xx:FF:FE:xx:xx:xx), else its first 6 bytes.
+    pub fn source_uuid(&self) -> [u8; 6] {
+        let id = &self.clock_identity;
+        if id[3] == 0xFF && id[4] == 0xFE {
+            [id[0], id[1], id[2], id[5], id[6], id[7]]
+        } else {
+            [id[0], id[1], id[2], id[3], id[4], id[5]]
+        }
+    }
+}
+
+/// PTPv2 timestamp: 48-bit seconds and 32-bit nanoseconds (10 bytes).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PtpV2Timestamp {
+    pub seconds: u64,
+    pub nanoseconds: u32,
+}
+
+impl PtpV2Timestamp {
+    pub const SIZE: usize = 10;
+
+    fn read(rdr: &mut Cursor<&[u8]>) -> Result<Self> {
+        let seconds = rdr.read_u48::<BigEndian>()?;
+        let nanoseconds = rdr.read_u32::<BigEndian>()?;
+        Ok(PtpV2Timestamp {
+            seconds,
+            nanoseconds,
+        })
+    }
+
+    fn write(&self, w: &mut Cursor<&mut [u8]>) {
+        let _ = w.write_u48::<BigEndian>(self.seconds & 0xFFFF_FFFF_FFFF);
+        let _ = w.write_u32::<BigEndian>(self.nanoseconds);
+    }
+
+    /// Saturating, like `PtpTimestamp::to_nanos`.
+    pub fn to_nanos(&self) -> i64 {
+        (self.seconds.min(i64::MAX as u64) as i64)
+            .saturating_mul(1_000_000_000)
+            .saturating_add(self.nanoseconds as i64)
+    }
+
+    pub fn from_nanos(ns: i64) -> Self {
+        let ns = ns.max(0);
+        PtpV2Timestamp {
+            seconds: (ns / 1_000_000_000) as u64,
+            nanoseconds: (ns % 1_000_000_000) as u32,
+        }
+    }
+}
+
+/// Sync body: originTimestamp (precise for one-step masters).
+#[derive(Debug, PartialEq, Eq)]
+pub struct PtpV2SyncBody {
+    pub origin_timestamp: PtpV2Timestamp,
+}
+
+impl PtpV2SyncBody {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < PtpV2Timestamp::SIZE {
+            return Err(anyhow!("Packet too short for PTPv2 Sync body"));
+        }
+        let origin_timestamp = PtpV2Timestamp::read(&mut Cursor::new(data))?;
+        Ok(PtpV2SyncBody { origin_timestamp })
+    }
+}
+
+/// Follow_Up body: preciseOriginTimestamp of the Sync with the same sequenceId.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PtpV2FollowUpBody {
+    pub precise_origin_timestamp: PtpV2Timestamp,
+}
+
+impl PtpV2FollowUpBody {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < PtpV2Timestamp::SIZE {
+            return Err(anyhow!("Packet too short for PTPv2 Follow_Up body"));
+        }
+        let precise_origin_timestamp = PtpV2Timestamp::read(&mut Cursor::new(data))?;
+        Ok(PtpV2FollowUpBody {
+            precise_origin_timestamp,
+        })
+    }
+}
+
+/// Announce body: the grandmaster's quality, ranked by master selection.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PtpV2AnnounceBody {
+    pub current_utc_offset: i16,
+    pub grandmaster_priority1: u8,
+    pub grandmaster_clock_class: u8,
+    pub grandmaster_clock_accuracy: u8,
+    pub grandmaster_offset_scaled_log_variance: u16,
+    pub grandmaster_priority2: u8,
+    pub grandmaster_identity: [u8; 8],
+    pub steps_removed: u16,
+}
+
+impl PtpV2AnnounceBody {
+    pub const SIZE: usize = 30;
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < Self::SIZE {
+            return Err(anyhow!("Packet too short for PTPv2 Announce body"));
+        }
+        let mut rdr = Cursor::new(data);
+        // Skip originTimestamp (10)
+        rdr.set_position(PtpV2Timestamp::SIZE as u64);
+        let current_utc_offset = rdr.read_i16::<BigEndian>()?;
+        let _reserved = rdr.read_u8()?;
+        let grandmaster_priority1 = rdr.read_u8()?;
+        let grandmaster_clock_class = rdr.read_u8()?;
+        let grandmaster_clock_accuracy = rdr.read_u8()?;
+        let grandmaster_offset_scaled_log_variance = rdr.read_u16::<BigEndian>()?;
+        let grandmaster_priority2 = rdr.read_u8()?;
+        let mut grandmaster_identity = [0u8; 8];
+        for byte in &mut grandmaster_identity {
+            *byte = rdr.read_u8()?;
+        }
+        let steps_removed = rdr.read_u16::<BigEndian>()?;
+
+        Ok(PtpV2AnnounceBody {
+            current_utc_offset,
+            grandmaster_priority1,
+            grandmaster_clock_class,
+            grandmaster_clock_accuracy,
+            grandmaster_offset_scaled_log_variance,
+            grandmaster_priority2,
+            grandmaster_identity,
+            steps_removed,
+        })
+    }
+}
+
+/// Encode a PTPv2 Sync (two-step) or Follow_Up carrying `timestamp`.
+/// `correction_ns` goes into the correctionField.
+pub fn encode_v2(
+    message_type: PtpV2MessageType,
+    clock_identity: [u8; 8],
+    sequence_id: u16,
+    timestamp: PtpV2Timestamp,
+    correction_ns: i64,
+    two_step: bool,
+) -> Vec<u8> {
+    let (type_nibble, control) = match message_type {
+        PtpV2MessageType::FollowUp => (0x8, 2),
+        _ => (0x0, 0),
+    };
+    let len = PtpV2Header::SIZE + PtpV2Timestamp::SIZE;
+    let mut buf = vec![0u8; len];
+    let flags = if two_step { PTP_V2_TWO_STEP } else { 0 };
+    let mut w = Cursor::new(&mut buf[..]);
+    let _ = w.write_u8(type_nibble);
+    let _ = w.write_u8(2); // versionPTP
+    let _ = w.write_u16::<BigEndian>(len as u16);
+    let _ = w.write_u8(0); // domainNumber
+    let _ = w.write_u8(0);
+    let _ = w.write_u16::<BigEndian>(flags);
+    let _ = w.write_i64::<BigEndian>(correction_ns << 16);
+    w.set_position(20);
+    let pos = w.position() as usize;
+    w.get_mut()[pos..pos + 8].copy_from_slice(&clock_identity);
+    w.set_position(28);
+    let _ = w.write_u16::<BigEndian>(1); // portNumber
+    let _ = w.write_u16::<BigEndian>(sequence_id);
+    let _ = w.write_u8(control);
+    let _ = w.write_i8(0); // logMessageInterval: 1/s
+    timestamp.write(&mut w);
+    buf
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +836,24 @@ mod tests {
         assert_eq!(header.message_type, PtpV1Control::Sync);
         assert_eq!(header.sequence_id, 258);
         assert_eq!(header.source_uuid, [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]);
+        assert_eq!(header.subdomain_name(), "");
+    }
+
+    #[test]
+    fn test_subdomain_decoded() {
+        let sync = encode_sync([1; 6], 1, PtpTimestamp::from_nanos(0), true);
+        assert_eq!(
+            PtpV1Header::parse(&sync).unwrap().subdomain_name(),
+            DEFAULT_SUBDOMAIN
+        );
+
+        // A name filling all 16 bytes has no NUL terminator
+        let mut sync = sync;
+        write_subdomain(&mut sync, "SIXTEEN_CHARS_XY_overflow");
+        assert_eq!(
+            PtpV1Header::parse(&sync).unwrap().subdomain_name(),
+            "SIXTEEN_CHARS_XY"
+        );
     }
 
     #[test]
@@ -248,15 +891,14 @@ mod tests {
 
     #[test]
     fn test_parse_sync_body_gm_uuid() {
-        let mut data = vec![0u8; 20];
-        // 13 bytes skip
-        // 13: GM UUID start
-        data[13] = 0x11;
-        data[14] = 0x22;
-        data[15] = 0x33;
-        data[16] = 0x44;
-        data[17] = 0x55;
-        data[18] = 0x66;
+        let mut data = vec![0u8; 24];
+        // 18: GM UUID start (absolute byte 54)
+        data[18] = 0x11;
+        data[19] = 0x22;
+        data[20] = 0x33;
+        data[21] = 0x44;
+        data[22] = 0x55;
+        data[23] = 0x66;
 
         let body = PtpV1SyncMessageBody::parse(&data).unwrap();
         assert_eq!(
@@ -264,4 +906,301 @@ mod tests {
             [0x11, 0x22, 0x33, 0x44, 0x55, 0x66]
         );
     }
+
+    #[test]
+    fn test_communication_technology_fields() {
+        let mut buf = encode_sync(
+            [0x00, 0x1D, 0xC1, 0xFE, 0xED, 0x01],
+            1,
+            PtpTimestamp::from_nanos(0),
+            true,
+        );
+        let header = PtpV1Header::parse(&buf).unwrap();
+        assert_eq!(header.source_communication_technology, COMM_TECH_ETHERNET);
+        assert!(header.is_ethernet());
+
+        // IEEE 1394 (PTP_IEEE1394 = 243): UUIDs are not MAC addresses
+        buf[21] = 243;
+        buf[53] = 243;
+        assert!(!PtpV1Header::parse(&buf).unwrap().is_ethernet());
+        let body = PtpV1SyncMessageBody::parse(&buf[PtpV1Header::SIZE..]).unwrap();
+        assert_eq!(body.grandmaster_communication_technology, 243);
+    }
+
+    #[test]
+    fn test_parse_sync_body_origin_timestamp() {
+        // Body after the 36-byte header: 4 reserved bytes, then originTimestamp
+        let mut data = vec![0u8; 24];
+        data[7] = 0x2A; // 42 seconds
+        data[10] = 0x03;
+        data[11] = 0xE8; // 1000 nanos
+
+        data[14] = 0x00;
+        data[15] = 37; // currentUtcOffset
+
+        let body = PtpV1SyncMessageBody::parse(&data).unwrap();
+        assert_eq!(body.origin_timestamp.seconds, 42);
+        assert_eq!(body.origin_timestamp.nanoseconds, 1000);
+        assert_eq!(body.current_utc_offset, 37);
+    }
+
+    #[test]
+    fn test_encoded_sync_parses_back() {
+        let uuid = [0x00, 0x1D, 0xC1, 0xFE, 0xED, 0x01];
+        let origin = PtpTimestamp::from_nanos(1_234_567_890_123);
+        let buf = encode_sync(uuid, 77, origin, true);
+        assert_eq!(buf.len(), SYNC_MESSAGE_SIZE);
+
+        let header = PtpV1Header::parse(&buf).unwrap();
+        assert_eq!(header.message_type, PtpV1Control::Sync);
+        assert_eq!(header.source_uuid, uuid);
+        assert_eq!(header.sequence_id, 77);
+        assert!(header.is_two_step());
+
+        let body = PtpV1SyncMessageBody::parse(&buf[PtpV1Header::SIZE..]).unwrap();
+        assert_eq!(body.origin_timestamp, origin);
+        assert_eq!(body.grandmaster_clock_uuid, uuid);
+        assert_eq!(
+            body.grandmaster_communication_technology,
+            COMM_TECH_ETHERNET
+        );
+
+        let one_step = encode_sync(uuid, 78, origin, false);
+        assert!(!PtpV1Header::parse(&one_step).unwrap().is_two_step());
+    }
+
+    #[test]
+    fn test_encoded_sync_wire_layout() {
+        let uuid = [0x00, 0x1D, 0xC1, 0xFE, 0xED, 0x01];
+        let origin = PtpTimestamp {
+            seconds: 0x0102_0304,
+            nanoseconds: 0x0506_0708,
+        };
+        let buf = encode_sync(uuid, 0x0A0B, origin, false);
+
+        // Absolute offsets per IEEE 1588-2002, not what our parser expects
+        assert_eq!(&buf[36..40], &[0; 4], "reserved");
+        assert_eq!(&buf[40..48], &[1, 2, 3, 4, 5, 6, 7, 8], "originTimestamp");
+        assert_eq!(&buf[48..50], &[0, 0], "epochNumber");
+        assert_eq!(&buf[50..52], &[0, 0], "currentUTCOffset");
+        assert_eq!(buf[52], 0, "reserved");
+        assert_eq!(
+            buf[53], COMM_TECH_ETHERNET,
+            "grandmasterCommunicationTechnology"
+        );
+        assert_eq!(&buf[54..60], &uuid, "grandmasterClockUuid");
+        assert_eq!(&buf[60..62], &[0, 1], "grandmasterPortId");
+        assert_eq!(&buf[62..64], &[0x0A, 0x0B], "grandmasterSequenceId");
+
+        let req = encode_delay_req(uuid, 0x0A0B, origin, DEFAULT_SUBDOMAIN);
+        assert_eq!(
+            &req[40..48],
+            &buf[40..48],
+            "Delay_Req shares the Sync layout"
+        );
+    }
+
+    #[test]
+    fn test_encoded_follow_up_parses_back() {
+        let uuid = [0x00, 0x1D, 0xC1, 0xFE, 0xED, 0x01];
+        let precise = PtpTimestamp::from_nanos(5_000_000_250);
+        let buf = encode_follow_up(uuid, 9, 77, precise);
+        assert_eq!(buf.len(), FOLLOW_UP_MESSAGE_SIZE);
+
+        let header = PtpV1Header::parse(&buf).unwrap();
+        assert_eq!(header.message_type, PtpV1Control::FollowUp);
+        let body = PtpV1FollowUpBody::parse(&buf[PtpV1Header::SIZE..]).unwrap();
+        assert_eq!(body.associated_sequence_id, 77);
+        assert_eq!(body.precise_origin_timestamp, precise);
+        assert_eq!(body.precise_origin_timestamp.to_nanos(), 5_000_000_250);
+    }
+
+    #[test]
+    fn test_encoded_delay_exchange_parses_back() {
+        let slave = [0x02, 0x11, 0x22, 0x33, 0x44, 0x55];
+        let master = [0x00, 0x1D, 0xC1, 0xFE, 0xED, 0x01];
+        let t3 = PtpTimestamp::from_nanos(7_000_000_100);
+        let req = encode_delay_req(slave, 5, t3, "AV_NET");
+        let header = PtpV1Header::parse(&req).unwrap();
+        assert_eq!(header.message_type, PtpV1Control::DelayReq);
+        assert_eq!(header.source_uuid, slave);
+        assert_eq!(header.subdomain_name(), "AV_NET");
+        let body = PtpV1DelayReqBody::parse(&req[PtpV1Header::SIZE..]).unwrap();
+        assert_eq!(body.origin_timestamp, t3);
+
+        let t4 = PtpTimestamp::from_nanos(7_000_050_000);
+        let resp = encode_delay_resp(master, 9, t4, (slave, 1), 5);
+        let header = PtpV1Header::parse(&resp).unwrap();
+        assert_eq!(header.message_type, PtpV1Control::DelayResp);
+        let body = PtpV1DelayRespBody::parse(&resp[PtpV1Header::SIZE..]).unwrap();
+        assert_eq!(body.delay_receipt_timestamp, t4);
+        assert_eq!(body.requesting_source_uuid, slave);
+        assert_eq!(body.requesting_source_port_id, 1);
+        assert_eq!(body.requesting_source_sequence_id, 5);
+    }
+
+    #[test]
+    fn test_header_two_step_flag() {
+        let mut data = vec![0u8; 36];
+        data[0] = 0x10;
+        assert!(!PtpV1Header::parse(&data).unwrap().is_two_step());
+
+        data[35] = PTP_ASSIST as u8;
+        let header = PtpV1Header::parse(&data).unwrap();
+        assert_eq!(header.flags, PTP_ASSIST);
+        assert!(header.is_two_step());
+    }
+
+    #[test]
+    fn test_parse_delay_resp_body() {
+        // Body after the 36-byte header: 4 reserved bytes, then delayReceiptTimestamp
+        let mut data = vec![0u8; 24];
+        data[7] = 0x07; // 7 seconds
+        data[11] = 0x64; // 100 nanos
+        data[14..20].copy_from_slice(&[0x00, 0x1D, 0xC1, 0x01, 0x02, 0x03]);
+        data[21] = 0x01; // port 1
+        data[22] = 0x01;
+        data[23] = 0x2C; // seq 300
+
+        let body = PtpV1DelayRespBody::parse(&data).unwrap();
+        assert_eq!(body.delay_receipt_timestamp.to_nanos(), 7_000_000_100);
+        assert_eq!(
+            body.requesting_source_uuid,
+            [0x00, 0x1D, 0xC1, 0x01, 0x02, 0x03]
+        );
+        assert_eq!(body.requesting_source_port_id, 1);
+        assert_eq!(body.requesting_source_sequence_id, 300);
+
+        assert!(PtpV1DelayRespBody::parse(&data[..23]).is_err());
+    }
+    #[test]
+    fn test_parse_v2_header_and_bodies() {
+        let id = [0x00, 0x1D, 0xC1, 0xFF, 0xFE, 0x0A, 0x0B, 0x0C];
+        let ts = PtpV2Timestamp {
+            seconds: 0x0001_0000_0002,
+            nanoseconds: 500,
+        };
+        let buf = encode_v2(PtpV2MessageType::Sync, id, 42, ts, -3, true);
+        assert!(is_ptp_v2(&buf));
+        assert!(!is_ptp_v2(&encode_sync(
+            [0; 6],
+            1,
+            PtpTimestamp::from_nanos(0),
+            true
+        )));
+
+        let header = PtpV2Header::parse(&buf).unwrap();
+        assert_eq!(header.message_type, PtpV2MessageType::Sync);
+        assert_eq!(header.version_ptp, 2);
+        assert_eq!(header.message_length, 44);
+        assert_eq!(header.sequence_id, 42);
+        assert_eq!(header.port_number, 1);
+        assert!(header.is_two_step());
+        assert_eq!(header.correction_ns(), -3);
+        assert_eq!(header.source_uuid(), [0x00, 0x1D, 0xC1, 0x0A, 0x0B, 0x0C]);
+
+        let body = PtpV2SyncBody::parse(&buf[PtpV2Header::SIZE..]).unwrap();
+        assert_eq!(body.origin_timestamp, ts);
+        assert_eq!(
+            body.origin_timestamp.to_nanos(),
+            0x0001_0000_0002 * 1_000_000_000 + 500
+        );
+
+        let buf = encode_v2(PtpV2MessageType::FollowUp, id, 42, ts, 0, false);
+        let header = PtpV2Header::parse(&buf).unwrap();
+        assert_eq!(header.message_type, PtpV2MessageType::FollowUp);
+        assert!(!header.is_two_step());
+        let body = PtpV2FollowUpBody::parse(&buf[PtpV2Header::SIZE..]).unwrap();
+        assert_eq!(body.precise_origin_timestamp, ts);
+
+        assert!(PtpV2Header::parse(&buf[..33]).is_err());
+        assert!(PtpV2SyncBody::parse(&[0u8; 9]).is_err());
+    }
+
+    #[test]
+    fn test_truncated_v1_message_rejected_by_type() {
+        let t1 = PtpTimestamp::from_nanos(1_000);
+        let follow_up = encode_follow_up([0xAA; 6], 7, 6, t1);
+        assert!(PtpV1Header::parse(&follow_up).is_ok());
+        // Header intact, body cut short: never handed to the body parser
+        let err = PtpV1Header::parse(&follow_up[..follow_up.len() - 1]).unwrap_err();
+        assert!(err.to_string().contains("FollowUp"), "{}", err);
+
+        let delay_resp = encode_delay_resp([0xAA; 6], 8, t1, ([0xBB; 6], 1), 5);
+        assert!(PtpV1Header::parse(&delay_resp).is_ok());
+        assert!(PtpV1Header::parse(&delay_resp[..PtpV1Header::SIZE + 23]).is_err());
+
+        // A two-step Sync is still usable with a short body
+        let sync = encode_sync([0xAA; 6], 7, t1, true);
+        assert!(PtpV1Header::parse(&sync[..PtpV1Header::SIZE]).is_ok());
+
+        // Unknown control values only need the header
+        let mut other = follow_up[..PtpV1Header::SIZE].to_vec();
+        other[32] = 0x7F;
+        assert_eq!(
+            PtpV1Header::parse(&other).unwrap().message_type,
+            PtpV1Control::Other
+        );
+    }
+
+    #[test]
+    fn test_v2_message_length_must_fit_packet() {
+        let ts = PtpV2Timestamp {
+            seconds: 1,
+            nanoseconds: 0,
+        };
+        let mut buf = encode_v2(PtpV2MessageType::Sync, [1; 8], 1, ts, 0, true);
+        // Trailing padding (e.g. Ethernet minimum frame) is fine
+        let mut padded = buf.clone();
+        padded.resize(64, 0);
+        assert!(PtpV2Header::parse(&padded).is_ok());
+
+        // Claims more bytes than were received
+        buf[2..4].copy_from_slice(&45u16.to_be_bytes());
+        let err = PtpV2Header::parse(&buf).unwrap_err();
+        assert!(err.to_string().contains("messageLength 45"), "{}", err);
+        // Claims less than a header
+        buf[2..4].copy_from_slice(&33u16.to_be_bytes());
+        assert!(PtpV2Header::parse(&buf).is_err());
+    }
+
+    #[test]
+    fn test_v2_correction_is_scaled_nanoseconds() {
+        let mut buf = encode_v2(
+            PtpV2MessageType::Sync,
+            [1; 8],
+            1,
+            PtpV2Timestamp::from_nanos(0),
+            0,
+            false,
+        );
+        // 2.5ns = 0x28000 scaled
+        buf[8..16].copy_from_slice(&0x28000i64.to_be_bytes());
+        assert_eq!(PtpV2Header::parse(&buf).unwrap().correction_ns(), 2);
+        buf[8..16].copy_from_slice(&(-0x28000i64).to_be_bytes());
+        assert_eq!(PtpV2Header::parse(&buf).unwrap().correction_ns(), -3);
+    }
+    #[test]
+    fn test_parse_v2_announce_body() {
+        let mut body = vec![0u8; PtpV2AnnounceBody::SIZE];
+        body[10..12].copy_from_slice(&37i16.to_be_bytes());
+        body[13] = 100; // priority1
+        body[14] = 6; // clockClass
+        body[15] = 0x21; // clockAccuracy
+        body[16..18].copy_from_slice(&0x4E5Du16.to_be_bytes());
+        body[18] = 128; // priority2
+        body[19..27].copy_from_slice(&[0x00, 0x1D, 0xC1, 0xFF, 0xFE, 0x0A, 0x0B, 0x0C]);
+        body[27..29].copy_from_slice(&1u16.to_be_bytes());
+
+        let announce = PtpV2AnnounceBody::parse(&body).unwrap();
+        assert_eq!(announce.current_utc_offset, 37);
+        assert_eq!(announce.grandmaster_priority1, 100);
+        assert_eq!(announce.grandmaster_clock_class, 6);
+        assert_eq!(announce.grandmaster_clock_accuracy, 0x21);
+        assert_eq!(announce.grandmaster_offset_scaled_log_variance, 0x4E5D);
+        assert_eq!(announce.grandmaster_priority2, 128);
+        assert_eq!(announce.grandmaster_identity[7], 0x0C);
+        assert_eq!(announce.steps_removed, 1);
+        assert!(PtpV2AnnounceBody::parse(&body[..29]).is_err());
+    }
 }
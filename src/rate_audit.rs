@@ -0,0 +1,227 @@
+//! Self-audit: is the OS actually applying our frequency corrections?
+//!
+//! Elapsed system time is compared with elapsed raw monotonic time (a counter the
+//! frequency adjustment does not touch) over a window. The ratio is the frequency
+//! correction really in effect; it should equal what the servo commanded, averaged
+//! over the same window. A persistent divergence means adjustments are silently
+//! ignored or overridden - the failure `clocktest` reproduces by hand.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Divergence this large is a step (ours or another time service), not a rate
+const STEP_DISCARD_PPM: f64 = 5_000.0;
+
+/// Consecutive divergent windows before the audit alarms
+const ALARM_WINDOWS: u32 = 2;
+
+/// Raw monotonic and system time read back to back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockPair {
+    pub mono_ns: u64,
+    pub system_ns: i128,
+}
+
+impl ClockPair {
+    pub fn now() -> Self {
+        let mono_ns = raw_monotonic_ns();
+        let system_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as i128)
+            .unwrap_or(0);
+        Self { mono_ns, system_ns }
+    }
+}
+
+/// Monotonic time that frequency adjustment does not slew.
+///
+/// Linux slews CLOCK_MONOTONIC along with the system clock (adjtimex), so
+/// CLOCK_MONOTONIC_RAW is needed there. `Instant` is already raw elsewhere
+/// (QPC on Windows, mach_absolute_time on macOS).
+#[cfg(target_os = "linux")]
+fn raw_monotonic_ns() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: valid clock id and a properly sized out-pointer
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_RAW, &mut ts) };
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
+
+#[cfg(not(target_os = "linux"))]
+fn raw_monotonic_ns() -> u64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+/// Result of one audit window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuditResult {
+    /// Frequency correction actually in effect
+    pub measured_ppm: f64,
+    /// Time-weighted mean of what the servo commanded over the window
+    pub commanded_ppm: f64,
+    /// Divergence beyond tolerance for `ALARM_WINDOWS` windows in a row
+    pub alarm: bool,
+}
+
+impl AuditResult {
+    pub fn divergence_ppm(&self) -> f64 {
+        self.measured_ppm - self.commanded_ppm
+    }
+}
+
+#[derive(Debug)]
+pub struct RateAudit {
+    window: Duration,
+    tolerance_ppm: f64,
+    start: Option<ClockPair>,
+    /// Last change of the commanded frequency
+    last_mark: Option<ClockPair>,
+    commanded_ppm: f64,
+    /// Integral of commanded ppm over raw time since `start` (ppm·ns)
+    weighted_ppm_ns: f64,
+    divergent_windows: u32,
+}
+
+impl RateAudit {
+    pub fn new(window: Duration, tolerance_ppm: f64) -> Self {
+        Self {
+            window,
+            tolerance_ppm,
+            start: None,
+            last_mark: None,
+            commanded_ppm: 0.0,
+            weighted_ppm_ns: 0.0,
+            divergent_windows: 0,
+        }
+    }
+
+    /// The servo commanded `ppm` at `now`.
+    pub fn set_commanded(&mut self, now: ClockPair, ppm: f64) {
+        self.integrate(now);
+        self.commanded_ppm = ppm;
+    }
+
+    fn integrate(&mut self, now: ClockPair) {
+        if let Some(mark) = self.last_mark {
+            let dt_ns = now.mono_ns.saturating_sub(mark.mono_ns) as f64;
+            self.weighted_ppm_ns += self.commanded_ppm * dt_ns;
+        }
+        if self.start.is_none() {
+            self.start = Some(now);
+        }
+        self.last_mark = Some(now);
+    }
+
+    /// Close the window if it is due. Returns the result at the end of each window.
+    pub fn check(&mut self, now: ClockPair) -> Option<AuditResult> {
+        let Some(start) = self.start else {
+            self.integrate(now);
+            return None;
+        };
+        let mono_ns = now.mono_ns.saturating_sub(start.mono_ns);
+        if mono_ns < self.window.as_nanos() as u64 {
+            return None;
+        }
+        self.integrate(now);
+
+        let system_ns = (now.system_ns - start.system_ns) as f64;
+        let measured_ppm = (system_ns - mono_ns as f64) / mono_ns as f64 * 1e6;
+        let commanded_ppm = self.weighted_ppm_ns / mono_ns as f64;
+        self.start = Some(now);
+        self.weighted_ppm_ns = 0.0;
+
+        if (measured_ppm - commanded_ppm).abs() > STEP_DISCARD_PPM {
+            return None; // Clock stepped inside the window
+        }
+        if (measured_ppm - commanded_ppm).abs() > self.tolerance_ppm {
+            self.divergent_windows += 1;
+        } else {
+            self.divergent_windows = 0;
+        }
+        Some(AuditResult {
+            measured_ppm,
+            commanded_ppm,
+            alarm: self.divergent_windows >= ALARM_WINDOWS,
+        })
+    }
+
+    /// Drop the open window (the clock was stepped). Keeps the commanded value.
+    pub fn restart(&mut self) {
+        self.start = None;
+        self.last_mark = None;
+        self.weighted_ppm_ns = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    /// Clock pair after `secs` of raw time with the system clock running at `ppm`
+    fn at(secs: f64, ppm: f64) -> ClockPair {
+        let mono_ns = (secs * 1e9) as u64;
+        ClockPair {
+            mono_ns,
+            system_ns: (mono_ns as f64 * (1.0 + ppm / 1e6)) as i128,
+        }
+    }
+
+    #[test]
+    fn test_honored_adjustment_passes() {
+        let mut audit = RateAudit::new(WINDOW, 5.0);
+        audit.set_commanded(at(0.0, 35.0), 35.0);
+        assert_eq!(audit.check(at(30.0, 35.0)), None);
+        let result = audit.check(at(60.0, 35.0)).unwrap();
+        assert!((result.measured_ppm - 35.0).abs() < 0.01);
+        assert!((result.commanded_ppm - 35.0).abs() < 0.01);
+        assert!(!result.alarm);
+    }
+
+    #[test]
+    fn test_commanded_is_time_weighted() {
+        let mut audit = RateAudit::new(WINDOW, 5.0);
+        audit.set_commanded(at(0.0, 0.0), 10.0);
+        audit.set_commanded(at(45.0, 0.0), 50.0);
+        // Whatever the clock did, the servo asked for 10ppm x 45s + 50ppm x 15s
+        let result = audit.check(at(60.0, 20.0)).unwrap();
+        assert!((result.commanded_ppm - 20.0).abs() < 0.01);
+        assert!(!result.alarm);
+    }
+
+    #[test]
+    fn test_ignored_adjustment_alarms_when_persistent() {
+        let mut audit = RateAudit::new(WINDOW, 5.0);
+        audit.set_commanded(at(0.0, 0.0), 40.0);
+        // OS ignores the adjustment: system time runs at nominal
+        let first = audit.check(at(60.0, 0.0)).unwrap();
+        assert!((first.divergence_ppm() + 40.0).abs() < 0.01);
+        assert!(!first.alarm, "A single window is not persistent");
+        let second = audit.check(at(120.0, 0.0)).unwrap();
+        assert!(second.alarm);
+    }
+
+    #[test]
+    fn test_step_inside_window_is_discarded() {
+        let mut audit = RateAudit::new(WINDOW, 5.0);
+        audit.set_commanded(at(0.0, 0.0), 0.0);
+        let mut stepped = at(60.0, 0.0);
+        stepped.system_ns += 1_000_000_000;
+        assert_eq!(audit.check(stepped), None);
+    }
+
+    #[test]
+    fn test_raw_clock_pair_is_consistent() {
+        let a = ClockPair::now();
+        std::thread::sleep(Duration::from_millis(20));
+        let b = ClockPair::now();
+        let mono = b.mono_ns - a.mono_ns;
+        assert!(mono >= 20_000_000);
+        assert!(((b.system_ns - a.system_ns) as f64 - mono as f64).abs() < 5_000_000.0);
+    }
+}
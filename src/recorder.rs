@@ -0,0 +1,325 @@
+//! PTP packet recorder for offline timestamp-source analysis.
+//!
+//! Every received packet is written as one JSON line containing BOTH:
+//! - the timestamp delivered by the network backend (kernel SO_TIMESTAMPNS on Linux,
+//!   Npcap driver timestamp on Windows), and
+//! - the application `SystemTime::now()` taken right after the packet was returned.
+//!
+//! Replaying a recording (see the `ptpreplay` binary) quantifies how much jitter the
+//! application timestamp would have added compared to the kernel/driver path, which
+//! tells us whether a machine actually benefits from the better timestamp source.
+
+use crate::clock::system_time_to_unix_nanos;
+use crate::ptp::{is_ptp_v2, PtpV1Control, PtpV1Header, PtpV2Header, PtpV2MessageType};
+use crate::traits::PtpNetwork;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::net::Ipv4Addr;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// A single recorded packet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PacketRecord {
+    /// Timestamp delivered by the network backend (ns since Unix epoch)
+    pub kernel_ts_ns: i64,
+    /// Application `now()` right after the packet was received (ns since Unix epoch)
+    pub app_ts_ns: i64,
+    /// Label of the timestamp source that produced `kernel_ts_ns` (e.g. "SO_TIMESTAMPNS")
+    pub source: String,
+    /// Sender IP address, if known
+    pub source_ip: Option<Ipv4Addr>,
+    /// Raw PTP payload
+    pub data: Vec<u8>,
+}
+
+/// Writes PacketRecords as JSON lines.
+pub struct PacketRecorder {
+    writer: BufWriter<File>,
+    source: String,
+    count: u64,
+}
+
+impl PacketRecorder {
+    /// Create (truncate) a recording file. `source` labels the backend timestamp source.
+    pub fn create<P: AsRef<Path>>(path: P, source: &str) -> Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            source: source.to_string(),
+            count: 0,
+        })
+    }
+
+    /// Append one packet with both its backend and application timestamps.
+    /// Packets with a timestamp before the Unix epoch are not recorded.
+    pub fn record(
+        &mut self,
+        data: &[u8],
+        kernel_ts: SystemTime,
+        app_ts: SystemTime,
+        source_ip: Option<Ipv4Addr>,
+    ) -> Result<()> {
+        let (Some(kernel_ts_ns), Some(app_ts_ns)) = (
+            system_time_to_unix_nanos(kernel_ts),
+            system_time_to_unix_nanos(app_ts),
+        ) else {
+            return Ok(());
+        };
+        let rec = PacketRecord {
+            kernel_ts_ns,
+            app_ts_ns,
+            source: self.source.clone(),
+            source_ip,
+            data: data.to_vec(),
+        };
+        serde_json::to_writer(&mut self.writer, &rec)?;
+        self.writer.write_all(b"\n")?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Number of packets recorded so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl Drop for PacketRecorder {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+/// Read all records from a JSON-lines recording. Blank lines are skipped.
+pub fn read_records<P: AsRef<Path>>(path: P) -> Result<Vec<PacketRecord>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(&line)?);
+    }
+    Ok(records)
+}
+
+/// PtpNetwork wrapper that records every received packet when a recorder is attached.
+pub struct RecordingNetwork<N: PtpNetwork> {
+    inner: N,
+    recorder: Option<PacketRecorder>,
+}
+
+impl<N: PtpNetwork> RecordingNetwork<N> {
+    pub fn new(inner: N, recorder: Option<PacketRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<N: PtpNetwork> PtpNetwork for RecordingNetwork<N> {
+    fn recv_packet(&mut self) -> Result<Option<(Vec<u8>, usize, SystemTime, Option<Ipv4Addr>)>> {
+        let result = self.inner.recv_packet()?;
+        if let (Some(rec), Some((data, size, ts, ip))) = (self.recorder.as_mut(), result.as_ref()) {
+            let app_ts = SystemTime::now();
+            if let Err(e) = rec.record(&data[..*size], *ts, app_ts, *ip) {
+                log::warn!("[Recorder] Failed to write packet record: {}", e);
+            }
+        }
+        Ok(result)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.inner.reset()
+    }
+
+    fn send_packet(&mut self, data: &[u8]) -> Result<()> {
+        self.inner.send_packet(data)
+    }
+
+    fn hw_timestamping(&self) -> bool {
+        self.inner.hw_timestamping()
+    }
+
+    fn poll_readable(&mut self, timeout: Duration) -> Result<bool> {
+        self.inner.poll_readable(timeout)
+    }
+}
+
+// ============================================================================
+// ANALYSIS
+// ============================================================================
+
+/// Jitter comparison between application and kernel/driver timestamps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JitterReport {
+    /// Number of Sync inter-arrival intervals analyzed
+    pub intervals: usize,
+    /// Std deviation of Sync inter-arrival intervals using app timestamps (ns)
+    pub app_jitter_ns: f64,
+    /// Std deviation of Sync inter-arrival intervals using kernel/driver timestamps (ns)
+    pub kernel_jitter_ns: f64,
+    /// Mean (app - kernel) latency (ns)
+    pub mean_latency_ns: f64,
+    /// Maximum (app - kernel) latency (ns)
+    pub max_latency_ns: i64,
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    var.sqrt()
+}
+
+/// Compare app vs kernel timestamp jitter over the Sync messages in a recording.
+///
+/// Only Sync messages (PTPv1 or PTPv2) are used: they are the timestamp-critical
+/// (event) packets and arrive at a fixed rate, so the spread of their inter-arrival
+/// intervals is the jitter contributed by the timestamp path.
+pub fn analyze_jitter(records: &[PacketRecord]) -> Option<JitterReport> {
+    let syncs: Vec<&PacketRecord> = records.iter().filter(|r| is_sync(&r.data)).collect();
+
+    if syncs.len() < 3 {
+        return None;
+    }
+
+    let app_intervals: Vec<f64> = syncs
+        .windows(2)
+        .map(|w| (w[1].app_ts_ns - w[0].app_ts_ns) as f64)
+        .collect();
+    let kernel_intervals: Vec<f64> = syncs
+        .windows(2)
+        .map(|w| (w[1].kernel_ts_ns - w[0].kernel_ts_ns) as f64)
+        .collect();
+    let latencies: Vec<i64> = syncs.iter().map(|r| r.app_ts_ns - r.kernel_ts_ns).collect();
+
+    Some(JitterReport {
+        intervals: app_intervals.len(),
+        app_jitter_ns: std_dev(&app_intervals),
+        kernel_jitter_ns: std_dev(&kernel_intervals),
+        mean_latency_ns: latencies.iter().sum::<i64>() as f64 / latencies.len() as f64,
+        max_latency_ns: latencies.iter().copied().max().unwrap_or(0),
+    })
+}
+
+fn is_sync(data: &[u8]) -> bool {
+    if is_ptp_v2(data) {
+        PtpV2Header::parse(data).is_ok_and(|h| h.message_type == PtpV2MessageType::Sync)
+    } else {
+        PtpV1Header::parse(data).is_ok_and(|h| h.message_type == PtpV1Control::Sync)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn sync_packet(seq: u16) -> Vec<u8> {
+        let mut buf = vec![0u8; 60];
+        buf[0] = 0x10;
+        buf[32] = 0x00; // Sync
+        buf[30] = (seq >> 8) as u8;
+        buf[31] = (seq & 0xFF) as u8;
+        buf
+    }
+
+    fn record(seq: u16, kernel_ns: i64, app_ns: i64) -> PacketRecord {
+        PacketRecord {
+            kernel_ts_ns: kernel_ns,
+            app_ts_ns: app_ns,
+            source: "test".to_string(),
+            source_ip: None,
+            data: sync_packet(seq),
+        }
+    }
+
+    #[test]
+    fn test_recorder_roundtrip_keeps_both_timestamps() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let kernel = UNIX_EPOCH + Duration::from_nanos(1_000_000_000);
+        let app = UNIX_EPOCH + Duration::from_nanos(1_000_050_000);
+        {
+            let mut rec = PacketRecorder::create(file.path(), "SO_TIMESTAMPNS").unwrap();
+            rec.record(
+                &sync_packet(7),
+                kernel,
+                app,
+                Some(Ipv4Addr::new(10, 0, 0, 1)),
+            )
+            .unwrap();
+            // A pre-epoch timestamp is not recorded as 0
+            if let Some(before_epoch) = UNIX_EPOCH.checked_sub(Duration::from_secs(1)) {
+                rec.record(&sync_packet(8), before_epoch, app, None)
+                    .unwrap();
+            }
+            assert_eq!(rec.count(), 1);
+        }
+
+        let records = read_records(file.path()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kernel_ts_ns, 1_000_000_000);
+        assert_eq!(records[0].app_ts_ns, 1_000_050_000);
+        assert_eq!(records[0].source, "SO_TIMESTAMPNS");
+        assert_eq!(records[0].source_ip, Some(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(records[0].data, sync_packet(7));
+    }
+
+    #[test]
+    fn test_analyze_jitter_app_noisier_than_kernel() {
+        // Kernel timestamps perfectly periodic (125ms), app timestamps alternate 10µs/60µs late
+        let records: Vec<PacketRecord> = (0..20)
+            .map(|i| {
+                let k = i as i64 * 125_000_000;
+                let lat = if i % 2 == 0 { 10_000 } else { 60_000 };
+                record(i, k, k + lat)
+            })
+            .collect();
+
+        let report = analyze_jitter(&records).expect("enough samples");
+        assert_eq!(report.intervals, 19);
+        assert!(
+            report.kernel_jitter_ns < 1.0,
+            "Kernel jitter should be ~0, got {}",
+            report.kernel_jitter_ns
+        );
+        assert!(
+            report.app_jitter_ns > 40_000.0,
+            "App jitter should reflect 50µs latency swing, got {}",
+            report.app_jitter_ns
+        );
+        assert_eq!(report.max_latency_ns, 60_000);
+    }
+
+    #[test]
+    fn test_analyze_jitter_ignores_non_sync_and_short_input() {
+        let mut followup = record(1, 0, 0);
+        followup.data[32] = 0x02;
+        assert!(analyze_jitter(&[followup.clone(), followup]).is_none());
+    }
+
+    #[test]
+    fn test_analyze_jitter_counts_v2_syncs() {
+        use crate::ptp::{encode_v2, PtpV2Timestamp};
+        let ts = PtpV2Timestamp::from_nanos(0);
+        let records: Vec<PacketRecord> = (0..6)
+            .flat_map(|i| {
+                let k = i as i64 * 125_000_000;
+                let mut sync = record(i, k, k + 10_000);
+                sync.data = encode_v2(PtpV2MessageType::Sync, [1; 8], i, ts, 0, true);
+                let mut followup = record(i, k + 1_000, k + 11_000);
+                followup.data = encode_v2(PtpV2MessageType::FollowUp, [1; 8], i, ts, 0, false);
+                [sync, followup]
+            })
+            .collect();
+
+        let report = analyze_jitter(&records).expect("v2 Syncs are counted");
+        assert_eq!(report.intervals, 5);
+        assert!(report.kernel_jitter_ns < 1.0);
+    }
+}
@@ -0,0 +1,192 @@
+//! Hardware real-time clock (RTC) access via `/dev/rtc`.
+//!
+//! Used for an optional cold start: on hardware where the system clock comes up
+//! wildly wrong (no hctosys, dead CMOS battery on the previous boot), the RTC is
+//! read before NTP so the first NTP step is small instead of years.
+//!
+//! The RTC is assumed to keep UTC (the Linux default).
+
+use crate::clock::SystemClock;
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use log::{info, warn};
+use std::fs::File;
+use std::os::fd::AsRawFd;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const RTC_DEVICES: [&str; 2] = ["/dev/rtc", "/dev/rtc0"];
+
+/// Kernel `struct rtc_time` (same layout as `struct tm` without the tail)
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RtcTime {
+    pub tm_sec: i32,
+    pub tm_min: i32,
+    pub tm_hour: i32,
+    pub tm_mday: i32,
+    /// 0-11
+    pub tm_mon: i32,
+    /// Years since 1900
+    pub tm_year: i32,
+    pub tm_wday: i32,
+    pub tm_yday: i32,
+    pub tm_isdst: i32,
+}
+
+nix::ioctl_read!(rtc_rd_time, b'p', 0x09, RtcTime);
+
+impl RtcTime {
+    /// Interpret as UTC.
+    pub fn to_system_time(&self) -> Result<SystemTime> {
+        let secs = NaiveDate::from_ymd_opt(
+            self.tm_year + 1900,
+            (self.tm_mon + 1) as u32,
+            self.tm_mday as u32,
+        )
+        .and_then(|d| d.and_hms_opt(self.tm_hour as u32, self.tm_min as u32, self.tm_sec as u32))
+        .ok_or_else(|| anyhow!("invalid RTC time {:?}", self))?
+        .and_utc()
+        .timestamp();
+        if secs < 0 {
+            return Err(anyhow!("RTC time before 1970: {:?}", self));
+        }
+        Ok(UNIX_EPOCH + Duration::from_secs(secs as u64))
+    }
+}
+
+/// Read the hardware clock (whole seconds). Errors if no RTC device exists.
+pub fn read_rtc() -> Result<SystemTime> {
+    let file = RTC_DEVICES
+        .iter()
+        .find_map(|path| File::open(path).ok())
+        .ok_or_else(|| anyhow!("no RTC device ({})", RTC_DEVICES.join(", ")))?;
+
+    let mut tm = RtcTime::default();
+    unsafe { rtc_rd_time(file.as_raw_fd(), &mut tm) }.context("RTC_RD_TIME failed")?;
+    tm.to_system_time()
+}
+
+/// System clock minus RTC (ms). The RTC only has whole seconds, so this is
+/// accurate to about one second - enough to tell whether the RTC itself is off.
+pub fn system_offset_ms() -> Result<i64> {
+    let rtc = read_rtc()?;
+    Ok(offset_ms(SystemTime::now(), rtc))
+}
+
+fn offset_ms(system: SystemTime, rtc: SystemTime) -> i64 {
+    match system.duration_since(rtc) {
+        Ok(ahead) => ahead.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    }
+}
+
+/// Step needed to move `now` to `rtc` when they differ by more than `threshold`.
+pub fn cold_start_step(
+    rtc: SystemTime,
+    now: SystemTime,
+    threshold: Duration,
+) -> Option<(Duration, i8)> {
+    let (offset, sign) = match rtc.duration_since(now) {
+        Ok(ahead) => (ahead, 1),
+        Err(e) => (e.duration(), -1),
+    };
+    (offset > threshold).then_some((offset, sign))
+}
+
+/// Pre-seed the system clock from the RTC if it is off by more than `threshold`.
+/// Returns true if the clock was stepped. Never fails startup: problems are logged.
+pub fn cold_start<C: SystemClock>(clock: &mut C, threshold: Duration) -> bool {
+    let rtc = match read_rtc() {
+        Ok(t) => t,
+        Err(e) => {
+            warn!("[RTC] Cold start skipped: {}", e);
+            return false;
+        }
+    };
+    let Some((offset, sign)) = cold_start_step(rtc, SystemTime::now(), threshold) else {
+        info!("[RTC] System clock agrees with RTC - no cold start step");
+        return false;
+    };
+
+    let sign_str = if sign > 0 { "+" } else { "-" };
+    info!(
+        "[RTC] Cold start: system clock off by {}{:?} - seeding from RTC",
+        sign_str, offset
+    );
+    match clock.step_clock(offset, sign) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("[RTC] Cold start step failed: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rtc_time_to_system_time() {
+        // 2024-03-01 12:30:45 UTC
+        let tm = RtcTime {
+            tm_sec: 45,
+            tm_min: 30,
+            tm_hour: 12,
+            tm_mday: 1,
+            tm_mon: 2,
+            tm_year: 124,
+            ..Default::default()
+        };
+        let secs = tm
+            .to_system_time()
+            .unwrap()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert_eq!(secs, 1_709_296_245);
+
+        let invalid = RtcTime { tm_mon: 12, ..tm };
+        assert!(invalid.to_system_time().is_err());
+    }
+
+    #[test]
+    fn test_cold_start_step_direction_and_threshold() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let threshold = Duration::from_secs(60);
+
+        // Clock booted at 1970 + 1000s, RTC knows better: step forward
+        let rtc = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let (offset, sign) = cold_start_step(rtc, now, threshold).unwrap();
+        assert_eq!(sign, 1);
+        assert_eq!(offset, Duration::from_secs(1_700_000_000 - 1_000));
+
+        // System clock ahead of RTC: step back
+        let (_, sign) = cold_start_step(now, rtc, threshold).unwrap();
+        assert_eq!(sign, -1);
+
+        // Within threshold (RTC has 1s resolution): leave to NTP
+        assert_eq!(
+            cold_start_step(now + Duration::from_secs(30), now, threshold),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_rtc_without_device_does_not_panic() {
+        // Containers usually have no /dev/rtc - must be a clean error, not a panic
+        if let Ok(t) = read_rtc() {
+            assert!(t > UNIX_EPOCH);
+        }
+        if let Ok(offset) = system_offset_ms() {
+            assert!(offset.abs() < i64::MAX);
+        }
+    }
+
+    #[test]
+    fn test_offset_ms_sign() {
+        let rtc = UNIX_EPOCH + Duration::from_secs(1_000);
+        assert_eq!(offset_ms(rtc + Duration::from_millis(1_500), rtc), 1_500);
+        assert_eq!(offset_ms(rtc - Duration::from_secs(3_600), rtc), -3_600_000);
+    }
+}
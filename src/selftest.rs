@@ -0,0 +1,289 @@
+//! Built-in end-to-end self-check (`--selftest`).
+//!
+//! A transmitter thread encodes Sync/FollowUp with the same encoder as `ptpgen`,
+//! from a master clock running a known `drift_ppm` faster than the local clock, and
+//! hands them to a real `PtpController` over an in-process loopback network. The
+//! local clock is simulated: frequency corrections change how fast T2 advances,
+//! steps shift it, and the real system clock is never touched.
+//!
+//! The check passes when the initial NTP sync steps by exactly the injected offset
+//! and the servo locks with its frequency correction at the injected drift.
+
+use crate::clock::SystemClock;
+use crate::config::SystemConfig;
+use crate::controller::PtpController;
+use crate::ptp::{self, PtpTimestamp};
+use crate::status::SyncStatus;
+use crate::traits::{NtpSource, PtpNetwork};
+use anyhow::Result;
+use std::net::Ipv4Addr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Master uptime at start (ns) - Dante PTP carries uptime, not UTC
+const MASTER_START_NS: i64 = 1_000_000_000_000;
+/// Simulated local clock at start (ns since the Unix epoch)
+const LOCAL_START_NS: i64 = 1_700_000_000_000_000_000;
+const SELFTEST_UUID: [u8; 6] = [0x00, 0x1D, 0xC1, 0x5E, 0x1F, 0x00];
+/// Lock and frequency must hold this long before the check passes
+const PASS_HOLD: Duration = Duration::from_secs(3);
+/// Step must match the injected NTP offset this closely
+const STEP_TOLERANCE_NS: i64 = 1_000;
+
+#[derive(Debug, Clone)]
+pub struct SelftestParams {
+    /// Master frequency offset vs the local clock (PPM)
+    pub drift_ppm: f64,
+    /// Offset the simulated NTP server reports at startup (ns, positive = local behind)
+    pub ntp_offset_ns: i64,
+    /// Sync messages per second
+    pub rate_hz: f64,
+    /// Recovered frequency must be within this of `drift_ppm`
+    pub tolerance_ppm: f64,
+    /// Give up (FAIL) after this long
+    pub timeout: Duration,
+}
+
+impl Default for SelftestParams {
+    fn default() -> Self {
+        Self {
+            drift_ppm: 25.0,
+            ntp_offset_ns: 250_000_000,
+            rate_hz: 8.0,
+            tolerance_ppm: 1.0,
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SelftestReport {
+    pub expected_step_ns: i64,
+    pub stepped_ns: i64,
+    pub expected_ppm: f64,
+    pub recovered_ppm: f64,
+    pub locked: bool,
+    pub elapsed: Duration,
+    pub passed: bool,
+}
+
+impl SelftestReport {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} | step {:+.3}ms (injected {:+.3}ms) | frequency {:+.3}ppm (injected {:+.3}ppm) | {} after {:.1}s",
+            if self.passed { "PASS" } else { "FAIL" },
+            self.stepped_ns as f64 / 1e6,
+            self.expected_step_ns as f64 / 1e6,
+            self.recovered_ppm,
+            self.expected_ppm,
+            if self.locked { "locked" } else { "not locked" },
+            self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+/// Local clock that runs on real elapsed time, scaled by the applied frequency.
+#[derive(Debug)]
+struct SimLocalClock {
+    origin: Instant,
+    last_real: Duration,
+    local_ns: f64,
+    adj_ppm: f64,
+    stepped_ns: i64,
+}
+
+impl SimLocalClock {
+    fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            last_real: Duration::ZERO,
+            local_ns: LOCAL_START_NS as f64,
+            adj_ppm: 0.0,
+            stepped_ns: 0,
+        }
+    }
+
+    /// Advance to the current real time and return the local time (ns).
+    fn now_ns(&mut self) -> i64 {
+        let real = self.origin.elapsed();
+        let dt_ns = (real - self.last_real).as_nanos() as f64;
+        self.local_ns += dt_ns * (1.0 + self.adj_ppm / 1e6);
+        self.last_real = real;
+        self.local_ns as i64
+    }
+}
+
+#[derive(Clone)]
+struct SharedSimClock(Arc<Mutex<SimLocalClock>>);
+
+impl SystemClock for SharedSimClock {
+    fn adjust_frequency(&mut self, factor: f64) -> Result<()> {
+        let mut clock = self.0.lock().unwrap();
+        clock.now_ns();
+        clock.adj_ppm = (factor - 1.0) * 1e6;
+        Ok(())
+    }
+
+    fn step_clock(&mut self, offset: Duration, sign: i8) -> Result<()> {
+        let mut clock = self.0.lock().unwrap();
+        let ns = offset.as_nanos() as i64 * sign as i64;
+        clock.now_ns();
+        clock.local_ns += ns as f64;
+        clock.stepped_ns += ns;
+        Ok(())
+    }
+}
+
+/// NTP server that knows the true offset: what was injected minus what was stepped.
+struct SimNtp {
+    clock: SharedSimClock,
+    injected_ns: i64,
+}
+
+impl NtpSource for SimNtp {
+    fn get_offset(&self) -> Result<(Duration, i8)> {
+        let remaining = self.injected_ns - self.clock.0.lock().unwrap().stepped_ns;
+        let sign = if remaining >= 0 { 1 } else { -1 };
+        Ok((Duration::from_nanos(remaining.unsigned_abs()), sign))
+    }
+}
+
+/// Receives what the transmitter thread sends; T2 is taken on the simulated clock.
+struct LoopbackNetwork {
+    rx: Receiver<(Vec<u8>, SystemTime)>,
+}
+
+impl PtpNetwork for LoopbackNetwork {
+    fn recv_packet(&mut self) -> Result<Option<(Vec<u8>, usize, SystemTime, Option<Ipv4Addr>)>> {
+        match self.rx.recv_timeout(Duration::from_millis(10)) {
+            Ok((buf, t2)) => {
+                let len = buf.len();
+                Ok(Some((buf, len, t2, Some(Ipv4Addr::LOCALHOST))))
+            }
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => Ok(None),
+        }
+    }
+}
+
+fn to_system_time(ns: i64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_nanos(ns.max(0) as u64)
+}
+
+/// Transmit two-step Sync/FollowUp pairs at `rate_hz` until `stop` is set.
+fn spawn_transmitter(
+    clock: SharedSimClock,
+    drift_ppm: f64,
+    rate_hz: f64,
+    tx: mpsc::Sender<(Vec<u8>, SystemTime)>,
+    stop: Arc<AtomicBool>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let interval = Duration::from_secs_f64(1.0 / rate_hz);
+        let start = Instant::now();
+        let mut seq: u16 = 0;
+        let mut sent: u32 = 0;
+        while !stop.load(Ordering::SeqCst) {
+            let due = start + interval * sent;
+            if let Some(wait) = due.checked_duration_since(Instant::now()) {
+                thread::sleep(wait);
+            }
+
+            let elapsed_ns = start.elapsed().as_nanos() as f64;
+            let t1 = PtpTimestamp::from_nanos(
+                MASTER_START_NS + (elapsed_ns * (1.0 + drift_ppm / 1e6)) as i64,
+            );
+            let t2 = to_system_time(clock.0.lock().unwrap().now_ns());
+
+            let sync = ptp::encode_sync(SELFTEST_UUID, seq, t1, true);
+            let follow_up = ptp::encode_follow_up(SELFTEST_UUID, seq, seq, t1);
+            if tx.send((sync, t2)).is_err() || tx.send((follow_up, t2)).is_err() {
+                break;
+            }
+
+            seq = seq.wrapping_add(1);
+            sent += 1;
+        }
+    })
+}
+
+/// Run the self-check with `config` (the effective servo configuration of this build).
+pub fn run(mut config: SystemConfig, params: &SelftestParams) -> Result<SelftestReport> {
+    // The simulated clock never touches the host clock the audit measures
+    config.clock.rate_audit_secs = 0;
+    let clock = SharedSimClock(Arc::new(Mutex::new(SimLocalClock::new())));
+    let (tx, rx) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let transmitter = spawn_transmitter(
+        clock.clone(),
+        params.drift_ppm,
+        params.rate_hz,
+        tx,
+        stop.clone(),
+    );
+
+    let status = Arc::new(RwLock::new(SyncStatus::default()));
+    let ntp = SimNtp {
+        clock: clock.clone(),
+        injected_ns: params.ntp_offset_ns,
+    };
+    let mut controller = PtpController::new(
+        clock.clone(),
+        LoopbackNetwork { rx },
+        ntp,
+        status.clone(),
+        config,
+    );
+    controller.run_ntp_sync(false);
+
+    let start = Instant::now();
+    let mut passing_since: Option<Instant> = None;
+    let mut last_status = Instant::now();
+    let (mut locked, mut recovered_ppm) = (false, 0.0);
+    let outcome = loop {
+        if let Err(e) = controller.process_loop_iteration() {
+            break Err(e);
+        }
+        if last_status.elapsed() < Duration::from_millis(250) {
+            continue;
+        }
+        last_status = Instant::now();
+        controller.log_status();
+        if let Ok(s) = status.read() {
+            locked = s.is_locked;
+            recovered_ppm = s.freq_adj_ppm;
+        }
+
+        let on_target = locked && (recovered_ppm - params.drift_ppm).abs() <= params.tolerance_ppm;
+        passing_since = if on_target {
+            passing_since.or(Some(Instant::now()))
+        } else {
+            None
+        };
+        if passing_since.is_some_and(|t| t.elapsed() >= PASS_HOLD) {
+            break Ok(true);
+        }
+        if start.elapsed() >= params.timeout {
+            break Ok(false);
+        }
+    };
+
+    stop.store(true, Ordering::SeqCst);
+    drop(controller); // disconnects the channel so the transmitter exits
+    let _ = transmitter.join();
+
+    let frequency_ok = outcome?;
+    let stepped_ns = clock.0.lock().unwrap().stepped_ns;
+    let step_ok = (stepped_ns - params.ntp_offset_ns).abs() <= STEP_TOLERANCE_NS;
+    Ok(SelftestReport {
+        expected_step_ns: params.ntp_offset_ns,
+        stepped_ns,
+        expected_ppm: params.drift_ppm,
+        recovered_ppm,
+        locked,
+        elapsed: start.elapsed(),
+        passed: frequency_ok && step_ok,
+    })
+}
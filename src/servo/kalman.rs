@@ -0,0 +1,266 @@
+//! Two-state Kalman filter discipline.
+//!
+//! State: the offset's phase (µs) and the oscillator's own drift (ppm = µs/s).
+//! Between samples the phase advances by drift + the correction we applied, so
+//! the filter separates the drift from the timestamp noise instead of reacting
+//! to each jittery offset. The correction cancels the estimated drift; phase is
+//! estimated but not steered, as in the PI servo (UTC alignment is NTP's job).
+//!
+//! An offset far outside the prediction (NTP step, grandmaster restart, phase
+//! wrap) re-anchors the phase without touching the drift estimate.
+
+use super::Discipline;
+use crate::config::ServoConfig;
+use std::time::Duration;
+
+/// Uncertainty of the drift before the first samples (stddev, ppm)
+const INITIAL_DRIFT_SIGMA_PPM: f64 = 100.0;
+/// Innovations beyond this many standard deviations re-anchor the phase
+const GATE_SIGMAS: f64 = 6.0;
+/// ...but never smaller jumps (µs): with `kalman_measurement_noise_ns` set too
+/// low, jitter would otherwise re-anchor every sample and freeze the drift
+const MIN_STEP_US: f64 = 1_000.0;
+
+#[derive(Debug)]
+pub struct KalmanServo {
+    /// Phase of the offset (µs); None until the first sample
+    phase_us: Option<f64>,
+    /// Oscillator drift without correction (ppm)
+    drift_ppm: f64,
+    /// State covariance, [phase, drift]
+    p: [[f64; 2]; 2],
+    /// Frequency random walk (ppm²/s)
+    q: f64,
+    /// Measurement variance (µs²)
+    r: f64,
+    max_ppm: f64,
+    /// Correction applied since the last sample (ppm)
+    output_ppm: f64,
+    /// Samples that re-anchored the phase
+    reanchors: u64,
+}
+
+impl KalmanServo {
+    pub fn new(config: &ServoConfig, initial_ppm: f64) -> Self {
+        let noise_us = config.kalman_measurement_noise_ns / 1000.0;
+        let max_ppm = config.max_freq_adj_ppm;
+        let output_ppm = initial_ppm.clamp(-max_ppm, max_ppm);
+        Self {
+            phase_us: None,
+            drift_ppm: -output_ppm,
+            p: [[0.0, 0.0], [0.0, INITIAL_DRIFT_SIGMA_PPM.powi(2)]],
+            q: config.kalman_process_noise_ppm.powi(2),
+            r: noise_us.powi(2).max(f64::MIN_POSITIVE),
+            max_ppm,
+            output_ppm,
+            reanchors: 0,
+        }
+    }
+
+    /// Key parameters, e.g. for comparing machines from their status.
+    pub fn params(config: &ServoConfig) -> String {
+        format!(
+            "process_noise={}ppm/sqrt(s) measurement_noise={}ns",
+            config.kalman_process_noise_ppm, config.kalman_measurement_noise_ns
+        )
+    }
+
+    /// Estimated oscillator drift (ppm).
+    pub fn drift_ppm(&self) -> f64 {
+        self.drift_ppm
+    }
+
+    pub fn reanchors(&self) -> u64 {
+        self.reanchors
+    }
+
+    fn anchor_phase(&mut self, phase_us: f64) {
+        self.phase_us = Some(phase_us);
+        self.p[0][0] = self.r;
+        self.p[0][1] = 0.0;
+        self.p[1][0] = 0.0;
+    }
+}
+
+impl Discipline for KalmanServo {
+    fn sample(&mut self, offset_ns: i64, dt: Duration) -> f64 {
+        let z = offset_ns as f64 / 1000.0;
+        let Some(phase) = self.phase_us else {
+            self.anchor_phase(z);
+            return self.output_ppm;
+        };
+
+        // Predict: phase moves by drift + applied correction; drift random-walks
+        let dt = dt.as_secs_f64();
+        let phase = phase + (self.drift_ppm + self.output_ppm) * dt;
+        let [[p00, p01], [p10, p11]] = self.p;
+        let q = self.q;
+        let p00 = p00 + dt * (p01 + p10) + dt * dt * p11 + q * dt.powi(3) / 3.0;
+        let p01 = p01 + dt * p11 + q * dt * dt / 2.0;
+        let p10 = p10 + dt * p11 + q * dt * dt / 2.0;
+        let p11 = p11 + q * dt;
+
+        // Update with the measured phase
+        let innovation = z - phase;
+        let s = p00 + self.r;
+        if innovation.abs() > (GATE_SIGMAS * s.sqrt()).max(MIN_STEP_US) {
+            self.reanchors += 1;
+            self.p = [[p00, p01], [p10, p11]];
+            self.anchor_phase(z);
+            return self.output_ppm;
+        }
+        let k0 = p00 / s;
+        let k1 = p10 / s;
+        self.phase_us = Some(phase + k0 * innovation);
+        self.drift_ppm += k1 * innovation;
+        self.p = [
+            [(1.0 - k0) * p00, (1.0 - k0) * p01],
+            [p10 - k1 * p00, p11 - k1 * p01],
+        ];
+
+        self.output_ppm = (-self.drift_ppm).clamp(-self.max_ppm, self.max_ppm);
+        self.output_ppm
+    }
+
+    fn reset(&mut self) {
+        // Re-anchored on the next sample; the drift estimate stays
+        self.phase_us = None;
+    }
+
+    fn baseline_ppm(&self) -> f64 {
+        self.output_ppm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ServoAlgorithm, SystemConfig};
+    use crate::servo::{self, Discipline};
+
+    /// Deterministic, roughly Gaussian noise with the given stddev
+    struct Jitter(u64, f64);
+
+    impl Jitter {
+        fn next(&mut self) -> f64 {
+            // Sum of 3 uniforms in [-1, 1) has variance 1
+            (0..3)
+                .map(|_| {
+                    self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    (self.0 >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+                })
+                .sum::<f64>()
+                * self.1
+        }
+    }
+
+    fn servo(measurement_noise_ns: f64) -> KalmanServo {
+        let mut config = SystemConfig::default().servo;
+        config.kalman_measurement_noise_ns = measurement_noise_ns;
+        KalmanServo::new(&config, 0.0)
+    }
+
+    /// Closed loop at 1 sample/s against an oscillator `drift_ppm` off, with
+    /// `jitter_us` of timestamp noise. Returns the residual frequency error
+    /// (drift + correction) after each sample.
+    fn settle(
+        servo: &mut dyn Discipline,
+        drift_ppm: f64,
+        jitter_us: f64,
+        samples: usize,
+    ) -> Vec<f64> {
+        let mut jitter = Jitter(42, jitter_us);
+        let mut phase_us = 0.0;
+        let mut correction = 0.0;
+        (0..samples)
+            .map(|_| {
+                let measured = phase_us + jitter.next();
+                correction = servo.sample((measured * 1000.0) as i64, Duration::from_secs(1));
+                phase_us += drift_ppm + correction;
+                drift_ppm + correction
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_settles_to_drift_under_jitter() {
+        let mut kalman = servo(50_000.0);
+        let residual = settle(&mut kalman, 25.0, 50.0, 300);
+
+        assert!(residual[0].abs() > 20.0, "starts from 0ppm");
+        let worst = residual[150..].iter().fold(0.0f64, |m, r| m.max(r.abs()));
+        assert!(worst < 1.0, "settled within 1ppm: {:.3}", worst);
+        assert!((kalman.drift_ppm() - 25.0).abs() < 1.0);
+        assert_eq!(kalman.reanchors(), 0, "jitter is not mistaken for steps");
+    }
+
+    #[test]
+    fn test_realistic_measurement_noise_averages_jitter() {
+        // Same jittery sequence: trusting each offset follows the jitter, a
+        // noise setting matching it averages the jitter out
+        let eager = settle(&mut servo(1_000.0), 25.0, 50.0, 300);
+        let smooth = settle(&mut servo(50_000.0), 25.0, 50.0, 300);
+
+        assert!(
+            eager[150..].iter().all(|r| r.abs() < 5.0),
+            "still converges"
+        );
+        assert!(
+            rms(&smooth[150..]) < rms(&eager[150..]) / 3.0,
+            "steady-state error {:.3}ppm vs {:.3}ppm",
+            rms(&smooth[150..]),
+            rms(&eager[150..])
+        );
+    }
+
+    fn rms(r: &[f64]) -> f64 {
+        (r.iter().map(|x| x * x).sum::<f64>() / r.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn test_settles_closer_than_pi_under_jitter() {
+        // Same loop, same jitter sequence, both built the way the controller does
+        let run = |algorithm| {
+            let mut config = SystemConfig::default().servo;
+            config.algorithm = algorithm;
+            config.kalman_measurement_noise_ns = 50_000.0;
+            settle(&mut *servo::from_config(&config, 0.0), 25.0, 50.0, 300)
+        };
+        let pi = run(ServoAlgorithm::Pi);
+        let kalman = run(ServoAlgorithm::Kalman);
+
+        assert!(
+            rms(&kalman[150..]) < rms(&pi[150..]) / 10.0,
+            "settled error kalman {:.3}ppm vs pi {:.3}ppm",
+            rms(&kalman[150..]),
+            rms(&pi[150..])
+        );
+    }
+
+    #[test]
+    fn test_phase_step_keeps_drift_estimate() {
+        let mut kalman = servo(10_000.0);
+        let mut phase_us = 0.0;
+        let mut correction = 0.0;
+        for _ in 0..200 {
+            correction = kalman.sample((phase_us * 1000.0) as i64, Duration::from_secs(1));
+            phase_us += 30.0 + correction;
+        }
+        let before = correction;
+
+        // The clock is stepped by 5ms between two samples
+        phase_us += 5_000.0;
+        let after = kalman.sample((phase_us * 1000.0) as i64, Duration::from_secs(1));
+
+        assert_eq!(kalman.reanchors(), 1);
+        assert_eq!(after, before, "frequency untouched by the step");
+        assert!((after + 30.0).abs() < 0.1);
+
+        // Same after an explicit reset
+        kalman.reset();
+        phase_us -= 5_000.0;
+        let after = kalman.sample((phase_us * 1000.0) as i64, Duration::from_secs(1));
+        assert_eq!(after, before);
+        assert_eq!(kalman.reanchors(), 1, "reset, not a detected step");
+    }
+}
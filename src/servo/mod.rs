@@ -0,0 +1,68 @@
+//! Frequency discipline algorithms.
+//!
+//! The controller measures and filters the offsets and supervises acquisition
+//! and lock; the [`Discipline`] selected by `servo.algorithm` turns each
+//! filtered offset into a frequency correction. The default is the rate-based
+//! PI servo ([`PiServo`]).
+
+use crate::config::{ServoAlgorithm, ServoConfig};
+use std::time::Duration;
+
+mod kalman;
+mod pi;
+pub use self::kalman::KalmanServo;
+pub use self::pi::PiServo;
+
+/// Status name of the PI discipline (`servo.algorithm = "pi"`)
+pub const PI_ALGORITHM: &str = "rate-pi";
+/// Status name of the Kalman discipline (`servo.algorithm = "kalman"`)
+pub const KALMAN_ALGORITHM: &str = "kalman";
+
+/// Where the controller's supervision stands, handed to the discipline before
+/// each sample. The PI picks its gains from it; the Kalman filter ignores it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServoStage {
+    /// Rate stable within 5µs/s (production gains)
+    pub production: bool,
+    pub locked: bool,
+    /// Locked with a sustained sub-µs/s rate
+    pub nano: bool,
+    /// Sequenced acquisition, frequency-only stage
+    pub frequency_only: bool,
+}
+
+#[cfg_attr(test, mockall::automock)]
+pub trait Discipline {
+    /// Feed one filtered offset (local - reference, ns), measured `dt` after the
+    /// previous one. Returns the frequency correction (ppm, positive = speed the
+    /// local clock up), which the caller applies until the next sample.
+    fn sample(&mut self, offset_ns: i64, dt: Duration) -> f64;
+
+    /// Forget the measurement history (clock step, source change, grandmaster
+    /// restart), keeping the learned frequency.
+    fn reset(&mut self);
+
+    /// Learned frequency correction without transient terms (ppm): the drift
+    /// baseline reported in the status and kept in the drift file.
+    fn baseline_ppm(&self) -> f64;
+
+    /// Supervision state for the next sample.
+    fn set_stage(&mut self, _stage: ServoStage) {}
+}
+
+/// The discipline selected by `servo.algorithm`, starting from a correction of
+/// `initial_ppm`.
+pub fn from_config(config: &ServoConfig, initial_ppm: f64) -> Box<dyn Discipline + Send> {
+    match config.algorithm {
+        ServoAlgorithm::Pi => Box::new(PiServo::new(config, initial_ppm)),
+        ServoAlgorithm::Kalman => Box::new(KalmanServo::new(config, initial_ppm)),
+    }
+}
+
+/// Name and key parameters of the algorithm selected by `servo.algorithm`.
+pub fn describe(config: &ServoConfig) -> (&'static str, String) {
+    match config.algorithm {
+        ServoAlgorithm::Pi => (PI_ALGORITHM, PiServo::params(config)),
+        ServoAlgorithm::Kalman => (KALMAN_ALGORITHM, KalmanServo::params(config)),
+    }
+}
@@ -0,0 +1,293 @@
+//! Rate-based PI servo ("rate-pi"), the default discipline.
+//!
+//! Dante PTP timestamps are device uptime, not UTC, so the absolute offset means
+//! nothing; what matters is its RATE OF CHANGE. A stable offset means matched
+//! frequencies, a growing one a local clock that is too fast. The servo
+//! differentiates the offsets, rejects spikes (MAD filter), smooths the rate with
+//! a jitter-adaptive EMA and runs a PI on it:
+//!
+//! - P-term: answers the current rate error, with gains by stage (aggressive
+//!   while acquiring, gentle in production, tiny with a deadband in NANO):
+//!   `servo.kp`, `servo.kp_prod`, `servo.kp_nano`
+//! - I-term: integrates the rate error into the drift baseline, the auto-learned
+//!   natural drift of the oscillator (`servo.ki`, `servo.ki_nano`), held within
+//!   `servo.max_integral_ppm`
+//! - D-term (`servo.kd`, 0 = off): brakes while the rate error is shrinking
+
+use super::{Discipline, ServoStage};
+use crate::config::ServoConfig;
+use crate::spike_filter::{FilterMode, JitterEstimator, SpikeFilter};
+use std::time::Duration;
+
+// P-term limits by stage (gains come from `servo`)
+const P_MAX_ACQ_PPM: f64 = 200.0; // Limit to prevent wild swings
+const P_MAX_PROD_PPM: f64 = 100.0; // Allow enough for high drift rates
+const P_MAX_NANO_PPM: f64 = 10.0; // Tiny corrections only
+
+const NANO_DEADBAND_US: f64 = 0.1; // Ignore drift < 0.1 µs/s (noise floor)
+
+// Sequenced acquisition: direct drift learning while the P-term is frozen
+const SEQ_FREQ_GAIN: f64 = 0.3;
+
+// Rates beyond this (µs/s) are clamped before filtering
+const MAX_RATE_PPM: f64 = 500.0;
+
+#[derive(Debug)]
+pub struct PiServo {
+    stage: ServoStage,
+    kp_acq: f64,
+    kp_prod: f64,
+    kp_nano: f64,
+    ki: f64,
+    ki_nano: f64,
+    kd: f64,
+    max_ppm: f64,
+    /// Limit on the learned drift (ppm)
+    max_integral_ppm: f64,
+    /// Integrated rate error: the learned drift (ppm)
+    baseline_ppm: f64,
+    prev_offset_ns: Option<i64>,
+    spike_filter: SpikeFilter,
+    jitter: JitterEstimator,
+    /// Smoothed rate the gains act on (µs/s)
+    rate_ppm: f64,
+    /// Rate error of the previous sample (D-term)
+    prev_rate_ppm: Option<f64>,
+}
+
+impl PiServo {
+    pub fn new(config: &ServoConfig, initial_ppm: f64) -> Self {
+        let max_ppm = config.max_freq_adj_ppm;
+        let max_integral_ppm = config.max_integral_ppm.min(max_ppm);
+        Self {
+            stage: ServoStage::default(),
+            kp_acq: config.kp,
+            kp_prod: config.kp_prod,
+            kp_nano: config.kp_nano,
+            ki: config.ki,
+            ki_nano: config.ki_nano,
+            kd: config.kd,
+            max_ppm,
+            max_integral_ppm,
+            baseline_ppm: initial_ppm.clamp(-max_integral_ppm, max_integral_ppm),
+            prev_offset_ns: None,
+            spike_filter: SpikeFilter::new(),
+            jitter: JitterEstimator::new(),
+            rate_ppm: 0.0,
+            prev_rate_ppm: None,
+        }
+    }
+
+    /// Key parameters, e.g. for comparing machines from their status.
+    pub fn params(config: &ServoConfig) -> String {
+        format!(
+            "acq kp={} ki={} | prod kp={} ki={} | nano kp={} ki={} deadband={}us/s | max integral={}ppm",
+            config.kp,
+            config.ki,
+            config.kp_prod,
+            config.ki,
+            config.kp_nano,
+            config.ki_nano,
+            NANO_DEADBAND_US,
+            config.max_integral_ppm.min(config.max_freq_adj_ppm)
+        )
+    }
+
+    /// P and I gain the next sample uses, from the current stage.
+    pub fn gains(&self) -> (f64, f64) {
+        if self.stage.frequency_only {
+            // Sequenced acquisition: frequency-only stage freezes the P-term
+            (0.0, SEQ_FREQ_GAIN)
+        } else if self.stage.nano {
+            (self.kp_nano, self.ki_nano)
+        } else if self.stage.production {
+            (self.kp_prod, self.ki)
+        } else {
+            (self.kp_acq, self.ki)
+        }
+    }
+
+    fn filter_mode(&self) -> FilterMode {
+        if self.stage.nano {
+            FilterMode::Nano
+        } else if self.stage.locked {
+            FilterMode::Lock
+        } else if self.stage.production {
+            FilterMode::Prod
+        } else {
+            FilterMode::Acq
+        }
+    }
+}
+
+impl Discipline for PiServo {
+    fn sample(&mut self, offset_ns: i64, dt: Duration) -> f64 {
+        let dt_secs = dt.as_secs_f64();
+        let raw_rate_ppm = match self.prev_offset_ns {
+            // ns per s / 1000 = µs/s = ppm; needs a meaningful time delta
+            Some(prev) if dt_secs > 0.1 => {
+                ((offset_ns - prev) as f64 / 1000.0 / dt_secs).clamp(-MAX_RATE_PPM, MAX_RATE_PPM)
+            }
+            Some(_) => self.rate_ppm,
+            None => 0.0,
+        };
+        self.prev_offset_ns = Some(offset_ns);
+
+        let filtered_rate_ppm = self
+            .spike_filter
+            .filter(raw_rate_ppm, self.filter_mode())
+            .value;
+        let alpha = self.jitter.add_sample(filtered_rate_ppm);
+        self.rate_ppm = self.rate_ppm * (1.0 - alpha) + filtered_rate_ppm * alpha;
+        let rate_ppm = self.rate_ppm;
+
+        let p_max = if self.stage.nano {
+            P_MAX_NANO_PPM
+        } else if self.stage.production {
+            P_MAX_PROD_PPM
+        } else {
+            P_MAX_ACQ_PPM
+        };
+        let (p_gain, i_gain) = self.gains();
+
+        // NANO: don't correct tiny rates (noise)
+        let effective_rate = if self.stage.nano && rate_ppm.abs() < NANO_DEADBAND_US {
+            0.0
+        } else {
+            rate_ppm
+        };
+
+        // Negative rate = clock too slow, need positive adjustment
+        let p_term = (-effective_rate * p_gain).clamp(-p_max, p_max);
+
+        // Uses the real interval - Sync spacing varies
+        let kd = self.kd;
+        let d_term = match self.prev_rate_ppm {
+            Some(prev) if kd != 0.0 && dt_secs > 0.0 => {
+                (-kd * (effective_rate - prev) / dt_secs).clamp(-p_max, p_max)
+            }
+            _ => 0.0,
+        };
+        self.prev_rate_ppm = Some(effective_rate);
+
+        let i_term = -effective_rate * i_gain;
+        self.baseline_ppm =
+            (self.baseline_ppm + i_term).clamp(-self.max_integral_ppm, self.max_integral_ppm);
+
+        (self.baseline_ppm + p_term + d_term).clamp(-self.max_ppm, self.max_ppm)
+    }
+
+    fn reset(&mut self) {
+        self.prev_offset_ns = None;
+        // Rate before and after a reset are unrelated - no derivative across it
+        self.prev_rate_ppm = None;
+        self.spike_filter.clear();
+    }
+
+    fn baseline_ppm(&self) -> f64 {
+        self.baseline_ppm
+    }
+
+    fn set_stage(&mut self, stage: ServoStage) {
+        self.stage = stage;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SystemConfig;
+
+    fn servo() -> PiServo {
+        PiServo::new(&SystemConfig::default().servo, 0.0)
+    }
+
+    #[test]
+    fn test_nano_deadband_constant() {
+        assert!(
+            (NANO_DEADBAND_US - 0.1).abs() < 0.001,
+            "NANO deadband should be 0.1 µs/s"
+        );
+    }
+
+    #[test]
+    fn test_learns_drift_from_offset_rate() {
+        let mut pi = servo();
+        // Clock 20ppm slow: the offset falls 20µs per second
+        let mut offset_us = 0.0;
+        let mut correction = 0.0;
+        for _ in 0..200 {
+            correction = pi.sample((offset_us * 1000.0) as i64, Duration::from_secs(1));
+            offset_us += -20.0 + correction;
+        }
+        assert!((correction - 20.0).abs() < 0.5, "{}", correction);
+        assert!((pi.baseline_ppm() - 20.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_frequency_only_stage_freezes_p_term() {
+        let mut pi = servo();
+        pi.set_stage(ServoStage {
+            frequency_only: true,
+            ..Default::default()
+        });
+        pi.sample(0, Duration::from_secs(1));
+        let correction = pi.sample(-10_000, Duration::from_secs(1));
+        assert_eq!(correction, pi.baseline_ppm(), "no P-term");
+        assert!(correction > 0.0);
+    }
+
+    #[test]
+    fn test_gains_come_from_config() {
+        let mut config = SystemConfig::default().servo;
+        config.kp = 0.4;
+        config.ki = 0.02;
+        config.kp_prod = 0.07;
+        config.kp_nano = 0.003;
+        config.ki_nano = 0.001;
+        let mut pi = PiServo::new(&config, 0.0);
+        assert_eq!(pi.gains(), (0.4, 0.02));
+
+        pi.set_stage(ServoStage {
+            production: true,
+            ..Default::default()
+        });
+        assert_eq!(pi.gains(), (0.07, 0.02));
+
+        pi.set_stage(ServoStage {
+            production: true,
+            locked: true,
+            nano: true,
+            ..Default::default()
+        });
+        assert_eq!(pi.gains(), (0.003, 0.001));
+        assert!(PiServo::params(&config).starts_with("acq kp=0.4 ki=0.02"));
+    }
+
+    #[test]
+    fn test_learned_drift_held_within_max_integral() {
+        let mut config = SystemConfig::default().servo;
+        config.max_integral_ppm = 30.0;
+        let mut pi = PiServo::new(&config, 50.0);
+        assert_eq!(pi.baseline_ppm(), 30.0);
+
+        // Clock 80ppm slow: the I-term would learn 80ppm
+        let mut offset_us = 0.0;
+        for _ in 0..200 {
+            let correction = pi.sample((offset_us * 1000.0) as i64, Duration::from_secs(1));
+            offset_us += -80.0 + correction;
+        }
+        assert_eq!(pi.baseline_ppm(), 30.0);
+    }
+
+    #[test]
+    fn test_reset_keeps_learned_frequency() {
+        let mut pi = PiServo::new(&SystemConfig::default().servo, 12.0);
+        pi.sample(0, Duration::from_secs(1));
+        pi.reset();
+        // The offset jumped across the reset: not a rate
+        let correction = pi.sample(5_000_000, Duration::from_secs(1));
+        assert_eq!(correction, 12.0);
+        assert_eq!(pi.baseline_ppm(), 12.0);
+    }
+}
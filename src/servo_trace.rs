@@ -0,0 +1,141 @@
+//! Per-sample servo internals export (CSV) for offline tuning.
+//!
+//! Enabled with `--servo-trace <FILE>`. One row is written each time the servo runs
+//! (once per sample window); nothing is computed or written when disabled.
+//!
+//! Columns:
+//!
+//! | Column | Unit | Meaning |
+//! |---|---|---|
+//! | `t_s` | s | Time since the trace was started |
+//! | `mode` | - | ACQ / PROD / LOCK / NANO |
+//! | `offset_us` | µs | Median phase offset of the window (servo input) |
+//! | `raw_rate_ppm` | µs/s | Offset rate of change before filtering (the error) |
+//! | `filtered_rate_ppm` | µs/s | Rate after the spike filter |
+//! | `spike` | 0/1 | Raw rate was rejected as a spike |
+//! | `rate_ppm` | µs/s | Smoothed rate the gains act on |
+//! | `p_term_ppm` | ppm | Transient correction on top of the baseline (P-term, plus the D-term with `servo.kd`) |
+//! | `i_term_ppm` | ppm | Integral increment added to the drift baseline this sample |
+//! | `drift_baseline_ppm` | ppm | Integrated drift estimate |
+//! | `output_ppm` | ppm | Servo output (baseline + `p_term_ppm`) |
+//! | `factor` | - | Frequency factor handed to the clock (includes slew bias) |
+
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+pub const CSV_HEADER: &str = "t_s,mode,offset_us,raw_rate_ppm,filtered_rate_ppm,spike,rate_ppm,\
+p_term_ppm,i_term_ppm,drift_baseline_ppm,output_ppm,factor";
+
+/// Servo state for one sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServoTraceRecord {
+    pub mode: &'static str,
+    pub offset_us: f64,
+    pub raw_rate_ppm: f64,
+    pub filtered_rate_ppm: f64,
+    pub spike: bool,
+    pub rate_ppm: f64,
+    pub p_term_ppm: f64,
+    pub i_term_ppm: f64,
+    pub drift_baseline_ppm: f64,
+    pub output_ppm: f64,
+    pub factor: f64,
+}
+
+pub struct ServoTrace {
+    out: Box<dyn Write + Send>,
+    start: Instant,
+}
+
+impl ServoTrace {
+    /// Create (truncate) a CSV file and write the header.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(Box::new(BufWriter::new(File::create(path)?)))
+    }
+
+    pub fn new(mut out: Box<dyn Write + Send>) -> Result<Self> {
+        writeln!(out, "{}", CSV_HEADER)?;
+        Ok(Self {
+            out,
+            start: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, r: &ServoTraceRecord) -> Result<()> {
+        writeln!(
+            self.out,
+            "{:.3},{},{:.3},{:.4},{:.4},{},{:.4},{:.4},{:.5},{:.4},{:.4},{:.12}",
+            self.start.elapsed().as_secs_f64(),
+            r.mode,
+            r.offset_us,
+            r.raw_rate_ppm,
+            r.filtered_rate_ppm,
+            r.spike as u8,
+            r.rate_ppm,
+            r.p_term_ppm,
+            r.i_term_ppm,
+            r.drift_baseline_ppm,
+            r.output_ppm,
+            r.factor
+        )?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that keeps the bytes for inspection.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_rows_match_header_layout() {
+        let buf = SharedBuf::default();
+        let mut trace = ServoTrace::new(Box::new(buf.clone())).unwrap();
+        trace
+            .record(&ServoTraceRecord {
+                mode: "LOCK",
+                offset_us: 12.5,
+                raw_rate_ppm: 0.8,
+                filtered_rate_ppm: 0.8,
+                spike: false,
+                rate_ppm: 0.5,
+                p_term_ppm: -0.25,
+                i_term_ppm: -0.025,
+                drift_baseline_ppm: 14.2,
+                output_ppm: 13.95,
+                factor: 1.00001395,
+            })
+            .unwrap();
+
+        let text = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], CSV_HEADER);
+
+        let header: Vec<&str> = lines[0].split(',').collect();
+        let row: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(row.len(), header.len());
+        let col = |name: &str| row[header.iter().position(|h| *h == name).unwrap()];
+        assert_eq!(col("mode"), "LOCK");
+        assert_eq!(col("spike"), "0");
+        assert_eq!(col("output_ppm").parse::<f64>().unwrap(), 13.95);
+        assert!((col("factor").parse::<f64>().unwrap() - 1.00001395).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-source Sync rate limiting.
+//!
+//! Anyone on the LAN can send to the PTP multicast group. A broken or malicious
+//! device flooding it with Syncs would otherwise cost a parse, a master-tracker
+//! update and a pending-Sync entry per packet, crowding out the real master.
+//! Each source UUID gets a budget of Syncs per second; the excess is dropped
+//! before any of that work is done.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Length of one counting window.
+const WINDOW: Duration = Duration::from_secs(1);
+/// Sources tracked at most; the least recently seen is forgotten beyond that
+/// (a flood with spoofed UUIDs must not grow the table either).
+const MAX_SOURCES: usize = 64;
+/// Minimum spacing of drop warnings per source.
+const WARN_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+struct SourceWindow {
+    window_start: Instant,
+    count: u32,
+    last_seen: Instant,
+    /// Drops not yet reported by a warning
+    unreported: u64,
+    last_warn: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct SourceRateLimiter {
+    max_per_sec: u32,
+    sources: HashMap<[u8; 6], SourceWindow>,
+    dropped: u64,
+}
+
+impl SourceRateLimiter {
+    pub fn new(max_per_sec: u32) -> Self {
+        Self {
+            max_per_sec,
+            sources: HashMap::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Count a Sync from `source` received at `now`. Returns false if the source
+    /// is over its budget for the current window and the Sync should be dropped.
+    pub fn allow(&mut self, source: [u8; 6], now: Instant) -> bool {
+        if !self.sources.contains_key(&source) && self.sources.len() >= MAX_SOURCES {
+            if let Some(oldest) = self
+                .sources
+                .iter()
+                .min_by_key(|(_, w)| w.last_seen)
+                .map(|(uuid, _)| *uuid)
+            {
+                self.sources.remove(&oldest);
+            }
+        }
+        let window = self.sources.entry(source).or_insert(SourceWindow {
+            window_start: now,
+            count: 0,
+            last_seen: now,
+            unreported: 0,
+            last_warn: None,
+        });
+        window.last_seen = now;
+        if now.saturating_duration_since(window.window_start) >= WINDOW {
+            window.window_start = now;
+            window.count = 0;
+        }
+        window.count = window.count.saturating_add(1);
+        if window.count <= self.max_per_sec {
+            return true;
+        }
+        window.unreported += 1;
+        self.dropped += 1;
+        false
+    }
+
+    /// Drops from `source` since its last warning, if a warning is due
+    /// (the first drop, then at most every `WARN_INTERVAL`).
+    pub fn warn_due(&mut self, source: [u8; 6], now: Instant) -> Option<u64> {
+        let window = self.sources.get_mut(&source)?;
+        if window.unreported == 0
+            || window
+                .last_warn
+                .is_some_and(|t| now.saturating_duration_since(t) < WARN_INTERVAL)
+        {
+            return None;
+        }
+        window.last_warn = Some(now);
+        Some(std::mem::take(&mut window.unreported))
+    }
+
+    pub fn max_per_sec(&self) -> u32 {
+        self.max_per_sec
+    }
+
+    /// Sources currently tracked.
+    pub fn tracked_sources(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Total number of dropped Syncs.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASTER: [u8; 6] = [0x00, 0x1D, 0xC1, 0x00, 0x00, 0x01];
+    const CHATTY: [u8; 6] = [0x00, 0x1D, 0xC1, 0x00, 0x00, 0x66];
+
+    #[test]
+    fn test_chatty_source_does_not_crowd_out_master() {
+        let mut limiter = SourceRateLimiter::new(32);
+        let start = Instant::now();
+        let (mut master_ok, mut chatty_ok) = (0, 0);
+        // 3s: master at 8Hz, the other source at 1000/s
+        for ms in 0..3_000u64 {
+            let now = start + Duration::from_millis(ms);
+            if ms % 125 == 0 && limiter.allow(MASTER, now) {
+                master_ok += 1;
+            }
+            if limiter.allow(CHATTY, now) {
+                chatty_ok += 1;
+            }
+        }
+        assert_eq!(master_ok, 24, "every master Sync passes");
+        assert_eq!(chatty_ok, 3 * 32, "one budget per window");
+        assert_eq!(limiter.dropped(), 3_000 - 3 * 32);
+    }
+
+    #[test]
+    fn test_drop_warnings_are_throttled() {
+        let mut limiter = SourceRateLimiter::new(1);
+        let start = Instant::now();
+        assert!(limiter.allow(CHATTY, start));
+        assert_eq!(limiter.warn_due(CHATTY, start), None, "nothing dropped");
+
+        for _ in 0..5 {
+            assert!(!limiter.allow(CHATTY, start));
+        }
+        assert_eq!(limiter.warn_due(CHATTY, start), Some(5));
+        assert!(!limiter.allow(CHATTY, start));
+        assert_eq!(limiter.warn_due(CHATTY, start), None, "warned just now");
+
+        let later = start + WARN_INTERVAL;
+        assert!(limiter.allow(CHATTY, later), "new window");
+        assert!(!limiter.allow(CHATTY, later));
+        assert_eq!(limiter.warn_due(CHATTY, later), Some(2));
+    }
+
+    #[test]
+    fn test_spoofed_sources_stay_bounded() {
+        let mut limiter = SourceRateLimiter::new(8);
+        let start = Instant::now();
+        for i in 0..10_000u32 {
+            let mut uuid = [0u8; 6];
+            uuid[2..].copy_from_slice(&i.to_be_bytes());
+            let now = start + Duration::from_micros(i as u64);
+            assert!(limiter.allow(uuid, now), "fresh source {}", i);
+        }
+        assert_eq!(limiter.tracked_sources(), MAX_SOURCES);
+    }
+}
@@ -7,7 +7,7 @@ use std::net::Ipv4Addr;
 /// - Display sync state (locked, acquiring, offline)
 /// - Animate the icon based on drift rate
 /// - Show detailed status in tooltips and menus
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct SyncStatus {
     // ========================================================================
     // Core PTP Status (existing fields)
@@ -16,9 +16,15 @@ pub struct SyncStatus {
     /// Note: Absolute value is meaningless for Dante (device uptime, not UTC)
     pub offset_ns: i64,
 
-    /// Current frequency adjustment being applied (PPM)
+    /// Estimated intrinsic drift of the local oscillator (PPM): the correction it
+    /// needs, averaged over minutes. Positive = local clock runs slow.
     pub drift_ppm: f64,
 
+    /// Frequency adjustment the servo is applying right now (PPM): the intrinsic
+    /// drift plus the short-term phase correction on top of it
+    #[serde(default)]
+    pub freq_adj_ppm: f64,
+
     /// Grandmaster clock UUID (from PTP Sync messages)
     pub gm_uuid: Option<[u8; 6]>,
 
@@ -29,6 +35,14 @@ pub struct SyncStatus {
     /// True once sync is established (receiving valid packets)
     pub settled: bool,
 
+    /// Valid pairs seen so far while settling, up to `settling_threshold`
+    #[serde(default)]
+    pub settling_count: usize,
+
+    /// Valid pairs required before sync is established (`filters.settling_threshold`)
+    #[serde(default)]
+    pub settling_threshold: usize,
+
     /// Unix timestamp of last status update
     pub updated_ts: u64,
 
@@ -39,7 +53,8 @@ pub struct SyncStatus {
     /// Used for icon badge color (green = locked)
     pub is_locked: bool,
 
-    /// Smoothed rate of offset change (us/s)
+    /// Smoothed rate of offset change (us/s): residual frequency error left after
+    /// the applied adjustment, not the adjustment itself
     /// Used for icon animation speed - higher rate = faster pulse
     pub smoothed_rate_ppm: f64,
 
@@ -47,7 +62,8 @@ pub struct SyncStatus {
     /// Used for NTP status display in tray menu
     pub ntp_offset_us: i64,
 
-    /// Current operating mode: "ACQ" (acquiring), "PROD" (production), "LOCK" (locked), "NTP-only"
+    /// Current operating mode: "ACQ" (acquiring), "PROD" (production), "LOCK" (locked), "NTP-only",
+    /// "FAULT" (repeated resets, servo holding frequency)
     /// Used for status display and icon state
     pub mode: String,
 
@@ -58,6 +74,150 @@ pub struct SyncStatus {
     /// Tracks estimated UTC drift between NTP corrections
     /// Reset to 0 after each NTP step
     pub accumulated_phase_us: f64,
+
+    /// Offset reported by the independent check NTP server (microseconds)
+    /// None when no check server is configured or the last query failed
+    #[serde(default)]
+    pub check_ntp_offset_us: Option<i64>,
+
+    /// True when the check NTP server disagrees beyond the alert threshold
+    #[serde(default)]
+    pub check_ntp_alarm: bool,
+
+    /// Frequency the kernel actually accepted (PPM)
+    /// None if the platform does not report it; differs from freq_adj_ppm when clamped
+    #[serde(default)]
+    pub kernel_freq_ppm: Option<f64>,
+
+    /// Duration of the last main loop iteration (microseconds)
+    /// None unless loop timing instrumentation is enabled
+    #[serde(default)]
+    pub loop_last_iteration_us: Option<u64>,
+
+    /// Longest main loop iteration since start (microseconds)
+    #[serde(default)]
+    pub loop_max_iteration_us: Option<u64>,
+
+    /// Fitted offset slope while locked (ns/s)
+    /// None until enough locked history exists; near zero when converged
+    #[serde(default)]
+    pub offset_slope_ns_per_s: Option<f64>,
+
+    /// True when locked but the offset slope shows the clock diverging
+    #[serde(default)]
+    pub convergence_alarm: bool,
+
+    /// True while in FAULT (repeated resets, servo holding frequency)
+    #[serde(default)]
+    pub fault: bool,
+
+    /// Syncs rejected by the inter-arrival gate (delayed in the socket buffer)
+    #[serde(default)]
+    pub arrival_gate_rejects: u64,
+
+    /// Phase offsets dropped as MAD outliers (filters.outlier_mad_k)
+    #[serde(default)]
+    pub phase_outlier_rejects: u64,
+
+    /// Syncs dropped from sources exceeding ptp.max_syncs_per_source
+    #[serde(default)]
+    pub sync_flood_drops: u64,
+
+    /// Measured Sync message rate (Hz), None until the first measurement window
+    #[serde(default)]
+    pub sync_rate_hz: Option<f64>,
+
+    /// Sync rate deviates from the expected rate (misconfigured master, heavy loss)
+    #[serde(default)]
+    pub sync_rate_alarm: bool,
+
+    /// Phase offset of the latest single Sync pair (nanoseconds, jittery)
+    #[serde(default)]
+    pub raw_offset_ns: i64,
+
+    /// Phase offset smoothed across sample windows (nanoseconds) - the value to
+    /// compare between machines
+    #[serde(default)]
+    pub smoothed_offset_ns: i64,
+
+    /// Raw offsets in the rolling statistics window (last 60s)
+    #[serde(default)]
+    pub offset_stats_samples: usize,
+
+    /// Smallest raw offset in the window (nanoseconds)
+    #[serde(default)]
+    pub offset_min_ns: Option<i64>,
+
+    /// Largest raw offset in the window (nanoseconds)
+    #[serde(default)]
+    pub offset_max_ns: Option<i64>,
+
+    /// Mean raw offset in the window (nanoseconds)
+    #[serde(default)]
+    pub offset_mean_ns: Option<i64>,
+
+    /// Standard deviation of the raw offsets in the window (nanoseconds) - the
+    /// stability figure
+    #[serde(default)]
+    pub offset_stddev_ns: Option<i64>,
+
+    /// 95% of the raw offsets in the window are within this of the mean (nanoseconds)
+    #[serde(default)]
+    pub offset_p95_ns: Option<i64>,
+
+    /// Lock health: "unlocked", "stable", or "hard-correction" (locked, but with a
+    /// large sustained correction - crystal near its spec limit)
+    #[serde(default)]
+    pub lock_health: String,
+
+    /// Seconds since the clock was last stepped (None = not stepped since start)
+    #[serde(default)]
+    pub secs_since_last_step: Option<u64>,
+
+    /// Active disciplining algorithm (e.g. "rate-pi")
+    #[serde(default)]
+    pub algorithm: String,
+
+    /// Key parameters of the active algorithm (gains per mode)
+    #[serde(default)]
+    pub algorithm_params: String,
+
+    /// System clock minus hardware RTC (milliseconds, ~1s resolution)
+    /// None if no RTC is readable (Windows, containers)
+    #[serde(default)]
+    pub rtc_offset_ms: Option<i64>,
+
+    /// Frequency correction actually in effect, measured against the raw
+    /// monotonic clock (PPM). None until the first audit window
+    #[serde(default)]
+    pub measured_freq_ppm: Option<f64>,
+
+    /// Measured frequency persistently differs from the commanded one: the OS
+    /// is not applying our adjustments
+    #[serde(default)]
+    pub rate_audit_alarm: bool,
+
+    /// The OS rejects frequency adjustments (Windows: read-back keeps disagreeing
+    /// or adjustment was disabled underneath us) - the clock is not disciplined
+    #[serde(default)]
+    pub clock_adjust_failed: bool,
+
+    /// Resolution of the master's T1 timestamps (ns), None until measured
+    #[serde(default)]
+    pub t1_granularity_ns: Option<u64>,
+
+    /// Mean path delay to the master from Delay_Req/Delay_Resp (ns), already
+    /// subtracted from the offset. None if the master has not answered
+    #[serde(default)]
+    pub path_delay_ns: Option<i64>,
+
+    /// Receive timestamps come from the NIC's PTP hardware clock
+    #[serde(default)]
+    pub hw_timestamping: bool,
+
+    /// The master announced a leap second for the coming UTC midnight
+    #[serde(default)]
+    pub leap_pending: bool,
 }
 
 impl Default for SyncStatus {
@@ -66,9 +226,12 @@ impl Default for SyncStatus {
             // Core fields
             offset_ns: 0,
             drift_ppm: 0.0,
+            freq_adj_ppm: 0.0,
             gm_uuid: None,
             gm_source_ip: None,
             settled: false,
+            settling_count: 0,
+            settling_threshold: 0,
             updated_ts: 0,
 
             // Extended fields for tray app
@@ -78,6 +241,67 @@ impl Default for SyncStatus {
             mode: "ACQ".to_string(),
             ntp_failed: false,
             accumulated_phase_us: 0.0,
+
+            // Independent NTP cross-check
+            check_ntp_offset_us: None,
+            check_ntp_alarm: false,
+
+            // Kernel-accepted frequency (clamp detection)
+            kernel_freq_ppm: None,
+
+            // Loop timing instrumentation
+            loop_last_iteration_us: None,
+            loop_max_iteration_us: None,
+
+            // Post-lock convergence verification
+            offset_slope_ns_per_s: None,
+            convergence_alarm: false,
+
+            // Repeated-reset FAULT state
+            fault: false,
+
+            // Inter-arrival gate
+            arrival_gate_rejects: 0,
+            phase_outlier_rejects: 0,
+            sync_flood_drops: 0,
+
+            // Sync rate supervision
+            sync_rate_hz: None,
+            sync_rate_alarm: false,
+
+            // Raw and smoothed phase offset
+            raw_offset_ns: 0,
+            smoothed_offset_ns: 0,
+
+            // Rolling offset statistics
+            offset_stats_samples: 0,
+            offset_min_ns: None,
+            offset_max_ns: None,
+            offset_mean_ns: None,
+            offset_stddev_ns: None,
+            offset_p95_ns: None,
+
+            // Lock health
+            lock_health: String::new(),
+
+            // Clock step rate limiting
+            secs_since_last_step: None,
+
+            // Disciplining algorithm
+            algorithm: String::new(),
+            algorithm_params: String::new(),
+
+            // Hardware clock diagnostics
+            rtc_offset_ms: None,
+            measured_freq_ppm: None,
+            rate_audit_alarm: false,
+            clock_adjust_failed: false,
+            t1_granularity_ns: None,
+            path_delay_ns: None,
+            hw_timestamping: false,
+
+            // Leap second
+            leap_pending: false,
         }
     }
 }
@@ -97,18 +321,26 @@ mod tests {
 
     #[test]
     fn test_sync_status_serde_roundtrip() {
-        let mut status = SyncStatus::default();
-        status.is_locked = true;
-        status.mode = "LOCK".to_string();
-        status.smoothed_rate_ppm = 2.5;
-        status.ntp_offset_us = 150;
+        let status = SyncStatus {
+            is_locked: true,
+            mode: "LOCK".to_string(),
+            smoothed_rate_ppm: 2.5,
+            ntp_offset_us: 150,
+            gm_uuid: Some([0x00, 0x1D, 0xC1, 0x0A, 0x0B, 0x0C]),
+            measured_freq_ppm: Some(-3.25),
+            path_delay_ns: Some(41_000),
+            hw_timestamping: true,
+            ..Default::default()
+        };
 
         let json = serde_json::to_string(&status).expect("serialize failed");
         let restored: SyncStatus = serde_json::from_str(&json).expect("deserialize failed");
 
-        assert_eq!(restored.is_locked, true);
+        assert!(restored.is_locked);
         assert_eq!(restored.mode, "LOCK");
         assert!((restored.smoothed_rate_ppm - 2.5).abs() < f64::EPSILON);
         assert_eq!(restored.ntp_offset_us, 150);
+        // Every field survives, so the tray sees what the service published
+        assert_eq!(restored, status);
     }
 }
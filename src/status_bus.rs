@@ -0,0 +1,152 @@
+//! Fan-out of status updates to independent consumers.
+//!
+//! The shared `Arc<RwLock<SyncStatus>>` serves consumers that poll (IPC pipe, time
+//! server). Consumers that want every update (loggers, exporters) subscribe here
+//! instead, so adding one never requires threading it through the controller.
+
+use crate::status::SyncStatus;
+use anyhow::Result;
+use log::warn;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::Mutex;
+use std::thread;
+
+/// Updates buffered per subscriber before newer ones are dropped for it.
+const SUBSCRIBER_QUEUE: usize = 16;
+
+#[derive(Debug, Default)]
+pub struct StatusBus {
+    subscribers: Mutex<Vec<SyncSender<SyncStatus>>>,
+}
+
+impl StatusBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive every status published from now on. Dropping the receiver unsubscribes.
+    pub fn subscribe(&self) -> Receiver<SyncStatus> {
+        let (tx, rx) = sync_channel(SUBSCRIBER_QUEUE);
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(tx);
+        }
+        rx
+    }
+
+    /// Send a status to all subscribers. Never blocks: a subscriber that falls
+    /// behind misses updates instead of stalling the sync loop.
+    pub fn publish(&self, status: &SyncStatus) {
+        let Ok(mut subscribers) = self.subscribers.lock() else {
+            return;
+        };
+        subscribers.retain(|tx| match tx.try_send(status.clone()) {
+            Ok(()) | Err(TrySendError::Full(_)) => true,
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().map_or(0, |s| s.len())
+    }
+}
+
+/// Consumer that appends every received status to `path` as one JSON line.
+/// Runs on its own thread until the bus goes away or a write fails.
+pub fn spawn_json_logger(rx: Receiver<SyncStatus>, path: &Path) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let path = path.display().to_string();
+    thread::spawn(move || {
+        for status in rx {
+            let written = serde_json::to_writer(&mut out, &status)
+                .map_err(std::io::Error::from)
+                .and_then(|_| out.write_all(b"\n"))
+                .and_then(|_| out.flush());
+            if let Err(e) = written {
+                warn!("[StatusLog] Write to {} failed, stopping: {}", path, e);
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_with_offset(offset_ns: i64) -> SyncStatus {
+        SyncStatus {
+            offset_ns,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_every_subscriber_gets_every_update() {
+        let bus = StatusBus::new();
+        let a = bus.subscribe();
+        let b = bus.subscribe();
+
+        bus.publish(&status_with_offset(1));
+        bus.publish(&status_with_offset(2));
+
+        for rx in [&a, &b] {
+            assert_eq!(rx.try_recv().unwrap().offset_ns, 1);
+            assert_eq!(rx.try_recv().unwrap().offset_ns, 2);
+            assert!(rx.try_recv().is_err());
+        }
+    }
+
+    #[test]
+    fn test_dropped_subscriber_is_removed() {
+        let bus = StatusBus::new();
+        let kept = bus.subscribe();
+        drop(bus.subscribe());
+        assert_eq!(bus.subscriber_count(), 2);
+
+        bus.publish(&status_with_offset(1));
+        assert_eq!(bus.subscriber_count(), 1);
+        assert_eq!(kept.try_recv().unwrap().offset_ns, 1);
+    }
+
+    #[test]
+    fn test_json_logger_writes_one_line_per_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("status.jsonl");
+        let bus = StatusBus::new();
+        spawn_json_logger(bus.subscribe(), &path).unwrap();
+
+        bus.publish(&status_with_offset(10));
+        bus.publish(&status_with_offset(20));
+        drop(bus); // ends the logger thread once drained
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let lines = loop {
+            let content = std::fs::read_to_string(&path).unwrap();
+            if content.lines().count() == 2 || std::time::Instant::now() > deadline {
+                break content;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+        };
+        let offsets: Vec<i64> = lines
+            .lines()
+            .map(|l| serde_json::from_str::<SyncStatus>(l).unwrap().offset_ns)
+            .collect();
+        assert_eq!(offsets, vec![10, 20]);
+    }
+
+    #[test]
+    fn test_slow_subscriber_does_not_block_publisher() {
+        let bus = StatusBus::new();
+        let slow = bus.subscribe();
+        for i in 0..(SUBSCRIBER_QUEUE as i64 * 2) {
+            bus.publish(&status_with_offset(i));
+        }
+        // Queue holds the oldest updates, the rest were dropped for this subscriber
+        assert_eq!(slow.try_iter().count(), SUBSCRIBER_QUEUE);
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+}
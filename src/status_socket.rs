@@ -0,0 +1,162 @@
+//! Unix domain socket status server (the Linux/macOS counterpart of the tray's named pipe).
+//!
+//! Every client gets the same length-prefixed JSON `SyncStatus` frames as the tray
+//! (see `ipc`), one per `STREAM_INTERVAL`, until it disconnects.
+
+use crate::ipc;
+use crate::status::SyncStatus;
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn};
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// Default socket path (`--status-socket`).
+pub const DEFAULT_STATUS_SOCKET: &str = "/run/dantesync.sock";
+
+/// A status frame is sent to each client this often.
+pub const STREAM_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct StatusSocketServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl StatusSocketServer {
+    /// Bind at `path`. A socket file left behind by a previous run is removed; one
+    /// that still accepts connections belongs to a running instance and is an error.
+    pub fn bind(path: &Path) -> Result<Self> {
+        if let Ok(meta) = fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                return Err(anyhow!("{} exists and is not a socket", path.display()));
+            }
+            if UnixStream::connect(path).is_ok() {
+                return Err(anyhow!(
+                    "{} is in use (another dantesync running?)",
+                    path.display()
+                ));
+            }
+            debug!("[StatusSocket] Removing stale socket {}", path.display());
+            fs::remove_file(path)
+                .with_context(|| format!("removing stale socket {}", path.display()))?;
+        }
+
+        let listener =
+            UnixListener::bind(path).with_context(|| format!("binding {}", path.display()))?;
+        // Status is read-only and not sensitive: let unprivileged users run dantesync-status
+        fs::set_permissions(path, fs::Permissions::from_mode(0o666))?;
+        info!("[StatusSocket] Serving status on {}", path.display());
+
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Accept clients on a background thread, one streaming thread per client.
+    pub fn spawn(self, status: Arc<RwLock<SyncStatus>>) {
+        thread::spawn(move || {
+            for stream in self.listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let status = status.clone();
+                        thread::spawn(move || stream_status(stream, &status));
+                    }
+                    Err(e) => warn!(
+                        "[StatusSocket] Accept on {} failed: {}",
+                        self.path.display(),
+                        e
+                    ),
+                }
+            }
+        });
+    }
+}
+
+/// Write status frames until the client goes away.
+fn stream_status(mut stream: UnixStream, status: &RwLock<SyncStatus>) {
+    loop {
+        let snapshot = match status.read() {
+            Ok(guard) => guard.clone(),
+            Err(e) => {
+                warn!("[StatusSocket] Status lock poisoned: {}", e);
+                return;
+            }
+        };
+        let frame = match serde_json::to_vec(&snapshot)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| ipc::encode_frame(&bytes))
+        {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("[StatusSocket] Status not sent: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = stream.write_all(&frame) {
+            debug!("[StatusSocket] Client disconnected: {}", e);
+            return;
+        }
+        thread::sleep(STREAM_INTERVAL);
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_status(path: &Path) -> SyncStatus {
+        let mut client = UnixStream::connect(path).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        serde_json::from_slice(&ipc::read_frame(&mut client).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_client_receives_status_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dantesync.sock");
+        let status = Arc::new(RwLock::new(SyncStatus {
+            offset_ns: 1234,
+            mode: "PTP".to_string(),
+            is_locked: true,
+            ..Default::default()
+        }));
+
+        StatusSocketServer::bind(&path).unwrap().spawn(status);
+
+        let received = read_status(&path);
+        assert_eq!(received.offset_ns, 1234);
+        assert_eq!(received.mode, "PTP");
+        assert!(received.is_locked);
+    }
+
+    #[test]
+    fn test_stale_socket_replaced_but_live_one_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dantesync.sock");
+
+        // A crashed run leaves the socket file without a listener
+        drop(UnixListener::bind(&path).unwrap());
+        let server = StatusSocketServer::bind(&path).expect("stale socket replaced");
+
+        // While that server listens, a second instance must not steal the path
+        assert!(StatusSocketServer::bind(&path).is_err());
+        drop(server);
+
+        let file = dir.path().join("not-a-socket");
+        fs::write(&file, b"keep me").unwrap();
+        assert!(StatusSocketServer::bind(&file).is_err());
+        assert_eq!(fs::read(&file).unwrap(), b"keep me");
+    }
+}
@@ -0,0 +1,178 @@
+//! Sync message-rate supervision.
+//!
+//! Grandmasters send Syncs at a configured rate (Dante: 8 Hz). A measured rate well
+//! below that means a misconfigured master or heavy loss - the timing network is
+//! degrading before the servo visibly suffers. The expected rate is either fixed
+//! or learned from the first measurement window ("auto").
+
+use std::time::{Duration, Instant};
+
+/// Result of one measurement window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateCheck {
+    pub measured_hz: f64,
+    pub expected_hz: f64,
+    /// Measured rate deviates from the expectation beyond the tolerance
+    pub alarm: bool,
+}
+
+#[derive(Debug)]
+pub struct SyncRateMonitor {
+    /// Fixed expectation; None = learn from the first window
+    fixed_hz: Option<f64>,
+    learned_hz: Option<f64>,
+    tolerance: f64,
+    window: Duration,
+    window_start: Option<Instant>,
+    count: u32,
+}
+
+impl SyncRateMonitor {
+    pub fn new(expected_hz: Option<f64>, tolerance_pct: f64, window: Duration) -> Self {
+        Self {
+            fixed_hz: expected_hz,
+            learned_hz: None,
+            tolerance: tolerance_pct / 100.0,
+            window,
+            window_start: None,
+            count: 0,
+        }
+    }
+
+    /// Count a Sync received at `now`. Returns a check at the end of each window.
+    pub fn on_sync(&mut self, now: Instant) -> Option<RateCheck> {
+        let Some(start) = self.window_start else {
+            self.window_start = Some(now);
+            return None;
+        };
+        self.count += 1;
+        self.close_window(start, now)
+    }
+
+    /// Close the window at `now` if it is due, even without a Sync (called on
+    /// every controller tick, so an outage still measures as a rate drop).
+    pub fn poll(&mut self, now: Instant) -> Option<RateCheck> {
+        let start = self.window_start?;
+        self.close_window(start, now)
+    }
+
+    fn close_window(&mut self, start: Instant, now: Instant) -> Option<RateCheck> {
+        let elapsed = now.duration_since(start);
+        if elapsed < self.window {
+            return None;
+        }
+
+        let count = std::mem::take(&mut self.count);
+        let measured_hz = count as f64 / elapsed.as_secs_f64();
+        self.window_start = Some(now);
+
+        let expected_hz = match self.fixed_hz.or(self.learned_hz) {
+            Some(hz) => hz,
+            // Nothing to learn from an empty window
+            None if count == 0 => return None,
+            None => {
+                self.learned_hz = Some(measured_hz);
+                measured_hz
+            }
+        };
+        let deviation = (measured_hz - expected_hz).abs() / expected_hz;
+        Some(RateCheck {
+            measured_hz,
+            expected_hz,
+            alarm: deviation > self.tolerance,
+        })
+    }
+
+    /// Forget the learned rate and the open window (sync source changed).
+    pub fn reset(&mut self) {
+        self.learned_hz = None;
+        self.window_start = None;
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(10);
+
+    /// Feed `secs` worth of Syncs at `hz`, returning the last check.
+    fn feed(
+        monitor: &mut SyncRateMonitor,
+        t: &mut Instant,
+        hz: f64,
+        secs: u32,
+    ) -> Option<RateCheck> {
+        let interval = Duration::from_secs_f64(1.0 / hz);
+        let mut last = None;
+        for _ in 0..(hz * secs as f64) as u32 {
+            *t += interval;
+            if let Some(check) = monitor.on_sync(*t) {
+                last = Some(check);
+            }
+        }
+        last
+    }
+
+    #[test]
+    fn test_auto_learns_then_alarms_on_drop() {
+        let mut monitor = SyncRateMonitor::new(None, 25.0, WINDOW);
+        let mut t = Instant::now();
+
+        let check = feed(&mut monitor, &mut t, 8.0, 11).unwrap();
+        assert!((check.measured_hz - 8.0).abs() < 0.1);
+        assert!(!check.alarm);
+
+        // Master reconfigured to 1 Hz
+        let check = feed(&mut monitor, &mut t, 1.0, 21).unwrap();
+        assert!((check.expected_hz - 8.0).abs() < 0.1);
+        assert!(check.measured_hz < 1.5);
+        assert!(check.alarm);
+    }
+
+    #[test]
+    fn test_fixed_expectation_and_tolerance() {
+        let mut monitor = SyncRateMonitor::new(Some(8.0), 25.0, WINDOW);
+        let mut t = Instant::now();
+        // 7 Hz is within 25% of 8 Hz
+        assert!(!feed(&mut monitor, &mut t, 7.0, 11).unwrap().alarm);
+        // 4 Hz (half the Syncs lost) is not
+        assert!(feed(&mut monitor, &mut t, 4.0, 11).unwrap().alarm);
+    }
+
+    #[test]
+    fn test_outage_alarms_on_poll() {
+        let mut monitor = SyncRateMonitor::new(Some(8.0), 25.0, WINDOW);
+        let mut t = Instant::now();
+        feed(&mut monitor, &mut t, 8.0, 11);
+
+        // Syncs stop: only the tick closes the windows
+        assert_eq!(monitor.poll(t + Duration::from_secs(5)), None);
+        assert!(monitor.poll(t + WINDOW).unwrap().alarm);
+        let check = monitor.poll(t + 2 * WINDOW).unwrap();
+        assert_eq!(check.measured_hz, 0.0);
+        assert!(check.alarm);
+    }
+
+    #[test]
+    fn test_poll_does_not_learn_from_silence() {
+        let mut monitor = SyncRateMonitor::new(None, 25.0, WINDOW);
+        let t = Instant::now();
+        monitor.on_sync(t);
+        assert_eq!(monitor.poll(t + WINDOW), None);
+        assert_eq!(monitor.learned_hz, None);
+    }
+
+    #[test]
+    fn test_reset_relearns() {
+        let mut monitor = SyncRateMonitor::new(None, 25.0, WINDOW);
+        let mut t = Instant::now();
+        feed(&mut monitor, &mut t, 8.0, 11);
+        monitor.reset();
+        // New source legitimately sends at 4 Hz
+        let check = feed(&mut monitor, &mut t, 4.0, 11).unwrap();
+        assert!(!check.alarm);
+        assert!((check.expected_hz - 4.0).abs() < 0.1);
+    }
+}
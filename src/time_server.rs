@@ -9,32 +9,47 @@
 //! **Port:** 31900 (UDP)
 //!
 //! **Request Packet:** 8 bytes
-//! - `[0-3]` Magic: "DSYN" (0x4453594E)
+//! - `[0-3]` Magic: "DSYN" (0x4453594E), or "DSYX" (0x44535958) for the extended
+//!   80-byte response
 //! - `[4-7]` Request ID (u32, for matching responses)
 //!
-//! **Response Packet:** 64 bytes
+//! **Shared-key mode** (`ntp_server_mode.time_query_key` set): the request is 24
+//! bytes, the 8 above followed by a 16-byte tag - HMAC-SHA256 over bytes 0-7 with
+//! the key, truncated to its first 16 bytes. Requests without a valid tag are
+//! dropped unanswered. The response then carries a tag over its 64 (80) bytes the
+//! same way, appended at `[64-79]` (`[80-95]`). Without a key, 8-byte requests are
+//! answered as before.
+//!
+//! **Response Packet:** 64 bytes to "DSYN"; "DSYX" adds `[64-79]`. Readers with a
+//! 64-byte buffer keep working: on Windows a longer datagram fails their recvfrom.
 //! - `[0-3]`   Magic: "DSYR" (0x44535952)
 //! - `[4-7]`   Request ID (echo back)
 //! - `[8-15]`  System time (UTC nanoseconds since Unix epoch, u64)
 //! - `[16-23]` Monotonic counter (QPC on Windows, CLOCK_MONOTONIC_RAW on Linux, u64)
-//! - `[24-31]` PTP offset from grandmaster (nanoseconds, signed i64)
+//! - `[24-31]` PTP offset from grandmaster: median of the last sample window (nanoseconds, signed i64)
 //! - `[32-35]` Drift rate (PPM × 1000, signed i32)
 //! - `[36-39]` Frequency adjustment (PPM × 1000, signed i32)
-//! - `[40]`    Mode: 0=INIT, 1=ACQ, 2=PROD, 3=LOCK, 4=NANO, 5=NTP_ONLY
+//! - `[40]`    Mode: 0=INIT, 1=ACQ, 2=PROD, 3=LOCK, 4=NANO, 5=NTP_ONLY, 6=FAULT
 //! - `[41]`    Is locked: 0/1
 //! - `[42-47]` Grandmaster UUID (6 bytes)
 //! - `[48-55]` Monotonic frequency (ticks per second, u64)
 //! - `[56-59]` NTP offset (microseconds, signed i32)
 //! - `[60-61]` Accumulated phase drift since last NTP step (microseconds, signed i16)
 //! - `[62]`    Flags: bit 0 = ntp_failed, bit 1 = settled
-//! - `[63]`    Reserved (zero)
+//! - `[63]`    Servo algorithm: 0=unknown, 1=rate-pi, 2=kalman
+//! - `[64-71]` Raw offset of the latest single Sync pair (nanoseconds, signed i64)
+//! - `[72-79]` Smoothed offset, EMA across sample windows (nanoseconds, signed i64).
+//!   Compare this one between machines - raw values are two noise samples.
 
+use crate::clock::system_time_to_unix_nanos;
 use crate::status::SyncStatus;
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use hmac::{Hmac, Mac};
 use log::{debug, error, info, warn};
-use std::net::UdpSocket;
+use sha2::Sha256;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
 use std::sync::{Arc, RwLock};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime};
 
 /// UDP port for time query server
 pub const TIME_SERVER_PORT: u16 = 31900;
@@ -42,6 +57,9 @@ pub const TIME_SERVER_PORT: u16 = 31900;
 /// Request magic bytes: "DSYN"
 const REQUEST_MAGIC: u32 = 0x4453594E;
 
+/// Request magic bytes asking for the extended response: "DSYX"
+const EXTENDED_REQUEST_MAGIC: u32 = 0x44535958;
+
 /// Response magic bytes: "DSYR"
 const RESPONSE_MAGIC: u32 = 0x44535952;
 
@@ -51,12 +69,21 @@ const REQUEST_SIZE: usize = 8;
 /// Response packet size
 const RESPONSE_SIZE: usize = 64;
 
+/// Response packet size with the raw/smoothed offset fields ("DSYX" requests)
+const EXTENDED_RESPONSE_SIZE: usize = 80;
+
+/// Truncated HMAC-SHA256 tag appended to packets in shared-key mode
+const TAG_SIZE: usize = 16;
+
+type HmacSha256 = Hmac<Sha256>;
+
 /// UDP Time Query Server for network time verification.
 ///
 /// Listens on port 31900 and responds to time queries with precise clock data
 /// including system time, monotonic counter, and sync status.
 pub struct TimeServer {
     socket: UdpSocket,
+    auth_key: Option<Vec<u8>>,
 }
 
 impl TimeServer {
@@ -64,16 +91,36 @@ impl TimeServer {
     ///
     /// The socket is set to non-blocking mode for integration with the main loop.
     pub fn new() -> Result<Self> {
-        let bind_addr = format!("0.0.0.0:{}", TIME_SERVER_PORT);
+        Self::bind(Ipv4Addr::UNSPECIFIED)
+    }
+
+    /// Create a new TimeServer listening on one local address only.
+    pub fn bind(ip: Ipv4Addr) -> Result<Self> {
+        let bind_addr = format!("{}:{}", ip, TIME_SERVER_PORT);
         let socket = UdpSocket::bind(&bind_addr)?;
         socket.set_nonblocking(true)?;
 
         info!(
-            "[TimeServer] Listening on UDP port {} for time queries",
-            TIME_SERVER_PORT
+            "[TimeServer] Listening on UDP {} for time queries",
+            bind_addr
         );
 
-        Ok(TimeServer { socket })
+        Ok(TimeServer {
+            socket,
+            auth_key: None,
+        })
+    }
+
+    /// Only answer requests tagged with this shared key, and tag the responses.
+    pub fn with_auth_key(mut self, key: &[u8]) -> Self {
+        info!("[TimeServer] Shared-key mode: unauthenticated queries are dropped");
+        self.auth_key = Some(key.to_vec());
+        self
+    }
+
+    /// The server socket, e.g. to wait for requests together with other sockets.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
     }
 
     /// Handle pending time query requests.
@@ -81,38 +128,31 @@ impl TimeServer {
     /// This is designed to be called from the main sync loop. It processes
     /// all pending requests without blocking.
     pub fn handle_requests(&self, status: &Arc<RwLock<SyncStatus>>) {
-        let mut buf = [0u8; REQUEST_SIZE];
+        let mut buf = [0u8; REQUEST_SIZE + TAG_SIZE];
 
         // Process all pending requests (non-blocking)
         loop {
             match self.socket.recv_from(&mut buf) {
                 Ok((size, src)) => {
-                    if size >= REQUEST_SIZE {
-                        let magic = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]);
-                        if magic == REQUEST_MAGIC {
-                            let request_id = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]);
-
-                            // Read status (handle poisoned lock gracefully)
-                            let sync_status = match status.read() {
-                                Ok(guard) => guard.clone(),
-                                Err(e) => {
-                                    warn!("[TimeServer] Status lock poisoned: {}", e);
-                                    continue;
-                                }
-                            };
-
-                            let response = build_response(request_id, &sync_status);
-                            if let Err(e) = self.socket.send_to(&response, src) {
-                                debug!("[TimeServer] Failed to send response to {}: {}", src, e);
-                            } else {
-                                debug!("[TimeServer] Responded to {}", src);
-                            }
-                        } else {
-                            debug!(
-                                "[TimeServer] Ignoring packet with invalid magic 0x{:08X} from {}",
-                                magic, src
-                            );
+                    // Read status (handle poisoned lock gracefully)
+                    let sync_status = match status.read() {
+                        Ok(guard) => guard.clone(),
+                        Err(e) => {
+                            warn!("[TimeServer] Status lock poisoned: {}", e);
+                            continue;
                         }
+                    };
+
+                    let Some(response) =
+                        answer(&buf[..size], self.auth_key.as_deref(), &sync_status)
+                    else {
+                        debug!("[TimeServer] Ignoring invalid query from {}", src);
+                        continue;
+                    };
+                    if let Err(e) = self.socket.send_to(&response, src) {
+                        debug!("[TimeServer] Failed to send response to {}: {}", src, e);
+                    } else {
+                        debug!("[TimeServer] Responded to {}", src);
                     }
                 }
                 Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -128,9 +168,48 @@ impl TimeServer {
     }
 }
 
-/// Build a time query response packet.
-fn build_response(request_id: u32, status: &SyncStatus) -> [u8; RESPONSE_SIZE] {
-    let mut resp = [0u8; RESPONSE_SIZE];
+/// The response to one request packet, or None if it must be dropped: wrong
+/// magic, too short, (with a key) missing or carrying a bad tag, or our clock is
+/// before the Unix epoch.
+fn answer(request: &[u8], key: Option<&[u8]>, status: &SyncStatus) -> Option<Vec<u8>> {
+    if request.len() < REQUEST_SIZE {
+        return None;
+    }
+    let size = match u32::from_be_bytes(request[0..4].try_into().ok()?) {
+        REQUEST_MAGIC => RESPONSE_SIZE,
+        EXTENDED_REQUEST_MAGIC => EXTENDED_RESPONSE_SIZE,
+        _ => return None,
+    };
+    if let Some(key) = key {
+        let tag = request.get(REQUEST_SIZE..REQUEST_SIZE + TAG_SIZE)?;
+        let mut mac = HmacSha256::new_from_slice(key).ok()?;
+        mac.update(&request[..REQUEST_SIZE]);
+        mac.verify_truncated_left(tag).ok()?;
+    }
+
+    let request_id = u32::from_be_bytes(request[4..8].try_into().ok()?);
+    let mut response = build_response(request_id, status)?[..size].to_vec();
+    if let Some(key) = key {
+        response.extend_from_slice(&auth_tag(key, &response));
+    }
+    Some(response)
+}
+
+/// Truncated HMAC-SHA256 of `data` under `key`.
+fn auth_tag(key: &[u8], data: &[u8]) -> [u8; TAG_SIZE] {
+    // HMAC accepts keys of any length
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC key of any length");
+    mac.update(data);
+    let mut tag = [0u8; TAG_SIZE];
+    tag.copy_from_slice(&mac.finalize().into_bytes()[..TAG_SIZE]);
+    tag
+}
+
+/// Build an extended time query response packet ("DSYN" gets its first 64
+/// bytes). None while the system clock is before the Unix epoch: no answer is
+/// better than a time of 0.
+fn build_response(request_id: u32, status: &SyncStatus) -> Option<[u8; EXTENDED_RESPONSE_SIZE]> {
+    let mut resp = [0u8; EXTENDED_RESPONSE_SIZE];
 
     // [0-3] Response magic
     resp[0..4].copy_from_slice(&RESPONSE_MAGIC.to_be_bytes());
@@ -139,10 +218,7 @@ fn build_response(request_id: u32, status: &SyncStatus) -> [u8; RESPONSE_SIZE] {
     resp[4..8].copy_from_slice(&request_id.to_be_bytes());
 
     // [8-15] System time (UTC nanoseconds since Unix epoch)
-    let system_ns = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_nanos() as u64)
-        .unwrap_or(0);
+    let system_ns = unix_nanos_now()?;
     resp[8..16].copy_from_slice(&system_ns.to_be_bytes());
 
     // [16-23] Monotonic counter (platform-specific)
@@ -158,7 +234,7 @@ fn build_response(request_id: u32, status: &SyncStatus) -> [u8; RESPONSE_SIZE] {
     resp[32..36].copy_from_slice(&drift_scaled.to_be_bytes());
 
     // [36-39] Frequency adjustment (PPM × 1000)
-    let adj_scaled = (status.drift_ppm * 1000.0) as i32;
+    let adj_scaled = (status.freq_adj_ppm * 1000.0) as i32;
     resp[36..40].copy_from_slice(&adj_scaled.to_be_bytes());
 
     // [40] Mode
@@ -168,6 +244,7 @@ fn build_response(request_id: u32, status: &SyncStatus) -> [u8; RESPONSE_SIZE] {
         "LOCK" => 3,
         "NANO" => 4,
         "NTP-only" => 5,
+        "FAULT" => 6,
         _ => 0,
     };
 
@@ -202,9 +279,195 @@ fn build_response(request_id: u32, status: &SyncStatus) -> [u8; RESPONSE_SIZE] {
     }
     resp[62] = flags;
 
-    // [63] Reserved (already zero)
+    // [63] Servo algorithm
+    resp[63] = match status.algorithm.as_str() {
+        crate::servo::PI_ALGORITHM => 1,
+        crate::servo::KALMAN_ALGORITHM => 2,
+        _ => 0,
+    };
+
+    // [64-71] Raw offset of the latest Sync pair (nanoseconds)
+    resp[64..72].copy_from_slice(&status.raw_offset_ns.to_be_bytes());
+
+    // [72-79] Smoothed offset (nanoseconds)
+    resp[72..80].copy_from_slice(&status.smoothed_offset_ns.to_be_bytes());
+
+    Some(resp)
+}
+
+/// One parsed time query response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeQueryResult {
+    /// Remote system time (UTC nanoseconds since Unix epoch)
+    pub system_ns: u64,
+    /// This machine's system time halfway through the round trip
+    pub local_ns: u64,
+    pub round_trip: Duration,
+    /// Remote monotonic counter and its ticks per second
+    pub monotonic: u64,
+    pub monotonic_freq: u64,
+    pub ptp_offset_ns: i64,
+    /// Smoothed drift rate (PPM)
+    pub drift_ppm: f64,
+    /// Frequency adjustment applied (PPM)
+    pub freq_adj_ppm: f64,
+    pub mode: &'static str,
+    pub is_locked: bool,
+    /// None while the remote has no grandmaster
+    pub gm_uuid: Option<[u8; 6]>,
+}
+
+impl TimeQueryResult {
+    /// Remote system time minus ours, assuming a symmetric path.
+    pub fn offset_from_local_ns(&self) -> i64 {
+        self.system_ns as i64 - self.local_ns as i64
+    }
+}
+
+/// Client for the time query protocol (what `dantesync-verify` uses).
+pub struct TimeClient {
+    socket: UdpSocket,
+    auth_key: Option<Vec<u8>>,
+}
+
+impl TimeClient {
+    pub fn new() -> Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        Ok(TimeClient {
+            socket,
+            auth_key: None,
+        })
+    }
+
+    /// Tag requests with the servers' shared key and require tagged responses.
+    pub fn with_auth_key(mut self, key: &[u8]) -> Self {
+        self.auth_key = Some(key.to_vec());
+        self
+    }
+
+    /// Query one server. Responses to other (earlier, timed out) requests are skipped.
+    pub fn query(&self, addr: SocketAddr, timeout: Duration) -> Result<TimeQueryResult> {
+        let request_id = u32::from_be_bytes(
+            uuid::Uuid::new_v4().as_bytes()[..4]
+                .try_into()
+                .expect("4 bytes"),
+        );
+        let mut request = REQUEST_MAGIC.to_be_bytes().to_vec();
+        request.extend_from_slice(&request_id.to_be_bytes());
+        if let Some(key) = &self.auth_key {
+            let tag = auth_tag(key, &request);
+            request.extend_from_slice(&tag);
+        }
+
+        let sent = Instant::now();
+        let sent_ns =
+            unix_nanos_now().ok_or_else(|| anyhow!("system clock is before the Unix epoch"))?;
+        self.socket
+            .send_to(&request, addr)
+            .with_context(|| format!("sending time query to {}", addr))?;
+
+        let deadline = sent + timeout;
+        let mut buf = [0u8; 256];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(anyhow!("no response from {} within {:?}", addr, timeout));
+            }
+            self.socket.set_read_timeout(Some(remaining))?;
+            let (size, src) = match self.socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue;
+                }
+                Err(e) => return Err(e).with_context(|| format!("querying {}", addr)),
+            };
+            let round_trip = sent.elapsed();
+            if src != addr {
+                continue;
+            }
+            match parse_response(&buf[..size], request_id, self.auth_key.as_deref()) {
+                Ok(mut result) => {
+                    result.round_trip = round_trip;
+                    result.local_ns = sent_ns + round_trip.as_nanos() as u64 / 2;
+                    return Ok(result);
+                }
+                Err(e) => debug!("[TimeClient] Skipping response from {}: {}", src, e),
+            }
+        }
+    }
+}
+
+/// Parse a response to request `request_id` (timing fields left zero).
+fn parse_response(data: &[u8], request_id: u32, key: Option<&[u8]>) -> Result<TimeQueryResult> {
+    let data = match key {
+        Some(key) => {
+            let body_len = data
+                .len()
+                .checked_sub(TAG_SIZE)
+                .filter(|&len| len >= RESPONSE_SIZE)
+                .ok_or_else(|| anyhow!("untagged response"))?;
+            let (body, tag) = data.split_at(body_len);
+            let mut mac = HmacSha256::new_from_slice(key).expect("HMAC key of any length");
+            mac.update(body);
+            mac.verify_truncated_left(tag)
+                .map_err(|_| anyhow!("bad response tag"))?;
+            body
+        }
+        None => data,
+    };
+    if data.len() < RESPONSE_SIZE {
+        return Err(anyhow!("short response ({} bytes)", data.len()));
+    }
+
+    let u32_at = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+    let u64_at = |i: usize| u64::from_be_bytes(data[i..i + 8].try_into().unwrap());
+    if u32_at(0) != RESPONSE_MAGIC {
+        return Err(anyhow!("invalid magic 0x{:08X}", u32_at(0)));
+    }
+    if u32_at(4) != request_id {
+        return Err(anyhow!(
+            "request ID 0x{:08X} does not match 0x{:08X}",
+            u32_at(4),
+            request_id
+        ));
+    }
+
+    let gm_uuid: [u8; 6] = data[42..48].try_into().unwrap();
+    Ok(TimeQueryResult {
+        system_ns: u64_at(8),
+        local_ns: 0,
+        round_trip: Duration::ZERO,
+        monotonic: u64_at(16),
+        monotonic_freq: u64_at(48),
+        ptp_offset_ns: u64_at(24) as i64,
+        drift_ppm: u32_at(32) as i32 as f64 / 1000.0,
+        freq_adj_ppm: u32_at(36) as i32 as f64 / 1000.0,
+        mode: mode_name(data[40]),
+        is_locked: data[41] == 1,
+        gm_uuid: (gm_uuid != [0; 6]).then_some(gm_uuid),
+    })
+}
+
+/// Mode name for the response's mode byte (inverse of `build_response`).
+fn mode_name(code: u8) -> &'static str {
+    match code {
+        1 => "ACQ",
+        2 => "PROD",
+        3 => "LOCK",
+        4 => "NANO",
+        5 => "NTP-only",
+        6 => "FAULT",
+        _ => "INIT",
+    }
+}
 
-    resp
+fn unix_nanos_now() -> Option<u64> {
+    system_time_to_unix_nanos(SystemTime::now()).map(|ns| ns as u64)
 }
 
 /// Get the monotonic counter value (platform-specific).
@@ -278,7 +541,7 @@ mod tests {
     fn test_build_response_format() {
         let status = SyncStatus::default();
         let request_id = 0x12345678u32;
-        let response = build_response(request_id, &status);
+        let response = build_response(request_id, &status).unwrap();
 
         // Check magic
         let magic = u32::from_be_bytes([response[0], response[1], response[2], response[3]]);
@@ -331,15 +594,17 @@ mod tests {
 
     #[test]
     fn test_build_response_with_status() {
-        let mut status = SyncStatus::default();
-        status.offset_ns = -12345;
-        status.smoothed_rate_ppm = 1.5;
-        status.drift_ppm = -0.75;
-        status.mode = "LOCK".to_string();
-        status.is_locked = true;
-        status.gm_uuid = Some([0x00, 0x1D, 0xC1, 0xAB, 0xCD, 0xEF]);
-
-        let response = build_response(42, &status);
+        let status = SyncStatus {
+            offset_ns: -12345,
+            smoothed_rate_ppm: 1.5,
+            freq_adj_ppm: -0.75,
+            mode: "LOCK".to_string(),
+            is_locked: true,
+            gm_uuid: Some([0x00, 0x1D, 0xC1, 0xAB, 0xCD, 0xEF]),
+            ..Default::default()
+        };
+
+        let response = build_response(42, &status).unwrap();
 
         // Check PTP offset
         let offset = i64::from_be_bytes([
@@ -381,20 +646,43 @@ mod tests {
             ("LOCK", 3),
             ("NANO", 4),
             ("NTP-only", 5),
+            ("FAULT", 6),
         ];
 
         for (mode_str, expected) in modes {
-            let mut status = SyncStatus::default();
-            status.mode = mode_str.to_string();
-            let response = build_response(0, &status);
+            let status = SyncStatus {
+                mode: mode_str.to_string(),
+                ..Default::default()
+            };
+            let response = build_response(0, &status).unwrap();
             assert_eq!(
                 response[40], expected,
                 "Mode '{}' should encode to {}",
                 mode_str, expected
             );
+            let name = if mode_str.is_empty() {
+                "INIT"
+            } else {
+                mode_str
+            };
+            assert_eq!(mode_name(expected), name);
         }
     }
 
+    #[test]
+    fn test_algorithm_encoding() {
+        let status = SyncStatus {
+            algorithm: crate::servo::PI_ALGORITHM.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(build_response(0, &status).unwrap()[63], 1);
+        let status = SyncStatus {
+            algorithm: crate::servo::KALMAN_ALGORITHM.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(build_response(0, &status).unwrap()[63], 2);
+    }
+
     #[test]
     fn test_monotonic_counter_increases() {
         let c1 = get_monotonic_counter();
@@ -419,8 +707,8 @@ mod tests {
     #[test]
     fn test_response_size() {
         let status = SyncStatus::default();
-        let response = build_response(0, &status);
-        assert_eq!(response.len(), RESPONSE_SIZE);
+        let response = build_response(0, &status).unwrap();
+        assert_eq!(response.len(), EXTENDED_RESPONSE_SIZE);
     }
 
     #[test]
@@ -430,13 +718,15 @@ mod tests {
 
     #[test]
     fn test_build_response_ntp_fields() {
-        let mut status = SyncStatus::default();
-        status.ntp_offset_us = 1234;
-        status.accumulated_phase_us = -567.8;
-        status.ntp_failed = false;
-        status.settled = true;
+        let status = SyncStatus {
+            ntp_offset_us: 1234,
+            accumulated_phase_us: -567.8,
+            ntp_failed: false,
+            settled: true,
+            ..Default::default()
+        };
 
-        let response = build_response(0, &status);
+        let response = build_response(0, &status).unwrap();
 
         // [56-59] NTP offset (i32)
         let ntp_off = i32::from_be_bytes([response[56], response[57], response[58], response[59]]);
@@ -449,50 +739,238 @@ mod tests {
         // [62] Flags: bit 0 = ntp_failed (0), bit 1 = settled (1) = 0b10 = 2
         assert_eq!(response[62], 0x02);
 
-        // [63] Reserved
+        // [63] Algorithm unknown (not reported yet)
         assert_eq!(response[63], 0);
     }
 
     #[test]
     fn test_build_response_ntp_failed_flag() {
-        let mut status = SyncStatus::default();
-        status.ntp_failed = true;
-        status.settled = false;
+        let status = SyncStatus {
+            ntp_failed: true,
+            settled: false,
+            ..Default::default()
+        };
 
-        let response = build_response(0, &status);
+        let response = build_response(0, &status).unwrap();
         // bit 0 = ntp_failed (1), bit 1 = settled (0) = 0b01 = 1
         assert_eq!(response[62], 0x01);
     }
 
     #[test]
     fn test_build_response_both_flags() {
-        let mut status = SyncStatus::default();
-        status.ntp_failed = true;
-        status.settled = true;
+        let status = SyncStatus {
+            ntp_failed: true,
+            settled: true,
+            ..Default::default()
+        };
 
-        let response = build_response(0, &status);
+        let response = build_response(0, &status).unwrap();
         // bit 0 = ntp_failed (1), bit 1 = settled (1) = 0b11 = 3
         assert_eq!(response[62], 0x03);
     }
 
     #[test]
     fn test_build_response_ntp_offset_negative() {
-        let mut status = SyncStatus::default();
-        status.ntp_offset_us = -42000;
+        let status = SyncStatus {
+            ntp_offset_us: -42000,
+            ..Default::default()
+        };
 
-        let response = build_response(0, &status);
+        let response = build_response(0, &status).unwrap();
         let ntp_off = i32::from_be_bytes([response[56], response[57], response[58], response[59]]);
         assert_eq!(ntp_off, -42000);
     }
 
     #[test]
     fn test_build_response_phase_clamp() {
-        let mut status = SyncStatus::default();
-        // Exceeds i16 range — should be clamped to i16::MAX
-        status.accumulated_phase_us = 50000.0;
+        let status = SyncStatus {
+            // Exceeds i16 range — should be clamped to i16::MAX
+            accumulated_phase_us: 50000.0,
+            ..Default::default()
+        };
 
-        let response = build_response(0, &status);
+        let response = build_response(0, &status).unwrap();
         let phase = i16::from_be_bytes([response[60], response[61]]);
         assert_eq!(phase, i16::MAX); // 32767
     }
+
+    #[test]
+    fn test_build_response_raw_and_smoothed_offset() {
+        let status = SyncStatus {
+            offset_ns: 1_200,
+            raw_offset_ns: -3_400,
+            smoothed_offset_ns: 1_050,
+            ..Default::default()
+        };
+
+        let response = build_response(0, &status).unwrap();
+        let window = i64::from_be_bytes(response[24..32].try_into().unwrap());
+        let raw = i64::from_be_bytes(response[64..72].try_into().unwrap());
+        let smoothed = i64::from_be_bytes(response[72..80].try_into().unwrap());
+        assert_eq!(window, 1_200);
+        assert_eq!(raw, -3_400);
+        assert_eq!(smoothed, 1_050);
+    }
+
+    fn request(request_id: u32) -> Vec<u8> {
+        let mut req = REQUEST_MAGIC.to_be_bytes().to_vec();
+        req.extend_from_slice(&request_id.to_be_bytes());
+        req
+    }
+
+    fn extended_request(request_id: u32) -> Vec<u8> {
+        let mut req = EXTENDED_REQUEST_MAGIC.to_be_bytes().to_vec();
+        req.extend_from_slice(&request_id.to_be_bytes());
+        req
+    }
+
+    fn signed_request(request_id: u32, key: &[u8]) -> Vec<u8> {
+        let mut req = request(request_id);
+        let tag = auth_tag(key, &req);
+        req.extend_from_slice(&tag);
+        req
+    }
+
+    #[test]
+    fn test_answer_unauthenticated_mode() {
+        let status = SyncStatus::default();
+        let response = answer(&request(7), None, &status).expect("legacy query answered");
+        assert_eq!(response.len(), RESPONSE_SIZE);
+        assert_eq!(&response[4..8], &7u32.to_be_bytes());
+
+        let mut bad_magic = request(7);
+        bad_magic[0] = b'X';
+        assert!(answer(&bad_magic, None, &status).is_none());
+        assert!(answer(&request(7)[..6], None, &status).is_none());
+    }
+
+    #[test]
+    fn test_extended_response_only_on_request() {
+        let status = SyncStatus {
+            raw_offset_ns: -3_400,
+            ..Default::default()
+        };
+        // 64-byte readers (Windows recvfrom fails on a longer datagram)
+        assert_eq!(answer(&request(7), None, &status).unwrap().len(), 64);
+
+        let response = answer(&extended_request(7), None, &status).unwrap();
+        assert_eq!(response.len(), EXTENDED_RESPONSE_SIZE);
+        assert_eq!(&response[0..4], b"DSYR");
+        assert_eq!(&response[64..72], &(-3_400i64).to_be_bytes());
+
+        let key = b"studio-shared-secret";
+        let mut signed = extended_request(42);
+        let tag = auth_tag(key, &signed);
+        signed.extend_from_slice(&tag);
+        let response = answer(&signed, Some(key), &status).unwrap();
+        assert_eq!(response.len(), EXTENDED_RESPONSE_SIZE + TAG_SIZE);
+        assert!(parse_response(&response, 42, Some(key)).is_ok());
+    }
+
+    #[test]
+    fn test_answer_accepts_correct_key() {
+        let key = b"studio-shared-secret";
+        let response = answer(&signed_request(42, key), Some(key), &SyncStatus::default())
+            .expect("tagged query answered");
+
+        assert_eq!(response.len(), RESPONSE_SIZE + TAG_SIZE);
+        assert_eq!(&response[4..8], &42u32.to_be_bytes());
+        let (body, tag) = response.split_at(RESPONSE_SIZE);
+        assert_eq!(tag, auth_tag(key, body), "response tag covers the body");
+    }
+
+    #[test]
+    fn test_answer_rejects_wrong_or_missing_key() {
+        let key = b"studio-shared-secret";
+        let status = SyncStatus::default();
+
+        assert!(answer(&signed_request(42, b"other-secret"), Some(key), &status).is_none());
+        assert!(answer(&request(42), Some(key), &status).is_none());
+
+        // A valid tag does not carry over to a different request ID
+        let mut replayed = signed_request(42, key);
+        replayed[7] ^= 1;
+        assert!(answer(&replayed, Some(key), &status).is_none());
+    }
+
+    #[test]
+    fn test_parse_response_fields() {
+        let status = SyncStatus {
+            offset_ns: -12345,
+            smoothed_rate_ppm: 1.5,
+            freq_adj_ppm: -0.75,
+            mode: "NANO".to_string(),
+            is_locked: true,
+            gm_uuid: Some([0x00, 0x1D, 0xC1, 0xAB, 0xCD, 0xEF]),
+            ..Default::default()
+        };
+        let response = build_response(9, &status).unwrap();
+
+        let result = parse_response(&response, 9, None).unwrap();
+        assert_eq!(result.ptp_offset_ns, -12345);
+        assert_eq!(result.drift_ppm, 1.5);
+        assert_eq!(result.freq_adj_ppm, -0.75);
+        assert_eq!(result.mode, "NANO");
+        assert!(result.is_locked);
+        assert_eq!(result.gm_uuid, Some([0x00, 0x1D, 0xC1, 0xAB, 0xCD, 0xEF]));
+        assert!(result.monotonic_freq > 0);
+
+        // The 64-byte response parses the same
+        let legacy = parse_response(&response[..RESPONSE_SIZE], 9, None).unwrap();
+        assert_eq!(legacy.ptp_offset_ns, -12345);
+        assert_eq!(
+            parse_response(&build_response(9, &SyncStatus::default()).unwrap(), 9, None)
+                .unwrap()
+                .gm_uuid,
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_response_rejects_mismatches() {
+        let response = build_response(9, &SyncStatus::default()).unwrap();
+        assert!(
+            parse_response(&response, 10, None).is_err(),
+            "stale request ID"
+        );
+        assert!(parse_response(&response[..40], 9, None).is_err(), "short");
+
+        let key = b"studio-shared-secret";
+        let tagged = answer(&signed_request(9, key), Some(key), &SyncStatus::default()).unwrap();
+        assert!(parse_response(&tagged, 9, Some(key)).is_ok());
+        assert!(parse_response(&tagged, 9, Some(b"other-secret")).is_err());
+        assert!(parse_response(&response, 9, Some(key)).is_err(), "untagged");
+    }
+
+    #[test]
+    fn test_client_queries_server() {
+        let server = TimeServer {
+            socket: UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap(),
+            auth_key: None,
+        };
+        let addr = server.socket.local_addr().unwrap();
+        let status = Arc::new(RwLock::new(SyncStatus {
+            mode: "LOCK".to_string(),
+            ..Default::default()
+        }));
+        let handle = std::thread::spawn(move || {
+            server.socket.set_nonblocking(false).unwrap();
+            let mut buf = [0u8; 64];
+            let (size, src) = server.socket.recv_from(&mut buf).unwrap();
+            let status = status.read().unwrap().clone();
+            let response = answer(&buf[..size], None, &status).unwrap();
+            server.socket.send_to(&response, src).unwrap();
+        });
+
+        let result = TimeClient::new()
+            .unwrap()
+            .query(addr, Duration::from_secs(5))
+            .unwrap();
+        handle.join().unwrap();
+
+        assert_eq!(result.mode, "LOCK");
+        assert!(result.round_trip > Duration::ZERO);
+        // Same machine, same clock
+        assert!(result.offset_from_local_ns().abs() < 1_000_000_000);
+    }
 }
@@ -7,6 +7,33 @@ pub trait NtpSource {
     fn get_offset(&self) -> Result<(Duration, i8)>;
 }
 
+impl<T: NtpSource + ?Sized> NtpSource for Box<T> {
+    fn get_offset(&self) -> Result<(Duration, i8)> {
+        (**self).get_offset()
+    }
+}
+
+/// Error reported by `NoopNtpSource` - NTP is deliberately not configured, not failing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NtpNotConfigured;
+
+impl std::fmt::Display for NtpNotConfigured {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no NTP configured")
+    }
+}
+
+impl std::error::Error for NtpNotConfigured {}
+
+/// NTP source for PTP-only operation: never queries a server.
+pub struct NoopNtpSource;
+
+impl NtpSource for NoopNtpSource {
+    fn get_offset(&self) -> Result<(Duration, i8)> {
+        Err(NtpNotConfigured.into())
+    }
+}
+
 #[cfg_attr(test, mockall::automock)]
 pub trait PtpNetwork {
     /// Receive a packet. Returns Ok(Some((data, len, timestamp, source_ip))) if packet received.
@@ -21,4 +48,38 @@ pub trait PtpNetwork {
     fn reset(&mut self) -> Result<()> {
         Ok(())
     }
+
+    /// Send a PTP event message (Delay_Req) to the multicast group on port 319.
+    /// Default impl: receive-only backend, the controller then assumes zero path delay.
+    fn send_packet(&mut self, _data: &[u8]) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "sending is not supported by this network backend"
+        ))
+    }
+
+    /// Whether receive timestamps currently come from NIC hardware. Default impl: never.
+    fn hw_timestamping(&self) -> bool {
+        false
+    }
+
+    /// Block until a packet is ready to receive or `timeout` passes; true if one is.
+    /// Default impl: nothing to wait on, so sleep (at most 1ms, the old loop pace)
+    /// and report that a packet may be ready.
+    fn poll_readable(&mut self, timeout: Duration) -> Result<bool> {
+        std::thread::sleep(timeout.min(Duration::from_millis(1)));
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noop_ntp_source_reports_not_configured() {
+        let ntp: Box<dyn NtpSource> = Box::new(NoopNtpSource);
+        let err = ntp.get_offset().unwrap_err();
+        assert!(err.is::<NtpNotConfigured>());
+        assert_eq!(err.to_string(), "no NTP configured");
+    }
 }
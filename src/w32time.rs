@@ -0,0 +1,121 @@
+//! Windows Time service (w32time) detection through the Service Control Manager.
+//!
+//! w32time disciplines the same system clock. Running next to DanteSync the two
+//! overwrite each other's adjustments, which shows up as read-back mismatches in
+//! `WindowsClock::adjust_frequency`. The installer disables the service; this
+//! catches machines where it was re-enabled or started by a trigger.
+
+use anyhow::{anyhow, Context, Result};
+use log::{error, info, warn};
+use std::ffi::OsStr;
+use std::thread;
+use std::time::{Duration, Instant};
+use windows_service::service::{Service, ServiceAccess, ServiceState};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+
+pub const SERVICE_NAME: &str = "w32time";
+
+/// How long a stop or start may take before we give up waiting.
+const STATE_CHANGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn service_manager() -> Result<ServiceManager> {
+    ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .context("connecting to the Service Control Manager")
+}
+
+/// Current state of w32time, None if the service is not installed.
+pub fn state() -> Result<Option<ServiceState>> {
+    // Not installed (e.g. stripped images) counts as not competing
+    let Ok(service) = service_manager()?.open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS)
+    else {
+        return Ok(None);
+    };
+    Ok(Some(service.query_status()?.current_state))
+}
+
+/// Whether w32time is running (or about to be) and competing for the clock.
+pub fn is_running() -> Result<bool> {
+    Ok(matches!(
+        state()?,
+        Some(ServiceState::Running | ServiceState::StartPending | ServiceState::ContinuePending)
+    ))
+}
+
+fn wait_for(service: &Service, wanted: ServiceState) -> Result<()> {
+    let start = Instant::now();
+    loop {
+        let state = service.query_status()?.current_state;
+        if state == wanted {
+            return Ok(());
+        }
+        if start.elapsed() > STATE_CHANGE_TIMEOUT {
+            return Err(anyhow!(
+                "{} still {:?} after {:?}",
+                SERVICE_NAME,
+                state,
+                STATE_CHANGE_TIMEOUT
+            ));
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// w32time stopped for this session: started again when dropped.
+pub struct StoppedW32Time {
+    service: Service,
+}
+
+impl Drop for StoppedW32Time {
+    fn drop(&mut self) {
+        match self
+            .service
+            .start::<&OsStr>(&[])
+            .map_err(anyhow::Error::from)
+            .and_then(|()| wait_for(&self.service, ServiceState::Running))
+        {
+            Ok(()) => info!("[W32Time] Windows Time service restarted"),
+            Err(e) => error!(
+                "[W32Time] Failed to restart the Windows Time service: {:#}",
+                e
+            ),
+        }
+    }
+}
+
+/// Stop w32time for this session (`--stop-w32time`). None if it was not running.
+pub fn stop_for_session() -> Result<Option<StoppedW32Time>> {
+    if !is_running()? {
+        return Ok(None);
+    }
+    let service = service_manager()?
+        .open_service(
+            SERVICE_NAME,
+            ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::START,
+        )
+        .with_context(|| format!("opening service {}", SERVICE_NAME))?;
+    service
+        .stop()
+        .with_context(|| format!("stopping {}", SERVICE_NAME))?;
+    wait_for(&service, ServiceState::Stopped)?;
+    info!("[W32Time] Windows Time service stopped for this session (restarted on exit)");
+    Ok(Some(StoppedW32Time { service }))
+}
+
+/// Startup check: stop w32time if asked to, otherwise warn while it runs, or
+/// fail with `require_exclusive`. The returned guard restarts a stopped service.
+pub fn claim_clock(stop: bool, require_exclusive: bool) -> Result<Option<StoppedW32Time>> {
+    let stopped = if stop { stop_for_session()? } else { None };
+    if stopped.is_none() && is_running()? {
+        if require_exclusive {
+            return Err(anyhow!(
+                "the Windows Time service (w32time) is running and would fight DanteSync for the clock \
+                 (--require-exclusive); stop it or pass --stop-w32time"
+            ));
+        }
+        warn!("[W32Time] ⚠ Windows Time service is RUNNING and competes for the system clock!");
+        warn!(
+            "[W32Time]   Disable it (sc config w32time start= disabled) or run with --stop-w32time"
+        );
+    }
+    Ok(stopped)
+}
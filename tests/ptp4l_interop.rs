@@ -0,0 +1,150 @@
+//! Interop test against linuxptp's `ptp4l` as a reference grandmaster.
+//!
+//! Unit and simulation tests only feed hand-crafted bytes. This test runs the real
+//! parser and servo against packets from an independent implementation.
+//!
+//! Ignored by default: it needs root, `ptp4l` in PATH and a veth pair, so CI cannot
+//! run it. Setup (once, as root):
+//!
+//! ```bash
+//! ip link add veth-gm type veth peer name veth-ds
+//! ip addr add 10.99.0.1/24 dev veth-gm
+//! ip addr add 10.99.0.2/24 dev veth-ds
+//! ip link set veth-gm up
+//! ip link set veth-ds up
+//! ```
+//!
+//! Run:
+//!
+//! ```bash
+//! sudo -E cargo test --test ptp4l_interop -- --ignored --nocapture
+//! ```
+//!
+//! `ptp4l` runs master-only with software timestamps on `veth-gm` and the controller
+//! receives on `veth-ds`. Override with `PTP4L_GM_IFACE` and `PTP4L_SLAVE_IP`.
+//! Both ends share CLOCK_REALTIME, so the drift rate must converge to ~0 and lock.
+//! The test never touches the system clock (frequency writes go to a recording clock).
+//!
+//! Note: `ptp4l` only speaks PTPv2 while Dante uses PTPv1, so this test exercises
+//! the controller's PTPv2 Sync/Follow_Up path, not the Dante one.
+#![cfg(target_os = "linux")]
+
+use anyhow::Result;
+use dantesync::clock::SystemClock;
+use dantesync::config::SystemConfig;
+use dantesync::controller::PtpController;
+use dantesync::net;
+use dantesync::ptp::{PTP_EVENT_PORT, PTP_GENERAL_PORT, PTP_MULTICAST_ADDR};
+use dantesync::status::SyncStatus;
+use dantesync::traits::{NoopNtpSource, PtpNetwork};
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Kills ptp4l when the test ends (pass or panic).
+struct Ptp4l(Child);
+
+impl Drop for Ptp4l {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn spawn_ptp4l(iface: &str) -> Ptp4l {
+    let child = Command::new("ptp4l")
+        .args(["-i", iface, "-S", "-4", "-m", "--masterOnly", "1"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .expect("failed to start ptp4l (installed and in PATH?)");
+    Ptp4l(child)
+}
+
+/// Frequency writes are recorded, never applied to the real clock.
+#[derive(Default)]
+struct RecordingClock {
+    last_factor: Option<f64>,
+}
+
+impl SystemClock for RecordingClock {
+    fn adjust_frequency(&mut self, factor: f64) -> Result<()> {
+        self.last_factor = Some(factor);
+        Ok(())
+    }
+
+    fn step_clock(&mut self, _offset: Duration, _sign: i8) -> Result<()> {
+        panic!("interop test must never step the clock");
+    }
+}
+
+struct UdpPtpNetwork {
+    sock_event: UdpSocket,
+    sock_general: UdpSocket,
+    packets: Arc<RwLock<u64>>,
+}
+
+impl PtpNetwork for UdpPtpNetwork {
+    fn recv_packet(&mut self) -> Result<Option<(Vec<u8>, usize, SystemTime, Option<Ipv4Addr>)>> {
+        let mut buf = [0u8; 2048];
+        for sock in [&self.sock_event, &self.sock_general] {
+            if let Some((size, ts, source_ip)) = net::recv_with_timestamp(sock, &mut buf)? {
+                *self.packets.write().unwrap() += 1;
+                return Ok(Some((buf[..size].to_vec(), size, ts, source_ip)));
+            }
+        }
+        std::thread::sleep(Duration::from_millis(1));
+        Ok(None)
+    }
+}
+
+#[test]
+#[ignore = "needs root, ptp4l and a veth pair (see module docs)"]
+fn test_locks_to_ptp4l_grandmaster() {
+    let gm_iface = std::env::var("PTP4L_GM_IFACE").unwrap_or_else(|_| "veth-gm".to_string());
+    let slave_ip: Ipv4Addr = std::env::var("PTP4L_SLAVE_IP")
+        .unwrap_or_else(|_| "10.99.0.2".to_string())
+        .parse()
+        .expect("PTP4L_SLAVE_IP must be an IPv4 address");
+
+    let packets = Arc::new(RwLock::new(0u64));
+    let group = IpAddr::from(PTP_MULTICAST_ADDR);
+    let join = |port| net::create_multicast_socket(port, group, slave_ip, 0, &Default::default());
+    let network = UdpPtpNetwork {
+        sock_event: join(PTP_EVENT_PORT).unwrap(),
+        sock_general: join(PTP_GENERAL_PORT).unwrap(),
+        packets: packets.clone(),
+    };
+    let _ptp4l = spawn_ptp4l(&gm_iface);
+
+    let status = Arc::new(RwLock::new(SyncStatus::default()));
+    let mut controller = PtpController::new(
+        RecordingClock::default(),
+        network,
+        NoopNtpSource,
+        status.clone(),
+        SystemConfig::default(),
+    );
+
+    let start = Instant::now();
+    while start.elapsed() < LOCK_TIMEOUT && !status.read().unwrap().is_locked {
+        controller.process_loop_iteration().unwrap();
+    }
+
+    let packets = *packets.read().unwrap();
+    let status = status.read().unwrap();
+    assert!(packets > 0, "No packets from ptp4l - check the veth setup");
+    assert!(
+        status.is_locked,
+        "Did not lock to ptp4l within {:?} ({} packets, mode {}, rate {:+.2}us/s)",
+        LOCK_TIMEOUT, packets, status.mode, status.smoothed_rate_ppm
+    );
+    assert!(
+        status.smoothed_rate_ppm.abs() < 5.0,
+        "Shared clock must converge to ~0 drift, got {:+.2}us/s",
+        status.smoothed_rate_ppm
+    );
+}
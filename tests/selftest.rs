@@ -0,0 +1,28 @@
+use dantesync::config::SystemConfig;
+use dantesync::selftest::{self, SelftestParams};
+use std::time::Duration;
+
+// Real time: the servo measures sample intervals with Instant (~45s to lock).
+#[test]
+fn selftest_recovers_injected_drift_and_offset() {
+    let params = SelftestParams {
+        drift_ppm: -40.0,
+        ntp_offset_ns: -180_000_000,
+        ..Default::default()
+    };
+    let report = selftest::run(SystemConfig::default(), &params).unwrap();
+    assert!(report.passed, "{}", report.summary());
+    assert_eq!(report.stepped_ns, -180_000_000);
+}
+
+#[test]
+fn selftest_fails_when_not_locked_in_time() {
+    let params = SelftestParams {
+        timeout: Duration::from_secs(2),
+        ..Default::default()
+    };
+    let report = selftest::run(SystemConfig::default(), &params).unwrap();
+    assert!(!report.passed);
+    assert!(!report.locked);
+    assert!(report.summary().starts_with("FAIL"));
+}